@@ -69,6 +69,23 @@ fn lookup_accent(key: &str) -> Option<String> {
         .and_then(|jis_code| jis_to_unicode(jis_code))
 }
 
+/// JISコードからアクセント分解記法を逆引き
+///
+/// # Examples
+///
+/// ```
+/// use aozora_core::accent::accent_notation;
+///
+/// assert_eq!(accent_notation("1-09-24"), Some("A'"));
+/// assert_eq!(accent_notation("1-01-01"), None);
+/// ```
+pub fn accent_notation(jis_code: &str) -> Option<&'static str> {
+    ACCENT_TABLE
+        .iter()
+        .find(|(_, code)| **code == jis_code)
+        .map(|(key, _)| *key)
+}
+
 /// アクセント変換結果（1文字分）
 #[derive(Debug, Clone, PartialEq)]
 pub enum AccentPart {
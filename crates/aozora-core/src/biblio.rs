@@ -0,0 +1,372 @@
+//! 底本情報の解析
+//!
+//! 文書末尾の底本情報（`底本：` 以降）から、底本・親本・初出の書誌と、
+//! 入力者・校正者、ファイルの作成日・修正日、注記を取り出します。
+//!
+//! 青空文庫の底本情報は次のような形式です。全角空白で始まる行は直前の項目の続きです。
+//!
+//! ```text
+//! 底本：「夏目漱石全集１」ちくま文庫、筑摩書房
+//! 　　　1987（昭和62）年9月29日第1刷発行
+//! 底本の親本：「筑摩全集類聚版　夏目漱石全集」筑摩書房
+//! 　　　1971（昭和46）年4月
+//! 初出：「ホトトギス」
+//! 　　　1905（明治38）年1月
+//! ※底本は、物を数える際や地名などに用いる「ヶ」（区点番号5-86）を、大振りにつくっています。
+//! 入力：柴田卓治
+//! 校正：渡部峰子、おのしげひこ
+//! 1999年9月17日作成
+//! 2004年3月1日修正
+//! 青空文庫作成ファイル：
+//! このファイルは、インターネットの図書館、青空文庫（https://www.aozora.gr.jp/）で作られました。
+//! ```
+
+use crate::document::bibliographical_line_range;
+
+/// 青空文庫の定型の結び（この行以降は解析しない）
+const AOZORA_FOOTER: &str = "青空文庫作成ファイル：";
+
+/// 底本情報
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BiblioInfo {
+    /// 底本
+    pub base_texts: Vec<BiblioSource>,
+    /// 底本の親本
+    pub parent_texts: Vec<BiblioSource>,
+    /// 初出
+    pub first_publications: Vec<BiblioSource>,
+    /// 入力者
+    pub typists: Vec<String>,
+    /// 校正者
+    pub proofreaders: Vec<String>,
+    /// ファイルの作成日（例: `1999年9月17日`）
+    pub created: Option<String>,
+    /// ファイルの修正日
+    pub revised: Vec<String>,
+    /// 注記（`※` で始まる段落）
+    pub notes: Vec<String>,
+    /// 上のどれにも当てはまらない行
+    pub others: Vec<String>,
+}
+
+/// 底本・親本・初出の書誌
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BiblioSource {
+    /// 記載のまま（日付の行を除き、続きの行は改行でつなぐ）
+    pub description: String,
+    /// 書名（最初の `「…」` の内側）
+    pub title: Option<String>,
+    /// 叢書名（書名と出版社の間、例: `ちくま文庫`）
+    pub series: Option<String>,
+    /// 出版社
+    pub publisher: Option<String>,
+    /// 発行・印刷などの日付
+    pub dates: Vec<PublicationDate>,
+}
+
+/// 発行・印刷などの日付
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PublicationDate {
+    /// 日付（例: `1987（昭和62）年9月29日`）
+    pub date: String,
+    /// 西暦年
+    pub year: Option<u32>,
+    /// 日付に続く版・刷の記載（例: `第1刷発行`）
+    pub event: String,
+}
+
+/// 解析中の項目
+enum Field {
+    BaseText,
+    ParentText,
+    FirstPublication,
+    Note,
+    Other,
+}
+
+/// 文書から底本情報を解析
+///
+/// `lines` は文書全体の行でも、[`crate::document::extract_bibliographical_lines`]
+/// が返す底本情報の行だけでも構いません。
+///
+/// # Examples
+///
+/// ```
+/// use aozora_core::biblio::extract_biblio_info;
+///
+/// let lines = vec![
+///     "本文",
+///     "底本：「夏目漱石全集１」ちくま文庫、筑摩書房",
+///     "　　　1987（昭和62）年9月29日第1刷発行",
+///     "入力：柴田卓治",
+///     "1999年9月17日作成",
+/// ];
+/// let info = extract_biblio_info(&lines);
+/// let base = &info.base_texts[0];
+/// assert_eq!(base.title.as_deref(), Some("夏目漱石全集１"));
+/// assert_eq!(base.publisher.as_deref(), Some("筑摩書房"));
+/// assert_eq!(base.dates[0].year, Some(1987));
+/// assert_eq!(info.typists, vec!["柴田卓治"]);
+/// assert_eq!(info.created.as_deref(), Some("1999年9月17日"));
+/// ```
+pub fn extract_biblio_info(lines: &[&str]) -> BiblioInfo {
+    let mut info = BiblioInfo::default();
+    let mut field = Field::Other;
+
+    for line in &lines[bibliographical_line_range(lines)] {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if line.starts_with(AOZORA_FOOTER) {
+            break;
+        }
+
+        // 全角空白で始まる行は直前の項目の続き
+        if let Some(rest) = line.strip_prefix('　') {
+            let rest = rest.trim_start_matches('　');
+            match field {
+                Field::BaseText => push_continuation(info.base_texts.last_mut(), rest),
+                Field::ParentText => push_continuation(info.parent_texts.last_mut(), rest),
+                Field::FirstPublication => {
+                    push_continuation(info.first_publications.last_mut(), rest)
+                }
+                Field::Note => push_line(info.notes.last_mut(), rest),
+                Field::Other => info.others.push(rest.to_string()),
+            }
+            continue;
+        }
+
+        field = Field::Other;
+        if let Some(rest) = line.strip_prefix("底本の親本：") {
+            info.parent_texts.push(parse_source(rest));
+            field = Field::ParentText;
+        } else if let Some(rest) = line.strip_prefix("底本：") {
+            info.base_texts.push(parse_source(rest));
+            field = Field::BaseText;
+        } else if let Some(rest) = line.strip_prefix("初出：") {
+            info.first_publications.push(parse_source(rest));
+            field = Field::FirstPublication;
+        } else if let Some(rest) = line.strip_prefix("入力：") {
+            info.typists.extend(split_names(rest));
+        } else if let Some(rest) = line.strip_prefix("校正：") {
+            info.proofreaders.extend(split_names(rest));
+        } else if let Some(date) = line.strip_suffix("作成").filter(|d| is_date(d)) {
+            info.created = Some(date.to_string());
+        } else if let Some(date) = line.strip_suffix("修正").filter(|d| is_date(d)) {
+            info.revised.push(date.to_string());
+        } else if line.starts_with('※') {
+            info.notes.push(line.to_string());
+            field = Field::Note;
+        } else {
+            info.others.push(line.to_string());
+        }
+    }
+
+    info
+}
+
+/// 書誌の1行目を解析
+fn parse_source(text: &str) -> BiblioSource {
+    let mut source = BiblioSource {
+        description: text.to_string(),
+        ..Default::default()
+    };
+
+    let rest = match (text.find('「'), text.find('」')) {
+        (Some(start), Some(end)) if start < end => {
+            source.title = Some(text[start + '「'.len_utf8()..end].to_string());
+            &text[end + '」'.len_utf8()..]
+        }
+        _ => text,
+    };
+
+    // 書名の後に日付が続く（初出：「雑誌」1905（明治38）年1月）
+    let (rest, date) = match rest.find(|c: char| c.is_ascii_digit()) {
+        Some(pos) => (&rest[..pos], parse_date(&rest[pos..])),
+        None => (rest, None),
+    };
+    let rest = rest.trim_matches(|c: char| c == '、' || c.is_whitespace());
+    if !rest.is_empty() {
+        match rest.rsplit_once('、') {
+            Some((series, publisher)) => {
+                source.series = Some(series.to_string());
+                source.publisher = Some(publisher.to_string());
+            }
+            None => source.publisher = Some(rest.to_string()),
+        }
+    }
+    source.dates.extend(date);
+    source
+}
+
+/// 書誌の続きの行を追加
+fn push_continuation(source: Option<&mut BiblioSource>, text: &str) {
+    let Some(source) = source else {
+        return;
+    };
+    match parse_date(text) {
+        Some(date) => source.dates.push(date),
+        None => {
+            source.description.push('\n');
+            source.description.push_str(text);
+        }
+    }
+}
+
+/// 段落に行を追加
+fn push_line(paragraph: Option<&mut String>, text: &str) {
+    if let Some(paragraph) = paragraph {
+        paragraph.push('\n');
+        paragraph.push_str(text);
+    }
+}
+
+/// 日付で始まる記載を日付と続きに分ける
+///
+/// 例: `1987（昭和62）年9月29日第1刷発行` → (`1987（昭和62）年9月29日`, `第1刷発行`)
+fn parse_date(text: &str) -> Option<PublicationDate> {
+    if !text.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    // 数字、年月日、括弧内（和暦）、範囲の「～」までを日付とする
+    let mut end = 0;
+    let mut in_paren = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            '（' | '(' => in_paren = true,
+            '）' | ')' => in_paren = false,
+            _ if in_paren => {}
+            '年' | '月' | '日' | '～' | '〜' => {}
+            _ if c.is_ascii_digit() => {}
+            _ => break,
+        }
+        end = i + c.len_utf8();
+    }
+
+    let date = &text[..end];
+    if !date.contains('年') {
+        return None;
+    }
+    let year = date
+        .split(|c: char| !c.is_ascii_digit())
+        .next()
+        .and_then(|y| y.parse().ok());
+    Some(PublicationDate {
+        date: date.to_string(),
+        year,
+        event: text[end..].to_string(),
+    })
+}
+
+/// `1999年9月17日` 形式の日付かどうか
+fn is_date(text: &str) -> bool {
+    !text.is_empty()
+        && text.contains('年')
+        && text
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '年' | '月' | '日'))
+}
+
+/// 「、」区切りの人名を分ける
+fn split_names(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split('、')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &[&str] = &[
+        "底本：「夏目漱石全集１」ちくま文庫、筑摩書房",
+        "　　　1987（昭和62）年9月29日第1刷発行",
+        "　　　1995（平成7）年2月20日第7刷発行",
+        "底本の親本：「筑摩全集類聚版　夏目漱石全集」筑摩書房",
+        "　　　1971（昭和46）年4月～1972（昭和47）年1月",
+        "初出：「ホトトギス」1905（明治38）年1月",
+        "※底本は、物を数える際や地名などに用いる「ヶ」を、大振りにつくっています。",
+        "　　ルビは新仮名遣いに改めました。",
+        "入力：柴田卓治",
+        "校正：渡部峰子（一）、おのしげひこ（二～九）",
+        "1999年9月17日作成",
+        "2004年3月1日修正",
+        "2011年5月19日修正",
+        "青空文庫作成ファイル：",
+        "このファイルは、インターネットの図書館、青空文庫で作られました。",
+    ];
+
+    #[test]
+    fn test_base_text() {
+        let info = extract_biblio_info(SAMPLE);
+        assert_eq!(info.base_texts.len(), 1);
+        let base = &info.base_texts[0];
+        assert_eq!(base.description, "「夏目漱石全集１」ちくま文庫、筑摩書房");
+        assert_eq!(base.title.as_deref(), Some("夏目漱石全集１"));
+        assert_eq!(base.series.as_deref(), Some("ちくま文庫"));
+        assert_eq!(base.publisher.as_deref(), Some("筑摩書房"));
+        assert_eq!(
+            base.dates,
+            vec![
+                PublicationDate {
+                    date: "1987（昭和62）年9月29日".to_string(),
+                    year: Some(1987),
+                    event: "第1刷発行".to_string(),
+                },
+                PublicationDate {
+                    date: "1995（平成7）年2月20日".to_string(),
+                    year: Some(1995),
+                    event: "第7刷発行".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parent_text_and_first_publication() {
+        let info = extract_biblio_info(SAMPLE);
+        let parent = &info.parent_texts[0];
+        assert_eq!(
+            parent.title.as_deref(),
+            Some("筑摩全集類聚版　夏目漱石全集")
+        );
+        assert_eq!(parent.series, None);
+        assert_eq!(parent.publisher.as_deref(), Some("筑摩書房"));
+        assert_eq!(
+            parent.dates[0].date,
+            "1971（昭和46）年4月～1972（昭和47）年1月"
+        );
+        assert_eq!(parent.dates[0].event, "");
+
+        let first = &info.first_publications[0];
+        assert_eq!(first.title.as_deref(), Some("ホトトギス"));
+        assert_eq!(first.publisher, None);
+        assert_eq!(first.dates[0].year, Some(1905));
+    }
+
+    #[test]
+    fn test_people_dates_and_notes() {
+        let info = extract_biblio_info(SAMPLE);
+        assert_eq!(info.typists, vec!["柴田卓治"]);
+        assert_eq!(
+            info.proofreaders,
+            vec!["渡部峰子（一）", "おのしげひこ（二～九）"]
+        );
+        assert_eq!(info.created.as_deref(), Some("1999年9月17日"));
+        assert_eq!(info.revised, vec!["2004年3月1日", "2011年5月19日"]);
+        assert_eq!(info.notes.len(), 1);
+        assert!(info.notes[0].ends_with("\nルビは新仮名遣いに改めました。"));
+        // 青空文庫の定型文は含めない
+        assert!(info.others.is_empty());
+    }
+
+    #[test]
+    fn test_no_bibliography() {
+        assert_eq!(extract_biblio_info(&["本文"]), BiblioInfo::default());
+    }
+}
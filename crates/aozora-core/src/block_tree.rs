@@ -0,0 +1,443 @@
+//! ブロックの入れ子構造
+//!
+//! パーサーが出力する平坦なノード列では、ブロックは `Node::BlockStart` と
+//! `Node::BlockEnd` の目印として表現されます。このモジュールは目印の対応を取り、
+//! 子ノードを持つブロックの木構造に変換します。
+//!
+//! # 対応の規則
+//!
+//! - 「ここから」で始まるブロック（`is_block`）は、対応する終わりの注記まで続きます。
+//! - それ以外（行単位の字下げ・地付き、太字、見出しなど）は行末で閉じられます。
+//! - 字下げ・地付き・ぶら下げのブロックが開いている間に同種のブロックが始まると、
+//!   前のブロックは暗黙に閉じられます（字下げとぶら下げは同種として扱います）。
+//! - 字下げの終わりは、ぶら下げも閉じます。
+//! - 範囲が重なった場合（内側のブロックより先に外側のブロックが閉じられた場合）、
+//!   内側のブロックはそこで分割され、外側のブロックの後ろで続きとして再開されます。
+//! - 対応する開始のない終わりの注記は、そのままノードとして残します。
+//! - 文書の終わりまで閉じられなかったブロックは、そこで閉じられます。
+
+use crate::document::DocumentLine;
+use crate::node::{BlockParams, BlockType, Node};
+use crate::span::{Span, Spanned};
+
+/// 木構造のノード
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TreeNode {
+    /// ブロック以外のノード（対応の取れない終わりの注記を含む）
+    Node(Spanned<Node>),
+    /// ブロック
+    Block(Block),
+    /// 行の終わり
+    LineBreak {
+        /// 終わった行の行番号（0始まり）
+        line: usize,
+    },
+}
+
+/// ブロックの閉じられ方
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlockClose {
+    /// 終わりの注記で閉じられた
+    Explicit,
+    /// 行末で閉じられた（行単位・インラインのブロック）
+    LineEnd,
+    /// 同種のブロックの開始で暗黙に閉じられた
+    Implicit,
+    /// 外側のブロックの終わりで分割された（続きは次のブロック）
+    Interrupted,
+    /// 文書の終わりまで閉じられなかった
+    Unclosed,
+}
+
+/// 子ノードを持つブロック
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Block {
+    /// ブロックの種類
+    pub block_type: BlockType,
+    /// 開始注記のパラメータ
+    pub params: BlockParams,
+    /// 終了注記のパラメータ（終わりの注記で閉じられた場合）
+    pub end_params: Option<BlockParams>,
+    /// 子ノード
+    pub children: Vec<TreeNode>,
+    /// 開始注記の範囲
+    pub start_span: Span,
+    /// 終了注記の範囲（終わりの注記で閉じられた場合）
+    pub end_span: Option<Span>,
+    /// 閉じられ方
+    pub close: BlockClose,
+    /// 分割されたブロックの続きかどうか
+    pub continued: bool,
+}
+
+impl Block {
+    /// 行末で閉じられるブロックかどうか
+    fn is_line_scoped(&self) -> bool {
+        !self.params.is_block
+    }
+
+    /// 終わりの注記がこのブロックに対応するかどうか
+    fn matches_end(&self, block_type: BlockType, params: &BlockParams) -> bool {
+        if self.block_type == block_type {
+            // 装飾は種類が指定されていれば一致するものだけを閉じる
+            return match (&self.params.style_type, &params.style_type) {
+                (Some(open), Some(close)) => open == close,
+                _ => true,
+            };
+        }
+        // 字下げ終わりでぶら下げも閉じる
+        block_type == BlockType::Jisage && self.block_type == BlockType::Burasage
+    }
+
+    /// 新しいブロックの開始で暗黙に閉じられるかどうか
+    fn is_closed_by(&self, new_type: BlockType) -> bool {
+        if !self.params.is_block {
+            return false;
+        }
+        match new_type {
+            BlockType::Jisage | BlockType::Chitsuki => {
+                self.block_type == new_type || self.block_type == BlockType::Burasage
+            }
+            BlockType::Burasage => {
+                matches!(self.block_type, BlockType::Jisage | BlockType::Burasage)
+            }
+            _ => false,
+        }
+    }
+
+    /// 分割されたブロックの続きを作成
+    fn continuation(&self) -> Block {
+        Block {
+            block_type: self.block_type,
+            params: self.params.clone(),
+            end_params: None,
+            children: Vec::new(),
+            start_span: self.start_span,
+            end_span: None,
+            close: BlockClose::Unclosed,
+            continued: true,
+        }
+    }
+}
+
+/// ブロックの木構造を組み立てる
+///
+/// ノードを行ごとに渡し、最後に [`BlockTreeBuilder::finish`] で木構造を取り出します。
+///
+/// # Examples
+///
+/// ```
+/// use aozora_core::block_tree::{BlockTreeBuilder, TreeNode};
+/// use aozora_core::document::DocumentLine;
+///
+/// let mut builder = BlockTreeBuilder::new();
+/// builder.push_line(&DocumentLine::parse(0, 0, "［＃ここから２字下げ］"));
+/// builder.push_line(&DocumentLine::parse(1, 0, "本文"));
+/// builder.push_line(&DocumentLine::parse(2, 0, "［＃ここで字下げ終わり］"));
+/// let tree = builder.finish();
+/// assert!(matches!(&tree[0], TreeNode::Block(block) if block.children.len() == 3));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BlockTreeBuilder {
+    /// 最上位の子ノード
+    root: Vec<TreeNode>,
+    /// 開いているブロック（外側から順）
+    stack: Vec<Block>,
+}
+
+impl BlockTreeBuilder {
+    /// 新しいビルダーを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 解析済みの1行を追加
+    pub fn push_line(&mut self, line: &DocumentLine) {
+        for node in &line.nodes {
+            self.push_node(node.clone());
+        }
+        self.end_line(line.line);
+    }
+
+    /// ノードを1つ追加
+    pub fn push_node(&mut self, node: Spanned<Node>) {
+        match node.value {
+            Node::BlockStart { block_type, params } => {
+                if params.is_block {
+                    self.close_related(block_type);
+                }
+                self.stack.push(Block {
+                    block_type,
+                    params,
+                    end_params: None,
+                    children: Vec::new(),
+                    start_span: node.span,
+                    end_span: None,
+                    close: BlockClose::Unclosed,
+                    continued: false,
+                });
+            }
+            Node::BlockEnd { block_type, params } => {
+                let pos = self
+                    .stack
+                    .iter()
+                    .rposition(|b| b.matches_end(block_type, &params));
+                match pos {
+                    Some(pos) => self.close_at(pos, params, node.span),
+                    None => self.append(TreeNode::Node(Spanned::new(
+                        Node::BlockEnd { block_type, params },
+                        node.span,
+                    ))),
+                }
+            }
+            value => self.append(TreeNode::Node(Spanned::new(value, node.span))),
+        }
+    }
+
+    /// 行の終わりを追加（行単位・インラインのブロックを閉じる）
+    pub fn end_line(&mut self, line: usize) {
+        while let Some(pos) = self.stack.iter().rposition(Block::is_line_scoped) {
+            self.close_interrupting(pos, BlockClose::LineEnd);
+        }
+        self.append(TreeNode::LineBreak { line });
+    }
+
+    /// 開いているブロックをすべて閉じ、木構造を返す
+    pub fn finish(mut self) -> Vec<TreeNode> {
+        while let Some(mut block) = self.stack.pop() {
+            block.close = BlockClose::Unclosed;
+            self.append(TreeNode::Block(block));
+        }
+        self.root
+    }
+
+    /// 現在のブロック（なければ最上位）に子ノードを追加
+    fn append(&mut self, node: TreeNode) {
+        match self.stack.last_mut() {
+            Some(block) => block.children.push(node),
+            None => self.root.push(node),
+        }
+    }
+
+    /// 新しいブロックの開始で暗黙に閉じられるブロックを閉じる
+    fn close_related(&mut self, new_type: BlockType) {
+        while let Some(pos) = self.stack.iter().rposition(|b| b.is_closed_by(new_type)) {
+            self.close_interrupting(pos, BlockClose::Implicit);
+        }
+    }
+
+    /// 終わりの注記で `pos` のブロックを閉じる
+    fn close_at(&mut self, pos: usize, params: BlockParams, span: Span) {
+        let reopened = self.split_above(pos);
+        let mut block = self.stack.pop().expect("block at pos");
+        block.close = BlockClose::Explicit;
+        block.end_params = Some(params);
+        block.end_span = Some(span);
+        self.append(TreeNode::Block(block));
+        self.stack.extend(reopened);
+    }
+
+    /// 終わりの注記以外の理由で `pos` のブロックを閉じる
+    fn close_interrupting(&mut self, pos: usize, close: BlockClose) {
+        let reopened = self.split_above(pos);
+        let mut block = self.stack.pop().expect("block at pos");
+        block.close = close;
+        self.append(TreeNode::Block(block));
+        self.stack.extend(reopened);
+    }
+
+    /// `pos` より内側のブロックを分割して閉じ、続きのブロックを返す
+    fn split_above(&mut self, pos: usize) -> Vec<Block> {
+        let mut reopened = Vec::new();
+        while self.stack.len() > pos + 1 {
+            let mut inner = self.stack.pop().expect("inner block");
+            inner.close = BlockClose::Interrupted;
+            reopened.push(inner.continuation());
+            self.append(TreeNode::Block(inner));
+        }
+        reopened.reverse();
+        reopened
+    }
+}
+
+/// 解析済みの行からブロックの木構造を組み立てる
+pub fn build_block_tree(lines: &[DocumentLine]) -> Vec<TreeNode> {
+    let mut builder = BlockTreeBuilder::new();
+    for line in lines {
+        builder.push_line(line);
+    }
+    builder.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::StyleType;
+
+    fn tree(text: &str) -> Vec<TreeNode> {
+        let lines: Vec<DocumentLine> = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| DocumentLine::parse(i, 0, line))
+            .collect();
+        build_block_tree(&lines)
+    }
+
+    fn block(node: &TreeNode) -> &Block {
+        match node {
+            TreeNode::Block(block) => block,
+            other => panic!("expected block, got {other:?}"),
+        }
+    }
+
+    fn text(node: &TreeNode) -> &str {
+        match node {
+            TreeNode::Node(Spanned {
+                value: Node::Text(s),
+                ..
+            }) => s,
+            other => panic!("expected text, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_block_level() {
+        let tree = tree("前\n［＃ここから２字下げ］\n本文\n［＃ここで字下げ終わり］\n後");
+        assert_eq!(tree.len(), 6);
+        assert_eq!(text(&tree[0]), "前");
+        assert_eq!(tree[1], TreeNode::LineBreak { line: 0 });
+        let jisage = block(&tree[2]);
+        assert_eq!(jisage.block_type, BlockType::Jisage);
+        assert_eq!(jisage.params.width, Some(2));
+        assert_eq!(jisage.close, BlockClose::Explicit);
+        assert_eq!(
+            jisage.children,
+            vec![
+                TreeNode::LineBreak { line: 1 },
+                tree_text("本文", 0..6),
+                TreeNode::LineBreak { line: 2 },
+            ]
+        );
+        // 終わりの注記の行の改行はブロックの後ろ
+        assert_eq!(tree[3], TreeNode::LineBreak { line: 3 });
+        assert_eq!(text(&tree[4]), "後");
+    }
+
+    fn tree_text(s: &str, range: std::ops::Range<usize>) -> TreeNode {
+        TreeNode::Node(Spanned::new(
+            Node::Text(s.to_string()),
+            Span::new(range.start, range.end),
+        ))
+    }
+
+    #[test]
+    fn test_inline_blocks() {
+        let tree = tree("［＃太字］強調［＃太字終わり］と［＃中見出し］見出し［＃中見出し終わり］");
+        let futoji = block(&tree[0]);
+        assert_eq!(futoji.block_type, BlockType::Style);
+        assert_eq!(futoji.params.style_type, Some(StyleType::Bold));
+        assert_eq!(futoji.close, BlockClose::Explicit);
+        assert_eq!(text(&futoji.children[0]), "強調");
+        assert_eq!(text(&tree[1]), "と");
+        let midashi = block(&tree[2]);
+        assert_eq!(midashi.block_type, BlockType::Midashi);
+        assert_eq!(text(&midashi.children[0]), "見出し");
+    }
+
+    #[test]
+    fn test_line_scope_indent() {
+        let tree = tree("［＃３字下げ］本文\n次");
+        let jisage = block(&tree[0]);
+        assert_eq!(jisage.close, BlockClose::LineEnd);
+        assert_eq!(text(&jisage.children[0]), "本文");
+        assert_eq!(tree[1], TreeNode::LineBreak { line: 0 });
+        assert_eq!(text(&tree[2]), "次");
+    }
+
+    #[test]
+    fn test_nested() {
+        let tree = tree(
+            "［＃ここから罫囲み］\n［＃ここから２字下げ］\n本文\n［＃ここで字下げ終わり］\n［＃ここで罫囲み終わり］",
+        );
+        assert_eq!(tree.len(), 2);
+        let keigakomi = block(&tree[0]);
+        assert_eq!(keigakomi.block_type, BlockType::Keigakomi);
+        let jisage = block(&keigakomi.children[1]);
+        assert_eq!(jisage.block_type, BlockType::Jisage);
+        assert_eq!(text(&jisage.children[1]), "本文");
+    }
+
+    #[test]
+    fn test_implicit_close() {
+        let tree = tree(
+            "［＃ここから２字下げ］\n一\n［＃ここから３字下げ］\n二\n［＃ここで字下げ終わり］",
+        );
+        let first = block(&tree[0]);
+        assert_eq!(first.close, BlockClose::Implicit);
+        assert_eq!(first.params.width, Some(2));
+        let second = block(&tree[1]);
+        assert_eq!(second.close, BlockClose::Explicit);
+        assert_eq!(second.params.width, Some(3));
+    }
+
+    #[test]
+    fn test_burasage_closed_by_jisage_end() {
+        let tree =
+            tree("［＃ここから２字下げ、折り返して３字下げ］\n本文\n［＃ここで字下げ終わり］");
+        let burasage = block(&tree[0]);
+        assert_eq!(burasage.block_type, BlockType::Burasage);
+        assert_eq!(burasage.close, BlockClose::Explicit);
+    }
+
+    #[test]
+    fn test_overlapping() {
+        let tree = tree(
+            "［＃ここから罫囲み］\n［＃ここから太字］\n一\n［＃ここで罫囲み終わり］\n二\n［＃ここで太字終わり］",
+        );
+        let keigakomi = block(&tree[0]);
+        assert_eq!(keigakomi.close, BlockClose::Explicit);
+        let inner = block(&keigakomi.children[1]);
+        assert_eq!(inner.block_type, BlockType::Futoji);
+        assert_eq!(inner.close, BlockClose::Interrupted);
+        assert!(!inner.continued);
+        let rest = block(&tree[1]);
+        assert_eq!(rest.block_type, BlockType::Futoji);
+        assert_eq!(rest.close, BlockClose::Explicit);
+        assert!(rest.continued);
+        assert_eq!(text(&rest.children[1]), "二");
+    }
+
+    #[test]
+    fn test_unclosed_and_unmatched() {
+        let tree = tree("［＃ここで字下げ終わり］\n［＃ここから地付き］\n本文");
+        assert!(matches!(
+            &tree[0],
+            TreeNode::Node(Spanned {
+                value: Node::BlockEnd {
+                    block_type: BlockType::Jisage,
+                    ..
+                },
+                ..
+            })
+        ));
+        let chitsuki = block(&tree[2]);
+        assert_eq!(chitsuki.close, BlockClose::Unclosed);
+        assert_eq!(text(&chitsuki.children[1]), "本文");
+    }
+
+    #[test]
+    fn test_style_end_matches_style_type() {
+        let tree = tree("［＃傍点］あ［＃傍線］い［＃傍点終わり］う［＃傍線終わり］");
+        let bouten = block(&tree[0]);
+        assert_eq!(bouten.params.style_type, Some(StyleType::SesameDot));
+        assert_eq!(bouten.close, BlockClose::Explicit);
+        let bousen = block(&bouten.children[1]);
+        assert_eq!(bousen.close, BlockClose::Interrupted);
+        let rest = block(&tree[1]);
+        assert!(rest.continued);
+        assert_eq!(rest.close, BlockClose::Explicit);
+    }
+}
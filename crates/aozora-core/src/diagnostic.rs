@@ -0,0 +1,749 @@
+//! 診断情報
+//!
+//! 未知の注記、参照先の見つからない注記、対応の取れないブロック、閉じられていない
+//! `《` / `［＃`、変換できない外字などを検出し、位置情報付きの診断として報告します。
+//! 診断を出した場合でも、解析結果（ノード列）はそのまま利用できます。
+
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::delimiters::{COMMAND_BEGIN, COMMAND_END, GAIJI_MARK, IGETA, RUBY_END};
+use crate::document::{after_text_line_range, body_line_range, DocumentLine};
+use crate::gaiji::{parse_gaiji, GaijiResult};
+use crate::node::{BlockType, Node};
+use crate::parser::{parse_command, CommandResult};
+use crate::span::{LineIndex, Position, Span, Spanned};
+use crate::token::Token;
+use crate::tokenizer::tokenize_with_spans;
+
+/// 診断の重大度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// エラー（記法として誤っている）
+    Error,
+    /// 警告（変換はできるが意図どおりでない可能性がある）
+    Warning,
+}
+
+impl Severity {
+    /// 表示用の名前
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 診断の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiagnosticCode {
+    /// 解釈できない注記（注記としてそのまま出力される）
+    UnknownCommand,
+    /// 参照先の文字列が見つからない注記
+    UnresolvedReference,
+    /// 「ここから」で始まり、終わりのないブロック
+    UnclosedBlock,
+    /// 対応する開始のない「終わり」注記
+    UnmatchedBlockEnd,
+    /// 終わりの注記がないまま、次のブロック開始で閉じられたブロック
+    ImplicitlyClosedBlock,
+    /// 行内で閉じられていないインライン注記
+    UnclosedInline,
+    /// `］` で閉じられていない `［＃`
+    UnterminatedCommand,
+    /// `》` で閉じられていない `《`
+    UnterminatedRuby,
+    /// 変換できない外字
+    UnresolvedGaiji,
+}
+
+impl DiagnosticCode {
+    /// 機械可読なコード名
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiagnosticCode::UnknownCommand => "unknown-command",
+            DiagnosticCode::UnresolvedReference => "unresolved-reference",
+            DiagnosticCode::UnclosedBlock => "unclosed-block",
+            DiagnosticCode::UnmatchedBlockEnd => "unmatched-block-end",
+            DiagnosticCode::ImplicitlyClosedBlock => "implicitly-closed-block",
+            DiagnosticCode::UnclosedInline => "unclosed-inline",
+            DiagnosticCode::UnterminatedCommand => "unterminated-command",
+            DiagnosticCode::UnterminatedRuby => "unterminated-ruby",
+            DiagnosticCode::UnresolvedGaiji => "unresolved-gaiji",
+        }
+    }
+
+    /// この種類の診断の重大度
+    pub fn severity(&self) -> Severity {
+        match self {
+            DiagnosticCode::UnresolvedReference
+            | DiagnosticCode::UnclosedBlock
+            | DiagnosticCode::UnmatchedBlockEnd
+            | DiagnosticCode::UnterminatedCommand
+            | DiagnosticCode::UnterminatedRuby => Severity::Error,
+            DiagnosticCode::UnknownCommand
+            | DiagnosticCode::ImplicitlyClosedBlock
+            | DiagnosticCode::UnclosedInline
+            | DiagnosticCode::UnresolvedGaiji => Severity::Warning,
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 診断
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// 重大度
+    pub severity: Severity,
+    /// 種類
+    pub code: DiagnosticCode,
+    /// メッセージ
+    pub message: String,
+    /// 入力全体でのバイト範囲
+    pub span: Span,
+    /// 開始位置
+    pub start: Position,
+    /// 終了位置
+    pub end: Position,
+}
+
+impl fmt::Display for Diagnostic {
+    /// `行:桁: 重大度[コード]: メッセージ` 形式（行・桁は1始まり）
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}[{}]: {}",
+            self.start.line + 1,
+            self.start.column + 1,
+            self.severity,
+            self.code,
+            self.message
+        )
+    }
+}
+
+/// 診断付きの解析結果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CheckResult {
+    /// 解析した行
+    pub lines: Vec<DocumentLine>,
+    /// 診断（出現順）
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl CheckResult {
+    /// エラーを含むかどうか
+    pub fn has_errors(&self) -> bool {
+        self.error_count() > 0
+    }
+
+    /// エラーの数
+    pub fn error_count(&self) -> usize {
+        self.count(Severity::Error)
+    }
+
+    /// 警告の数
+    pub fn warning_count(&self) -> usize {
+        self.count(Severity::Warning)
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == severity)
+            .count()
+    }
+}
+
+/// テキスト全体を本文として解析し、診断を収集
+///
+/// # Examples
+///
+/// ```
+/// use aozora_core::diagnostic::{check_text, DiagnosticCode};
+///
+/// let result = check_text("吾輩は猫である［＃「犬」に傍点］");
+/// assert_eq!(result.diagnostics.len(), 1);
+/// assert_eq!(result.diagnostics[0].code, DiagnosticCode::UnresolvedReference);
+/// assert_eq!(result.lines.len(), 1);
+/// ```
+pub fn check_text(text: &str) -> CheckResult {
+    let lines: Vec<&str> = text.lines().collect();
+    let all = 0..lines.len();
+    check_lines(text, &lines, std::slice::from_ref(&all))
+}
+
+/// 青空文庫形式の文書を解析し、診断を収集
+///
+/// 本文と本文終わり後のテキストが対象です（ヘッダー、記号説明、底本情報は対象外）。
+pub fn check_document(text: &str) -> CheckResult {
+    let lines: Vec<&str> = text.lines().collect();
+    let ranges = [body_line_range(&lines), after_text_line_range(&lines)];
+    check_lines(text, &lines, &ranges)
+}
+
+/// 1行を解析し、行内で完結する診断（ブロックの対応以外）を返す
+///
+/// ブロックの対応は [`Checker::check_blocks`] で解析済みの行から検査できます。
+/// 編集された行だけを検査し直す場合に使います。
+///
+/// # Examples
+///
+/// ```
+/// use aozora_core::diagnostic::{check_line_local, DiagnosticCode};
+///
+/// let (line, diagnostics) = check_line_local(2, 10, "［＃ここから２字下げ］猫［＃「犬」に傍点］");
+/// assert_eq!(line.line, 2);
+/// assert_eq!(diagnostics.len(), 1);
+/// assert_eq!(diagnostics[0].code, DiagnosticCode::UnresolvedReference);
+/// assert_eq!(diagnostics[0].start.line, 2);
+/// ```
+pub fn check_line_local(line: usize, offset: usize, text: &str) -> (DocumentLine, Vec<Diagnostic>) {
+    let ctx = LineContext { line, offset, text };
+    let mut checker = Checker::new();
+    let (parsed, reference_spans) = checker.parse_line(&ctx);
+    for node in &parsed.nodes {
+        checker.check_reference(
+            &ctx,
+            Spanned::new(&node.value, ctx.local(node.span)),
+            &reference_spans,
+        );
+    }
+    (parsed, checker.diagnostics)
+}
+
+/// 行範囲ごとに解析（ブロックの対応は範囲ごとに確認）
+fn check_lines(text: &str, lines: &[&str], ranges: &[std::ops::Range<usize>]) -> CheckResult {
+    let index = LineIndex::new(text);
+    let mut result = CheckResult::default();
+
+    for range in ranges {
+        let mut checker = Checker::new();
+        for i in range.clone() {
+            let offset = index.line_start(i).unwrap_or(text.len());
+            result.lines.push(checker.check_line(i, offset, lines[i]));
+        }
+        result.diagnostics.extend(checker.finish());
+    }
+
+    result
+}
+
+/// 開いているブロック
+#[derive(Debug, Clone)]
+struct OpenBlock {
+    block_type: BlockType,
+    is_block: bool,
+    /// 開始注記の表記
+    source: String,
+    span: Span,
+    start: Position,
+    end: Position,
+}
+
+/// 行単位の診断器
+///
+/// 行をまたぐブロックの対応を追跡しながら、1行ずつ解析して診断を収集します。
+///
+/// # Examples
+///
+/// ```
+/// use aozora_core::diagnostic::{Checker, DiagnosticCode};
+///
+/// let mut checker = Checker::new();
+/// checker.check_line(0, 0, "［＃ここから２字下げ］");
+/// let diagnostics = checker.finish();
+/// assert_eq!(diagnostics[0].code, DiagnosticCode::UnclosedBlock);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Checker {
+    open_blocks: Vec<OpenBlock>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker {
+    /// 新しい診断器を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// これまでに収集した診断
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// 1行を解析して診断を収集し、解析済みの行を返す
+    ///
+    /// `line` は行番号（0始まり）、`offset` は入力全体での行頭のバイト位置です。
+    /// ノードと診断の位置は `offset` だけずらした入力全体での位置になります。
+    pub fn check_line(&mut self, line: usize, offset: usize, text: &str) -> DocumentLine {
+        let ctx = LineContext { line, offset, text };
+        let (parsed, reference_spans) = self.parse_line(&ctx);
+        for node in &parsed.nodes {
+            let span = ctx.local(node.span);
+            self.check_reference(&ctx, Spanned::new(&node.value, span), &reference_spans);
+            self.check_block(&ctx, Spanned::new(&node.value, span));
+        }
+        self.close_inline_blocks(&ctx);
+
+        parsed
+    }
+
+    /// 解析済みの行のブロックの対応だけを検査
+    ///
+    /// 行内で完結する診断は [`check_line_local`] で別に求めます。
+    /// `offset` は入力全体での行頭のバイト位置です。
+    pub fn check_blocks(&mut self, line: &DocumentLine, offset: usize) {
+        let ctx = LineContext {
+            line: line.line,
+            offset,
+            text: &line.text,
+        };
+        for node in &line.nodes {
+            self.check_block(&ctx, Spanned::new(&node.value, ctx.local(node.span)));
+        }
+        self.close_inline_blocks(&ctx);
+    }
+
+    /// 解析を終了し、閉じられていないブロックを報告して診断を返す
+    pub fn finish(mut self) -> Vec<Diagnostic> {
+        for block in std::mem::take(&mut self.open_blocks) {
+            self.diagnostics.push(Diagnostic {
+                severity: DiagnosticCode::UnclosedBlock.severity(),
+                code: DiagnosticCode::UnclosedBlock,
+                message: format!("block {} is never closed", block.source),
+                span: block.span,
+                start: block.start,
+                end: block.end,
+            });
+        }
+        self.diagnostics
+    }
+
+    /// トークン単位の検査（閉じ忘れ、未知の注記、外字）
+    fn check_token(
+        &mut self,
+        ctx: &LineContext,
+        token: &Spanned<Token>,
+        reference_spans: &mut HashSet<Span>,
+    ) {
+        let source = &ctx.text[token.span.start..token.span.end];
+        match &token.value {
+            Token::Command { content } => {
+                let prefix_len = COMMAND_BEGIN.len_utf8() + IGETA.len_utf8();
+                if source.len() <= prefix_len + content.len() {
+                    self.push(
+                        ctx,
+                        DiagnosticCode::UnterminatedCommand,
+                        format!("annotation is not closed with {COMMAND_END}"),
+                        token.span,
+                    );
+                    return;
+                }
+                let result = parse_command(content);
+                if result.is_reference() {
+                    reference_spans.insert(token.span);
+                } else if let CommandResult::Unknown(_) = result {
+                    self.push(
+                        ctx,
+                        DiagnosticCode::UnknownCommand,
+                        format!("unknown annotation {source}"),
+                        token.span,
+                    );
+                }
+            }
+            Token::Gaiji { description } => {
+                let prefix_len =
+                    GAIJI_MARK.len_utf8() + COMMAND_BEGIN.len_utf8() + IGETA.len_utf8();
+                if source.len() <= prefix_len + description.len() {
+                    self.push(
+                        ctx,
+                        DiagnosticCode::UnterminatedCommand,
+                        format!("gaiji annotation is not closed with {COMMAND_END}"),
+                        token.span,
+                    );
+                }
+                self.check_gaiji(ctx, &token.value, token.span);
+            }
+            Token::Ruby { .. } | Token::PrefixedRuby { .. } => {
+                if !source.ends_with(RUBY_END) {
+                    self.push(
+                        ctx,
+                        DiagnosticCode::UnterminatedRuby,
+                        format!("ruby is not closed with {RUBY_END}"),
+                        token.span,
+                    );
+                }
+                self.check_gaiji(ctx, &token.value, token.span);
+            }
+            Token::Accent { .. } => self.check_gaiji(ctx, &token.value, token.span),
+            Token::Text(_) => {}
+        }
+    }
+
+    /// トークン内（入れ子を含む）の外字を検査
+    ///
+    /// 入れ子のトークンは位置情報を持たないため、外側のトークンの位置で報告します。
+    fn check_gaiji(&mut self, ctx: &LineContext, token: &Token, span: Span) {
+        match token {
+            Token::Gaiji { description } => {
+                if matches!(parse_gaiji(description), GaijiResult::Unconvertible) {
+                    self.push(
+                        ctx,
+                        DiagnosticCode::UnresolvedGaiji,
+                        format!("gaiji ※［＃{description}］ cannot be converted"),
+                        span,
+                    );
+                }
+            }
+            Token::Ruby { children } | Token::Accent { children } => {
+                for child in children {
+                    self.check_gaiji(ctx, child, span);
+                }
+            }
+            Token::PrefixedRuby {
+                base_children,
+                ruby_children,
+            } => {
+                for child in base_children.iter().chain(ruby_children) {
+                    self.check_gaiji(ctx, child, span);
+                }
+            }
+            Token::Text(_) | Token::Command { .. } => {}
+        }
+    }
+
+    /// 行をトークン化して検査し、解析済みの行と参照注記の位置を返す
+    fn parse_line(&mut self, ctx: &LineContext) -> (DocumentLine, HashSet<Span>) {
+        let tokens = tokenize_with_spans(ctx.text);
+        let mut reference_spans = HashSet::new();
+        for token in &tokens {
+            self.check_token(ctx, token, &mut reference_spans);
+        }
+        let parsed = DocumentLine::from_tokens(ctx.line, ctx.offset, ctx.text, &tokens);
+        (parsed, reference_spans)
+    }
+
+    /// 参照先の見つからない注記を検査
+    fn check_reference(
+        &mut self,
+        ctx: &LineContext,
+        node: Spanned<&Node>,
+        reference_spans: &HashSet<Span>,
+    ) {
+        // 参照先が見つからなかった注記は、注記ノードとして元の位置に残る
+        if matches!(node.value, Node::Note(_)) && reference_spans.contains(&node.span) {
+            let source = &ctx.text[node.span.start..node.span.end];
+            self.push(
+                ctx,
+                DiagnosticCode::UnresolvedReference,
+                format!("target of {source} is not found before the annotation"),
+                node.span,
+            );
+        }
+    }
+
+    /// ブロックの対応を検査
+    fn check_block(&mut self, ctx: &LineContext, node: Spanned<&Node>) {
+        let source = &ctx.text[node.span.start..node.span.end];
+        match node.value {
+            Node::BlockStart { block_type, params } => {
+                // 行単位の字下げ・地付きは行末で閉じられる
+                if !params.is_block && matches!(block_type, BlockType::Jisage | BlockType::Chitsuki)
+                {
+                    return;
+                }
+                if params.is_block {
+                    self.close_related_blocks(*block_type, source);
+                }
+                let (start, end) = ctx.range(node.span);
+                self.open_blocks.push(OpenBlock {
+                    block_type: *block_type,
+                    is_block: params.is_block,
+                    source: source.to_string(),
+                    span: node.span.shift(ctx.offset),
+                    start,
+                    end,
+                });
+            }
+            Node::BlockEnd { block_type, .. } => {
+                // 字下げ終わりはぶら下げも閉じる
+                let pos = self.open_blocks.iter().rposition(|b| {
+                    b.block_type == *block_type
+                        || (*block_type == BlockType::Jisage && b.block_type == BlockType::Burasage)
+                });
+                match pos {
+                    Some(pos) => {
+                        self.open_blocks.remove(pos);
+                    }
+                    None => self.push(
+                        ctx,
+                        DiagnosticCode::UnmatchedBlockEnd,
+                        format!("{source} has no matching start"),
+                        node.span,
+                    ),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// 新しいブロック開始で暗黙に閉じられるブロックを報告
+    fn close_related_blocks(&mut self, new_type: BlockType, source: &str) {
+        if !matches!(
+            new_type,
+            BlockType::Jisage | BlockType::Chitsuki | BlockType::Burasage
+        ) {
+            return;
+        }
+        while let Some(pos) = self.open_blocks.iter().rposition(|b| {
+            b.is_block
+                && (b.block_type == new_type
+                    || b.block_type == BlockType::Burasage
+                    || (new_type == BlockType::Burasage && b.block_type == BlockType::Jisage))
+        }) {
+            let block = self.open_blocks.remove(pos);
+            self.diagnostics.push(Diagnostic {
+                severity: DiagnosticCode::ImplicitlyClosedBlock.severity(),
+                code: DiagnosticCode::ImplicitlyClosedBlock,
+                message: format!(
+                    "block {} is closed by {} without an end annotation",
+                    block.source, source
+                ),
+                span: block.span,
+                start: block.start,
+                end: block.end,
+            });
+        }
+    }
+
+    /// 行末で閉じられていないインライン注記を報告
+    fn close_inline_blocks(&mut self, ctx: &LineContext) {
+        while let Some(pos) = self.open_blocks.iter().rposition(|b| !b.is_block) {
+            let block = self.open_blocks.remove(pos);
+            self.diagnostics.push(Diagnostic {
+                severity: DiagnosticCode::UnclosedInline.severity(),
+                code: DiagnosticCode::UnclosedInline,
+                message: format!("{} is not closed in line {}", block.source, ctx.line + 1),
+                span: block.span,
+                start: block.start,
+                end: block.end,
+            });
+        }
+    }
+
+    fn push(&mut self, ctx: &LineContext, code: DiagnosticCode, message: String, span: Span) {
+        let (start, end) = ctx.range(span);
+        self.diagnostics.push(Diagnostic {
+            severity: code.severity(),
+            code,
+            message,
+            span: span.shift(ctx.offset),
+            start,
+            end,
+        });
+    }
+}
+
+/// 検査中の行
+struct LineContext<'a> {
+    line: usize,
+    offset: usize,
+    text: &'a str,
+}
+
+impl LineContext<'_> {
+    /// 入力全体での範囲を行内の範囲に変換
+    fn local(&self, span: Span) -> Span {
+        Span::new(span.start - self.offset, span.end - self.offset)
+    }
+
+    /// 行内の範囲を行・桁位置に変換
+    fn range(&self, span: Span) -> (Position, Position) {
+        let (start, end) = LineIndex::new(self.text).range(span);
+        (
+            Position {
+                line: self.line,
+                column: start.column,
+            },
+            Position {
+                line: self.line,
+                column: end.column,
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(text: &str) -> Vec<DiagnosticCode> {
+        check_text(text)
+            .diagnostics
+            .iter()
+            .map(|d| d.code)
+            .collect()
+    }
+
+    #[test]
+    fn test_no_diagnostics() {
+        let text = "［＃ここから２字下げ］\n吾輩《わがはい》は猫である［＃「猫」に傍点］\n［＃ここで字下げ終わり］";
+        let result = check_text(text);
+        assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+        assert_eq!(result.lines.len(), 3);
+        assert!(!result.has_errors());
+    }
+
+    #[test]
+    fn test_line_indent_is_not_reported() {
+        assert!(codes("［＃３字下げ］本文\n［＃地付き］署名").is_empty());
+    }
+
+    #[test]
+    fn test_unknown_command() {
+        let result = check_text("本文［＃改行なし］");
+        assert_eq!(result.diagnostics.len(), 1);
+        let d = &result.diagnostics[0];
+        assert_eq!(d.code, DiagnosticCode::UnknownCommand);
+        assert_eq!(d.severity, Severity::Warning);
+        assert_eq!(d.start, Position { line: 0, column: 2 });
+        assert_eq!(d.end, Position { line: 0, column: 9 });
+        // 解析結果は注記として残る
+        assert!(matches!(&result.lines[0].nodes[1].value, Node::Note(s) if s == "改行なし"));
+    }
+
+    #[test]
+    fn test_unresolved_reference() {
+        let result = check_text("一行目\n吾輩は猫である［＃「犬」に傍点］");
+        assert_eq!(result.diagnostics.len(), 1);
+        let d = &result.diagnostics[0];
+        assert_eq!(d.code, DiagnosticCode::UnresolvedReference);
+        assert_eq!(d.severity, Severity::Error);
+        assert_eq!(d.start.line, 1);
+        assert_eq!(
+            &"一行目\n吾輩は猫である［＃「犬」に傍点］"[d.span.start..d.span.end],
+            "［＃「犬」に傍点］"
+        );
+    }
+
+    #[test]
+    fn test_unclosed_block() {
+        let result = check_text("［＃ここから２字下げ］\n本文");
+        assert_eq!(
+            codes("［＃ここから２字下げ］\n本文"),
+            vec![DiagnosticCode::UnclosedBlock]
+        );
+        assert_eq!(result.diagnostics[0].start, Position { line: 0, column: 0 });
+        assert!(result.has_errors());
+    }
+
+    #[test]
+    fn test_unmatched_block_end() {
+        assert_eq!(
+            codes("本文\n［＃ここで字下げ終わり］"),
+            vec![DiagnosticCode::UnmatchedBlockEnd]
+        );
+    }
+
+    #[test]
+    fn test_implicitly_closed_block() {
+        let text =
+            "［＃ここから２字下げ］\n本文\n［＃ここから３字下げ］\n本文\n［＃ここで字下げ終わり］";
+        assert_eq!(codes(text), vec![DiagnosticCode::ImplicitlyClosedBlock]);
+    }
+
+    #[test]
+    fn test_burasage_closed_by_jisage_end() {
+        let text = "［＃ここから２字下げ、折り返して３字下げ］\n本文\n［＃ここで字下げ終わり］";
+        assert!(codes(text).is_empty());
+    }
+
+    #[test]
+    fn test_unclosed_inline() {
+        assert_eq!(
+            codes("［＃太字］本文\n次の行［＃太字終わり］"),
+            vec![
+                DiagnosticCode::UnclosedInline,
+                DiagnosticCode::UnmatchedBlockEnd
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unterminated_command() {
+        assert_eq!(
+            codes("本文［＃「本文」に傍点"),
+            vec![DiagnosticCode::UnterminatedCommand]
+        );
+        assert_eq!(
+            codes("本文※［＃「丸印」、U+25CB"),
+            vec![DiagnosticCode::UnterminatedCommand]
+        );
+    }
+
+    #[test]
+    fn test_unterminated_ruby() {
+        assert_eq!(
+            codes("漢字《かんじ"),
+            vec![DiagnosticCode::UnterminatedRuby]
+        );
+        assert_eq!(
+            codes("｜漢字《かんじ"),
+            vec![DiagnosticCode::UnterminatedRuby]
+        );
+    }
+
+    #[test]
+    fn test_unresolved_gaiji() {
+        assert_eq!(
+            codes("※［＃「存在しない字」］"),
+            vec![DiagnosticCode::UnresolvedGaiji]
+        );
+        // ルビ内の外字も検査する
+        assert_eq!(
+            codes("漢字《※［＃「存在しない字」］》"),
+            vec![DiagnosticCode::UnresolvedGaiji]
+        );
+        assert!(codes("※［＃「丸印」、U+25CB］").is_empty());
+    }
+
+    #[test]
+    fn test_check_document() {
+        let text = "題名\n著者\n\n本文［＃「無」に傍点］\n［＃本文終わり］\n後記［＃ここから２字下げ］\n底本：某文庫［＃不明な注記］";
+        let result = check_document(text);
+        let codes: Vec<_> = result.diagnostics.iter().map(|d| d.code).collect();
+        assert_eq!(
+            codes,
+            vec![
+                DiagnosticCode::UnresolvedReference,
+                DiagnosticCode::UnclosedBlock
+            ]
+        );
+        assert_eq!(result.diagnostics[0].start.line, 3);
+        assert_eq!(result.diagnostics[1].start.line, 5);
+        let lines: Vec<_> = result.lines.iter().map(|l| l.line).collect();
+        assert_eq!(lines, vec![3, 5]);
+    }
+
+    #[test]
+    fn test_display() {
+        let result = check_text("本文\n漢字《かんじ");
+        assert_eq!(
+            result.diagnostics[0].to_string(),
+            "2:3: error[unterminated-ruby]: ruby is not closed with 》"
+        );
+    }
+}
@@ -1,5 +1,17 @@
 //! 文書構造の処理
 
+use std::ops::Range;
+
+use crate::biblio::{extract_biblio_info, BiblioInfo};
+use crate::block_tree::{build_block_tree, TreeNode};
+use crate::legend::{parse_legend, Legend};
+use crate::node::Node;
+use crate::outline::{build_outline, OutlineEntry};
+use crate::parser::{parse_with_spans, resolve_inline_ruby_with_spans};
+use crate::span::{LineIndex, Spanned};
+use crate::token::Token;
+use crate::tokenizer::tokenize_with_spans;
+
 /// 文書セクションの種類
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum SectionType {
@@ -11,23 +23,262 @@ enum SectionType {
     Chuuki,
     /// 本文
     Body,
+    /// 本文終わり後のテキスト
+    AfterText,
+    /// 底本情報
+    Bibliography,
 }
 
-/// 人物の種別
+/// 文書中の行が属するセクション
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum PersonType {
+pub enum Section {
+    /// ヘッダー（最初の空行まで）
+    Header,
+    /// セクションの区切り（ヘッダー後の空行、`---` の行、`［＃本文終わり］` の行）
+    Separator,
+    /// 記号説明（`---` で囲まれた注記セクション）
+    Legend,
+    /// 本文
+    Body,
+    /// 本文終わり後のテキスト
+    AfterText,
+    /// 底本情報
+    Bibliography,
+}
+
+/// 文書の行を先頭から順にセクションへ振り分ける
+///
+/// 文書全体を保持せずに1行ずつ判定するため、ストリーミング処理に使えます。
+///
+/// # Examples
+///
+/// ```
+/// use aozora_core::document::{Section, SectionScanner};
+///
+/// let mut scanner = SectionScanner::new();
+/// let sections: Vec<Section> = ["タイトル", "", "本文", "底本：〇〇文庫"]
+///     .iter()
+///     .map(|line| scanner.next_line(line))
+///     .collect();
+/// assert_eq!(
+///     sections,
+///     vec![Section::Header, Section::Separator, Section::Body, Section::Bibliography]
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct SectionScanner {
+    state: SectionType,
+}
+
+impl SectionScanner {
+    /// 新しいスキャナを作成
+    pub fn new() -> Self {
+        Self {
+            state: SectionType::Header,
+        }
+    }
+
+    /// 次の行のセクションを判定
+    pub fn next_line(&mut self, line: &str) -> Section {
+        match self.state {
+            SectionType::Header => {
+                // 空行でヘッダー終了
+                if line.is_empty() {
+                    self.state = SectionType::AfterHeader;
+                    Section::Separator
+                } else {
+                    Section::Header
+                }
+            }
+            SectionType::AfterHeader => {
+                // 空行後、---で始まれば注記セクション、そうでなければ本文
+                if line.starts_with("---") {
+                    self.state = SectionType::Chuuki;
+                    Section::Separator
+                } else if line.is_empty() {
+                    // 連続する空行はスキップ
+                    Section::Separator
+                } else if line.starts_with("底本：") {
+                    self.state = SectionType::Bibliography;
+                    Section::Bibliography
+                } else {
+                    // 本文開始
+                    self.state = SectionType::Body;
+                    Section::Body
+                }
+            }
+            SectionType::Chuuki => {
+                // ---で注記セクション終了
+                if line.starts_with("---") {
+                    self.state = SectionType::Body;
+                    Section::Separator
+                } else {
+                    Section::Legend
+                }
+            }
+            SectionType::Body => {
+                // 底本：または［＃本文終わり］で本文終了
+                if line.starts_with("底本：") {
+                    self.state = SectionType::Bibliography;
+                    Section::Bibliography
+                } else if line == "［＃本文終わり］" {
+                    self.state = SectionType::AfterText;
+                    Section::Separator
+                } else {
+                    Section::Body
+                }
+            }
+            SectionType::AfterText => {
+                if line.starts_with("底本：") {
+                    self.state = SectionType::Bibliography;
+                    Section::Bibliography
+                } else {
+                    Section::AfterText
+                }
+            }
+            SectionType::Bibliography => Section::Bibliography,
+        }
+    }
+}
+
+impl Default for SectionScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 著者・翻訳者などの役割
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ContributorRole {
     /// 著者
     Author,
-    /// 翻訳者
+    /// 翻訳者（訳、共訳）
     Translator,
-    /// 編者
+    /// 編者（編、編集）
     Editor,
     /// 編訳者
     Henyaku,
+    /// 校訂者
+    Revisor,
+    /// 監修者
+    Supervisor,
+    /// 挿絵画家（画、挿絵）
+    Illustrator,
+}
+
+impl ContributorRole {
+    /// 人物の行末に書かれる役割の表記（長いものから照合する）
+    const SUFFIXES: &'static [(&'static str, ContributorRole)] = &[
+        ("編訳", ContributorRole::Henyaku),
+        ("共訳", ContributorRole::Translator),
+        ("訳", ContributorRole::Translator),
+        ("校訂", ContributorRole::Revisor),
+        ("編集", ContributorRole::Editor),
+        ("編", ContributorRole::Editor),
+        ("監修", ContributorRole::Supervisor),
+        ("挿絵", ContributorRole::Illustrator),
+    ];
+
+    /// 名前の末尾の文字と区別するため、空白で区切られている場合のみ認める役割の表記
+    const SEPARATED_SUFFIXES: &'static [(&'static str, ContributorRole)] = &[
+        ("画", ContributorRole::Illustrator),
+        ("著", ContributorRole::Author),
+    ];
+
+    /// 役割の名前（`author`、`translator` など）
+    pub fn name(self) -> &'static str {
+        match self {
+            ContributorRole::Author => "author",
+            ContributorRole::Translator => "translator",
+            ContributorRole::Editor => "editor",
+            ContributorRole::Henyaku => "henyaku",
+            ContributorRole::Revisor => "revisor",
+            ContributorRole::Supervisor => "supervisor",
+            ContributorRole::Illustrator => "illustrator",
+        }
+    }
+
+    /// 人物の行から役割を判定し、役割の表記を除いた名前の部分を返す
+    ///
+    /// 役割の表記がなければ `None` を返します。
+    /// `画`・`著` は名前と空白で区切られている場合のみ役割の表記とみなします。
+    ///
+    /// 例: "山田太郎、鈴木花子共訳" → Some((Translator, "山田太郎、鈴木花子"))
+    pub fn from_credit(line: &str) -> Option<(Self, &str)> {
+        let separated = Self::SEPARATED_SUFFIXES
+            .iter()
+            .filter_map(|&(suffix, role)| {
+                let names = line.strip_suffix(suffix)?;
+                names
+                    .ends_with(char::is_whitespace)
+                    .then_some((role, names))
+            });
+        Self::SUFFIXES
+            .iter()
+            .filter_map(|&(suffix, role)| Some((role, line.strip_suffix(suffix)?)))
+            .chain(separated)
+            .find_map(|(role, names)| {
+                let names = names.trim_end();
+                (!names.is_empty()).then_some((role, names))
+            })
+    }
+}
+
+/// 著者・翻訳者など
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Contributor {
+    /// 名前（役割の表記を除く）
+    pub name: String,
+    /// 役割
+    pub role: ContributorRole,
+}
+
+/// ヘッダー行の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HeaderLineKind {
+    /// タイトル
+    Title,
+    /// 副題
+    Subtitle,
+    /// 原題
+    OriginalTitle,
+    /// 原副題
+    OriginalSubtitle,
+    /// 著者・翻訳者などの行
+    Contributor(ContributorRole),
+}
+
+/// 分類の確かさ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Confidence {
+    /// 役割の表記や文字種など、行の内容から判定した
+    High,
+    /// 行の位置だけから推定した（副題と共著者の区別などは付かない）
+    Low,
+}
+
+/// 分類済みのヘッダー行
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeaderLine {
+    /// 行のテキスト
+    pub text: String,
+    /// 行の種類
+    pub kind: HeaderLineKind,
+    /// 分類の確かさ
+    pub confidence: Confidence,
 }
 
 /// ヘッダー情報
-#[derive(Debug, Clone, Default)]
+///
+/// `title` から `henyaku` までは種類ごとの代表の行（同じ種類が複数あれば最後の行）です。
+/// すべての行の分類は `lines`、人物ごとの一覧は `contributors` にあります。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HeaderInfo {
     /// タイトル
     pub title: Option<String>,
@@ -41,10 +292,14 @@ pub struct HeaderInfo {
     pub original_subtitle: Option<String>,
     /// 翻訳者
     pub translator: Option<String>,
-    /// 編者
+    /// 編者（校訂者を含む）
     pub editor: Option<String>,
     /// 編訳者
     pub henyaku: Option<String>,
+    /// ヘッダーの各行の分類
+    pub lines: Vec<HeaderLine>,
+    /// 著者・翻訳者などの一覧（ヘッダーに現れる順）
+    pub contributors: Vec<Contributor>,
 }
 
 impl HeaderInfo {
@@ -83,109 +338,178 @@ impl HeaderInfo {
 ///
 /// 青空文庫のヘッダー形式:
 /// - 1行目: タイトル
-/// - 2行目以降: 著者、副題、原題など（行数によって解釈が変わる）
+/// - 2行目以降: 原題、副題、原副題、著者（行数によって解釈が変わる）
+/// - 最後の行: 著者、または訳・編などの役割を付けた人物
+///
+/// 役割を付けた人物の行が末尾に続く場合（共訳など）は、まとめて最後の行として扱います。
+///
+/// # Examples
+///
+/// ```
+/// use aozora_core::document::{extract_header_info, ContributorRole};
+///
+/// let info = extract_header_info(&["若草物語", "オルコット", "甲野一郎訳", "乙野花子訳", ""]);
+/// assert_eq!(info.author.as_deref(), Some("オルコット"));
+/// let translators: Vec<_> = info
+///     .contributors
+///     .iter()
+///     .filter(|c| c.role == ContributorRole::Translator)
+///     .map(|c| c.name.as_str())
+///     .collect();
+/// assert_eq!(translators, vec!["甲野一郎", "乙野花子"]);
+/// ```
 pub fn extract_header_info(lines: &[&str]) -> HeaderInfo {
-    let mut info = HeaderInfo::default();
-    let mut header_lines = Vec::new();
-
     // 最初の空行までをヘッダーとして収集
-    for line in lines {
-        if line.is_empty() {
-            break;
-        }
-        header_lines.push(*line);
-    }
+    let header_lines: Vec<&str> = lines
+        .iter()
+        .take_while(|line| !line.is_empty())
+        .copied()
+        .collect();
 
-    match header_lines.len() {
-        0 => {}
-        1 => {
-            info.title = Some(header_lines[0].to_string());
-        }
-        2 => {
-            info.title = Some(header_lines[0].to_string());
-            process_person(header_lines[1], &mut info);
-        }
-        3 => {
-            info.title = Some(header_lines[0].to_string());
-            if is_original_title(header_lines[1]) {
-                // パターンA: 作品名、原題、著者
-                info.original_title = Some(header_lines[1].to_string());
-                process_person(header_lines[2], &mut info);
-            } else if process_person(header_lines[2], &mut info) == PersonType::Author {
-                // パターンB: 作品名、副題、著者
-                info.subtitle = Some(header_lines[1].to_string());
-            } else {
-                // パターンC: 作品名、著者、訳者等
-                info.author = Some(header_lines[1].to_string());
+    let mut info = HeaderInfo {
+        lines: classify_header_lines(&header_lines),
+        ..Default::default()
+    };
+    for line in &info.lines {
+        let text = Some(line.text.clone());
+        match line.kind {
+            HeaderLineKind::Title => info.title = text,
+            HeaderLineKind::Subtitle => info.subtitle = text,
+            HeaderLineKind::OriginalTitle => info.original_title = text,
+            HeaderLineKind::OriginalSubtitle => info.original_subtitle = text,
+            HeaderLineKind::Contributor(role) => {
+                match role {
+                    ContributorRole::Author => info.author = text,
+                    ContributorRole::Translator => info.translator = text,
+                    ContributorRole::Editor | ContributorRole::Revisor => info.editor = text,
+                    ContributorRole::Henyaku => info.henyaku = text,
+                    ContributorRole::Supervisor | ContributorRole::Illustrator => {}
+                }
+                info.contributors
+                    .extend(split_contributors(&line.text, role));
             }
         }
-        4 => {
-            info.title = Some(header_lines[0].to_string());
-            if is_original_title(header_lines[1]) {
-                info.original_title = Some(header_lines[1].to_string());
+    }
+    info
+}
+
+/// ヘッダーの各行を分類
+fn classify_header_lines(lines: &[&str]) -> Vec<HeaderLine> {
+    let Some((&title, rest)) = lines.split_first() else {
+        return Vec::new();
+    };
+    let mut classified = vec![header_line(title, HeaderLineKind::Title, Confidence::High)];
+    if rest.is_empty() {
+        return classified;
+    }
+
+    // 末尾の人物の行（著者以外の役割が続く場合はまとめる）
+    let credits = rest
+        .iter()
+        .rev()
+        .take_while(|line| {
+            ContributorRole::from_credit(line).is_some_and(|(r, _)| r != ContributorRole::Author)
+        })
+        .count();
+    let (middle, last) = rest.split_at(rest.len() - credits.max(1));
+    let last_is_author = credits == 0;
+
+    let original = |line: &str, kind| {
+        let confidence = if is_original_title(line) {
+            Confidence::High
+        } else {
+            Confidence::Low
+        };
+        header_line(line, kind, confidence)
+    };
+
+    match middle {
+        [] => {}
+        // 作品名、原題、著者 / 作品名、副題、著者 / 作品名、著者、訳者等
+        [line] => classified.push(if is_original_title(line) {
+            header_line(line, HeaderLineKind::OriginalTitle, Confidence::High)
+        } else if last_is_author {
+            header_line(line, HeaderLineKind::Subtitle, Confidence::Low)
+        } else {
+            person_line(line, Confidence::Low)
+        }),
+        // 作品名、原題または副題、副題または著者、著者等
+        [first, second] => {
+            classified.push(if is_original_title(first) {
+                header_line(first, HeaderLineKind::OriginalTitle, Confidence::High)
             } else {
-                info.subtitle = Some(header_lines[1].to_string());
-            }
-            if process_person(header_lines[3], &mut info) == PersonType::Author {
-                info.subtitle = Some(header_lines[2].to_string());
+                header_line(first, HeaderLineKind::Subtitle, Confidence::Low)
+            });
+            classified.push(if last_is_author {
+                header_line(second, HeaderLineKind::Subtitle, Confidence::Low)
             } else {
-                info.author = Some(header_lines[2].to_string());
-            }
-        }
-        5 => {
-            info.title = Some(header_lines[0].to_string());
-            info.original_title = Some(header_lines[1].to_string());
-            info.subtitle = Some(header_lines[2].to_string());
-            info.author = Some(header_lines[3].to_string());
-            process_person(header_lines[4], &mut info);
+                person_line(second, Confidence::Low)
+            });
         }
-        6 => {
-            info.title = Some(header_lines[0].to_string());
-            info.original_title = Some(header_lines[1].to_string());
-            info.subtitle = Some(header_lines[2].to_string());
-            info.original_subtitle = Some(header_lines[3].to_string());
-            info.author = Some(header_lines[4].to_string());
-            process_person(header_lines[5], &mut info);
+        // 作品名、原題、副題、著者、著者等
+        [first, second, third] => {
+            classified.push(original(first, HeaderLineKind::OriginalTitle));
+            classified.push(header_line(
+                second,
+                HeaderLineKind::Subtitle,
+                Confidence::Low,
+            ));
+            classified.push(person_line(third, Confidence::Low));
         }
-        _ => {
-            // 7行以上は6行と同様に処理
-            info.title = Some(header_lines[0].to_string());
-            info.original_title = Some(header_lines[1].to_string());
-            info.subtitle = Some(header_lines[2].to_string());
-            info.original_subtitle = Some(header_lines[3].to_string());
-            info.author = Some(header_lines[4].to_string());
-            process_person(header_lines[5], &mut info);
+        // 作品名、原題、副題、原副題、著者（以降は人物）、著者等
+        [first, second, third, people @ ..] => {
+            classified.push(original(first, HeaderLineKind::OriginalTitle));
+            classified.push(header_line(
+                second,
+                HeaderLineKind::Subtitle,
+                Confidence::Low,
+            ));
+            classified.push(original(third, HeaderLineKind::OriginalSubtitle));
+            classified.extend(people.iter().map(|line| person_line(line, Confidence::Low)));
         }
     }
 
-    info
+    classified.extend(last.iter().map(|line| person_line(line, Confidence::High)));
+    classified
 }
 
-/// 人物名を処理してHeaderInfoに設定、種別を返す
-fn process_person(s: &str, info: &mut HeaderInfo) -> PersonType {
-    let person_type = detect_person_type(s);
-    match person_type {
-        PersonType::Editor => info.editor = Some(s.to_string()),
-        PersonType::Translator => info.translator = Some(s.to_string()),
-        PersonType::Henyaku => info.henyaku = Some(s.to_string()),
-        PersonType::Author => info.author = Some(s.to_string()),
+/// 分類済みのヘッダー行を作成
+fn header_line(text: &str, kind: HeaderLineKind, confidence: Confidence) -> HeaderLine {
+    HeaderLine {
+        text: text.to_string(),
+        kind,
+        confidence,
     }
-    person_type
 }
 
-/// 人物の種別を判定
-fn detect_person_type(s: &str) -> PersonType {
-    if s.ends_with("編訳") {
-        PersonType::Henyaku
-    } else if s.ends_with("校訂") || s.ends_with('編') || s.ends_with("編集") {
-        PersonType::Editor
-    } else if s.ends_with('訳') {
-        PersonType::Translator
-    } else {
-        PersonType::Author
+/// 人物の行を分類
+///
+/// 役割の表記があれば確かな分類、なければ `confidence` の確かさの著者とします。
+fn person_line(text: &str, confidence: Confidence) -> HeaderLine {
+    match ContributorRole::from_credit(text) {
+        Some((role, _)) => header_line(text, HeaderLineKind::Contributor(role), Confidence::High),
+        None => header_line(
+            text,
+            HeaderLineKind::Contributor(ContributorRole::Author),
+            confidence,
+        ),
     }
 }
 
+/// 人物の行を「、」で区切って人物の一覧にする
+fn split_contributors(text: &str, role: ContributorRole) -> Vec<Contributor> {
+    let names = ContributorRole::from_credit(text).map_or(text, |(_, names)| names);
+    names
+        .split('、')
+        .map(|name| name.trim_matches(|c: char| c.is_whitespace()))
+        .filter(|name| !name.is_empty())
+        .map(|name| Contributor {
+            name: name.to_string(),
+            role,
+        })
+        .collect()
+}
+
 /// 原題かどうかを判定
 ///
 /// 以下の文字のみで構成される場合に原題と判定:
@@ -238,49 +562,91 @@ fn is_original_title(s: &str) -> bool {
 /// assert_eq!(body, vec!["本文1行目"]);
 /// ```
 pub fn extract_body_lines<'a>(lines: &[&'a str]) -> Vec<&'a str> {
-    let mut result = Vec::new();
-    let mut section = SectionType::Header;
+    lines[body_line_range(lines)].to_vec()
+}
 
-    for line in lines {
-        match section {
-            SectionType::Header => {
-                // 空行でヘッダー終了
-                if line.is_empty() {
-                    section = SectionType::AfterHeader;
-                }
+/// 文書中の本文行の範囲（行インデックス）を返す
+///
+/// [`extract_body_lines`] が返す行は、この範囲の行と一致します。
+///
+/// # Examples
+///
+/// ```
+/// use aozora_core::document::body_line_range;
+///
+/// let lines = vec!["タイトル", "著者", "", "本文1行目", "底本：〇〇文庫"];
+/// assert_eq!(body_line_range(&lines), 3..4);
+/// ```
+pub fn body_line_range(lines: &[&str]) -> Range<usize> {
+    scan_sections(lines).body
+}
+
+/// 文書中の記号説明（`---` で囲まれた注記セクション）の範囲（行インデックス）を返す
+///
+/// 範囲には `---` の行自体は含みません。注記セクションがない場合は空の範囲を返します。
+///
+/// # Examples
+///
+/// ```
+/// use aozora_core::document::legend_line_range;
+///
+/// let lines = vec![
+///     "タイトル", "著者", "",
+///     "-------", "【テキスト中に現れる記号について】", "-------",
+///     "本文1行目",
+/// ];
+/// assert_eq!(legend_line_range(&lines), 4..5);
+/// ```
+pub fn legend_line_range(lines: &[&str]) -> Range<usize> {
+    scan_sections(lines).legend
+}
+
+/// セクションの範囲
+struct SectionRanges {
+    legend: Range<usize>,
+    body: Range<usize>,
+}
+
+/// 前付けから本文までのセクションを走査
+fn scan_sections(lines: &[&str]) -> SectionRanges {
+    let mut legend = lines.len()..lines.len();
+    let mut start = None;
+    let mut scanner = SectionScanner::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        let previous = scanner.state;
+        match scanner.next_line(line) {
+            Section::Separator if scanner.state == SectionType::Chuuki => {
+                legend = i + 1..lines.len();
             }
-            SectionType::AfterHeader => {
-                // 空行後、---で始まれば注記セクション、そうでなければ本文
-                if line.starts_with("---") {
-                    section = SectionType::Chuuki;
-                } else if line.is_empty() {
-                    // 連続する空行はスキップ
-                } else {
-                    // 本文開始
-                    if line.starts_with("底本：") {
-                        break;
-                    }
-                    result.push(*line);
-                    section = SectionType::Body;
-                }
+            Section::Separator if previous == SectionType::Chuuki => {
+                legend.end = i;
+                start = Some(i + 1);
             }
-            SectionType::Chuuki => {
-                // ---で注記セクション終了
-                if line.starts_with("---") {
-                    section = SectionType::Body;
-                }
+            Section::Body => {
+                start.get_or_insert(i);
             }
-            SectionType::Body => {
-                // 底本：または［＃本文終わり］で本文終了
-                if line.starts_with("底本：") || *line == "［＃本文終わり］" {
-                    break;
-                }
-                result.push(*line);
+            Section::Separator if previous == SectionType::Body => {
+                return SectionRanges {
+                    legend,
+                    body: start.unwrap_or(i)..i,
+                };
+            }
+            Section::Bibliography => {
+                return SectionRanges {
+                    legend,
+                    body: start.unwrap_or(i)..i,
+                };
             }
+            _ => {}
         }
     }
 
-    result
+    let body = match start {
+        Some(start) => start.min(lines.len())..lines.len(),
+        None => lines.len()..lines.len(),
+    };
+    SectionRanges { legend, body }
 }
 
 /// 文書から本文終わり後のテキスト（after_text）を抽出
@@ -288,42 +654,210 @@ pub fn extract_body_lines<'a>(lines: &[&'a str]) -> Vec<&'a str> {
 /// `［＃本文終わり］` から `底本：` までの行を抽出します。
 /// `［＃本文終わり］` がない場合は空のVecを返します。
 pub fn extract_after_text_lines<'a>(lines: &[&'a str]) -> Vec<&'a str> {
-    let mut result = Vec::new();
-    let mut in_after_text = false;
-
-    for line in lines {
-        if *line == "［＃本文終わり］" {
-            in_after_text = true;
-            continue; // ［＃本文終わり］自体は含めない
-        }
-        if in_after_text {
-            if line.starts_with("底本：") {
-                break;
-            }
-            result.push(*line);
-        }
-    }
+    lines[after_text_line_range(lines)].to_vec()
+}
 
-    result
+/// 文書中の本文終わり後のテキストの範囲（行インデックス）を返す
+///
+/// [`extract_after_text_lines`] が返す行は、この範囲の行と一致します。
+pub fn after_text_line_range(lines: &[&str]) -> Range<usize> {
+    let Some(marker) = lines.iter().position(|line| *line == "［＃本文終わり］") else {
+        return lines.len()..lines.len();
+    };
+    let start = marker + 1; // ［＃本文終わり］自体は含めない
+    let end = lines[start..]
+        .iter()
+        .position(|line| line.starts_with("底本："))
+        .map_or(lines.len(), |i| start + i);
+    start..end
 }
 
 /// 文書から底本情報（bibliographical information）を抽出
 ///
 /// 「底本：」で始まる行から最後までを抽出します。
 pub fn extract_bibliographical_lines<'a>(lines: &[&'a str]) -> Vec<&'a str> {
-    let mut result = Vec::new();
-    let mut in_biblio = false;
+    lines[bibliographical_line_range(lines)].to_vec()
+}
+
+/// 文書中の底本情報の範囲（行インデックス）を返す
+///
+/// [`extract_bibliographical_lines`] が返す行は、この範囲の行と一致します。
+pub fn bibliographical_line_range(lines: &[&str]) -> Range<usize> {
+    let start = lines
+        .iter()
+        .position(|line| line.starts_with("底本："))
+        .unwrap_or(lines.len());
+    start..lines.len()
+}
 
-    for line in lines {
-        if line.starts_with("底本：") {
-            in_biblio = true;
+/// 解析済みの1行
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DocumentLine {
+    /// 行番号（0始まり）
+    pub line: usize,
+    /// 行のテキスト
+    pub text: String,
+    /// ノード列（位置は文書全体でのバイト位置）
+    pub nodes: Vec<Spanned<Node>>,
+}
+
+impl DocumentLine {
+    /// 1行を解析
+    ///
+    /// `offset` は文書全体での行頭のバイト位置です。
+    pub fn parse(line: usize, offset: usize, text: &str) -> Self {
+        let tokens = tokenize_with_spans(text);
+        Self::from_tokens(line, offset, text, &tokens)
+    }
+
+    /// トークン化済みの1行から作成
+    pub(crate) fn from_tokens(
+        line: usize,
+        offset: usize,
+        text: &str,
+        tokens: &[Spanned<Token>],
+    ) -> Self {
+        let mut nodes = parse_with_spans(tokens);
+        // 行内ルビを解決
+        resolve_inline_ruby_with_spans(&mut nodes);
+        let nodes = nodes
+            .into_iter()
+            .map(|node| Spanned::new(node.value, node.span.shift(offset)))
+            .collect();
+        Self {
+            line,
+            text: text.to_string(),
+            nodes,
         }
-        if in_biblio {
-            result.push(*line);
+    }
+
+    /// 位置情報を除いたノード列
+    pub fn to_nodes(&self) -> Vec<Node> {
+        self.nodes.iter().map(|node| node.value.clone()).collect()
+    }
+}
+
+/// 青空文庫形式の文書全体
+///
+/// ヘッダー、記号説明、本文、本文終わり後のテキスト、底本情報の各セクションを
+/// まとめて保持します。本文などのノード列は行ごとにまとめられています。
+///
+/// # Examples
+///
+/// ```
+/// use aozora_core::document::AozoraDocument;
+///
+/// let text = "吾輩は猫である\n夏目漱石\n\n吾輩《わがはい》は猫である。\n\n底本：「吾輩は猫である」";
+/// let document = AozoraDocument::parse(text);
+/// assert_eq!(document.header.title.as_deref(), Some("吾輩は猫である"));
+/// assert_eq!(document.body.len(), 2);
+/// assert_eq!(document.body[0].line, 3);
+/// assert_eq!(document.bibliography.len(), 1);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AozoraDocument {
+    /// ヘッダー情報
+    pub header: HeaderInfo,
+    /// 記号説明（`---` で囲まれた注記セクション）の行
+    pub legend: Vec<String>,
+    /// 本文
+    pub body: Vec<DocumentLine>,
+    /// 本文終わり後のテキスト
+    pub after_text: Vec<DocumentLine>,
+    /// 底本情報
+    pub bibliography: Vec<DocumentLine>,
+}
+
+impl AozoraDocument {
+    /// テキスト全体を解析
+    pub fn parse(text: &str) -> Self {
+        let lines: Vec<&str> = text.lines().collect();
+        let index = LineIndex::new(text);
+        let parse_range = |range: Range<usize>| -> Vec<DocumentLine> {
+            range
+                .map(|i| {
+                    let offset = index.line_start(i).unwrap_or(text.len());
+                    DocumentLine::parse(i, offset, lines[i])
+                })
+                .collect()
+        };
+
+        Self {
+            header: extract_header_info(&lines),
+            legend: lines[legend_line_range(&lines)]
+                .iter()
+                .map(|line| line.to_string())
+                .collect(),
+            body: parse_range(body_line_range(&lines)),
+            after_text: parse_range(after_text_line_range(&lines)),
+            bibliography: parse_range(bibliographical_line_range(&lines)),
         }
     }
 
-    result
+    /// 本文のブロックの木構造
+    ///
+    /// 対応の規則は [`crate::block_tree`] を参照してください。
+    pub fn body_tree(&self) -> Vec<TreeNode> {
+        build_block_tree(&self.body)
+    }
+
+    /// 記号説明を解析
+    ///
+    /// 解析の規則は [`crate::legend`] を参照してください。
+    pub fn legend_info(&self) -> Legend {
+        let lines: Vec<&str> = self.legend.iter().map(String::as_str).collect();
+        parse_legend(&lines)
+    }
+
+    /// 底本情報を解析
+    ///
+    /// 解析の規則は [`crate::biblio`] を参照してください。
+    pub fn biblio_info(&self) -> BiblioInfo {
+        let lines: Vec<&str> = self.bibliography.iter().map(|l| l.text.as_str()).collect();
+        extract_biblio_info(&lines)
+    }
+
+    /// 本文の見出しの階層（目次）
+    ///
+    /// 見出しの集め方は [`crate::outline`] を参照してください。
+    pub fn outline(&self) -> Vec<OutlineEntry> {
+        build_outline(&self.body)
+    }
+
+    /// 本文を改ページ・改丁・改見開きの位置でページに分割
+    ///
+    /// 区切りの注記を含む行は、次のページの先頭になります。改段では分割しません。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aozora_core::document::AozoraDocument;
+    ///
+    /// let document = AozoraDocument::parse("題名\n\n一章\n［＃改ページ］\n二章\n");
+    /// let pages = document.body_pages();
+    /// assert_eq!(pages.len(), 2);
+    /// assert_eq!(pages[1][1].text, "二章");
+    /// ```
+    pub fn body_pages(&self) -> Vec<&[DocumentLine]> {
+        let mut pages = Vec::new();
+        let mut start = 0;
+        for (i, line) in self.body.iter().enumerate() {
+            let is_break = line
+                .nodes
+                .iter()
+                .any(|node| matches!(node.value, Node::Break { kind } if kind.is_page_break()));
+            if is_break && i > start {
+                pages.push(&self.body[start..i]);
+                start = i;
+            }
+        }
+        if start < self.body.len() {
+            pages.push(&self.body[start..]);
+        }
+        pages
+    }
 }
 
 #[cfg(test)]
@@ -345,6 +879,57 @@ mod tests {
         assert_eq!(body, vec!["本文1行目", "本文2行目", ""]);
     }
 
+    #[test]
+    fn test_section_scanner() {
+        let lines = [
+            "タイトル",
+            "",
+            "-------",
+            "《》：ルビ",
+            "-------",
+            "本文",
+            "［＃本文終わり］",
+            "後書き",
+            "底本：青空文庫",
+            "入力：某",
+        ];
+        let mut scanner = SectionScanner::new();
+        let sections: Vec<Section> = lines.iter().map(|l| scanner.next_line(l)).collect();
+        assert_eq!(
+            sections,
+            vec![
+                Section::Header,
+                Section::Separator,
+                Section::Separator,
+                Section::Legend,
+                Section::Separator,
+                Section::Body,
+                Section::Separator,
+                Section::AfterText,
+                Section::Bibliography,
+                Section::Bibliography,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_body_pages() {
+        let text = "題名\n\n［＃改ページ］\n一\n［＃改段］\n二\n［＃改丁］\n三\n";
+        let document = AozoraDocument::parse(text);
+        let pages: Vec<Vec<&str>> = document
+            .body_pages()
+            .iter()
+            .map(|page| page.iter().map(|line| line.text.as_str()).collect())
+            .collect();
+        assert_eq!(
+            pages,
+            vec![
+                vec!["［＃改ページ］", "一", "［＃改段］", "二"],
+                vec!["［＃改丁］", "三"],
+            ]
+        );
+    }
+
     #[test]
     fn test_with_chuuki() {
         let lines = vec![
@@ -531,13 +1116,150 @@ mod tests {
     }
 
     #[test]
-    fn test_detect_person_type() {
-        assert_eq!(detect_person_type("山田太郎"), PersonType::Author);
-        assert_eq!(detect_person_type("山田太郎訳"), PersonType::Translator);
-        assert_eq!(detect_person_type("山田太郎編"), PersonType::Editor);
-        assert_eq!(detect_person_type("山田太郎編集"), PersonType::Editor);
-        assert_eq!(detect_person_type("山田太郎校訂"), PersonType::Editor);
-        assert_eq!(detect_person_type("山田太郎編訳"), PersonType::Henyaku);
+    fn test_contributor_role_from_credit() {
+        assert_eq!(ContributorRole::from_credit("山田太郎"), None);
+        assert_eq!(
+            ContributorRole::from_credit("山田太郎訳"),
+            Some((ContributorRole::Translator, "山田太郎"))
+        );
+        assert_eq!(
+            ContributorRole::from_credit("山田太郎、鈴木花子共訳"),
+            Some((ContributorRole::Translator, "山田太郎、鈴木花子"))
+        );
+        assert_eq!(
+            ContributorRole::from_credit("山田太郎編"),
+            Some((ContributorRole::Editor, "山田太郎"))
+        );
+        assert_eq!(
+            ContributorRole::from_credit("山田太郎編集"),
+            Some((ContributorRole::Editor, "山田太郎"))
+        );
+        assert_eq!(
+            ContributorRole::from_credit("山田太郎校訂"),
+            Some((ContributorRole::Revisor, "山田太郎"))
+        );
+        assert_eq!(
+            ContributorRole::from_credit("山田太郎編訳"),
+            Some((ContributorRole::Henyaku, "山田太郎"))
+        );
+        assert_eq!(
+            ContributorRole::from_credit("山田太郎　監修"),
+            Some((ContributorRole::Supervisor, "山田太郎"))
+        );
+        assert_eq!(
+            ContributorRole::from_credit("山田太郎　画"),
+            Some((ContributorRole::Illustrator, "山田太郎"))
+        );
+        assert_eq!(
+            ContributorRole::from_credit("山田太郎挿絵"),
+            Some((ContributorRole::Illustrator, "山田太郎"))
+        );
+        assert_eq!(
+            ContributorRole::from_credit("山田太郎 著"),
+            Some((ContributorRole::Author, "山田太郎"))
+        );
+        // 名前の末尾の「画」「著」は役割の表記ではない
+        assert_eq!(ContributorRole::from_credit("小林映画"), None);
+        assert_eq!(ContributorRole::from_credit("鈴木顕著"), None);
+    }
+
+    #[test]
+    fn test_extract_header_contributors() {
+        // 複数の著者と共訳
+        let lines = vec!["短編集", "甲野一郎、乙野花子", "丙野次郎、丁野三郎共訳", ""];
+        let info = extract_header_info(&lines);
+        let names: Vec<_> = info
+            .contributors
+            .iter()
+            .map(|c| (c.name.as_str(), c.role))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("甲野一郎", ContributorRole::Author),
+                ("乙野花子", ContributorRole::Author),
+                ("丙野次郎", ContributorRole::Translator),
+                ("丁野三郎", ContributorRole::Translator),
+            ]
+        );
+        assert_eq!(info.subtitle, None);
+    }
+
+    #[test]
+    fn test_extract_header_multiple_credits() {
+        // 訳者・校訂者・挿絵が続いても著者を副題と取り違えない
+        let lines = vec!["タイトル", "著者名", "訳者訳", "校訂者校訂", "画家　画", ""];
+        let info = extract_header_info(&lines);
+        assert_eq!(info.author, Some("著者名".to_string()));
+        assert_eq!(info.translator, Some("訳者訳".to_string()));
+        assert_eq!(info.editor, Some("校訂者校訂".to_string()));
+        assert_eq!(info.original_title, None);
+        assert_eq!(info.subtitle, None);
+        let roles: Vec<_> = info.contributors.iter().map(|c| c.role).collect();
+        assert_eq!(
+            roles,
+            vec![
+                ContributorRole::Author,
+                ContributorRole::Translator,
+                ContributorRole::Revisor,
+                ContributorRole::Illustrator,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_header_confidence() {
+        let kinds = |lines: &[&str]| -> Vec<(HeaderLineKind, Confidence)> {
+            extract_header_info(lines)
+                .lines
+                .iter()
+                .map(|l| (l.kind, l.confidence))
+                .collect()
+        };
+        let author = HeaderLineKind::Contributor(ContributorRole::Author);
+
+        // 原題は文字種で判定できる
+        assert_eq!(
+            kinds(&["タイトル", "TITLE", "著者名"]),
+            vec![
+                (HeaderLineKind::Title, Confidence::High),
+                (HeaderLineKind::OriginalTitle, Confidence::High),
+                (author, Confidence::High),
+            ]
+        );
+        // 副題か共著者かは位置からの推定
+        assert_eq!(
+            kinds(&["タイトル", "副題", "著者名"])[1],
+            (HeaderLineKind::Subtitle, Confidence::Low)
+        );
+        assert_eq!(
+            kinds(&["タイトル", "著者名", "訳者訳"])[1],
+            (author, Confidence::Low)
+        );
+    }
+
+    #[test]
+    fn test_extract_header_more_than_6lines() {
+        let lines = vec![
+            "タイトル",
+            "ORIGINAL TITLE",
+            "副題",
+            "ORIGINAL SUBTITLE",
+            "著者名",
+            "共著者名",
+            "訳者訳",
+            "",
+        ];
+        let info = extract_header_info(&lines);
+        assert_eq!(info.lines.len(), 7);
+        let authors: Vec<_> = info
+            .contributors
+            .iter()
+            .filter(|c| c.role == ContributorRole::Author)
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(authors, vec!["著者名", "共著者名"]);
+        assert_eq!(info.translator, Some("訳者訳".to_string()));
     }
 
     #[test]
@@ -551,7 +1273,67 @@ mod tests {
             translator: Some("訳者訳".to_string()),
             editor: None,
             henyaku: None,
+            ..Default::default()
         };
         assert_eq!(info.html_title(), "著者名 訳者訳 タイトル");
     }
+
+    #[test]
+    fn test_legend_line_range() {
+        let lines = vec![
+            "タイトル",
+            "",
+            "-------",
+            "《》：ルビ",
+            "［＃］：入力者注",
+            "-------",
+            "本文",
+        ];
+        assert_eq!(legend_line_range(&lines), 3..5);
+        assert_eq!(legend_line_range(&["タイトル", "", "本文"]), 3..3);
+    }
+
+    #[test]
+    fn test_aozora_document() {
+        let text = "題名\r\n著者\r\n\r\n-------\r\n《》：ルビ\r\n-------\r\n私の東京《とうきょう》\r\n［＃本文終わり］\r\n後記\r\n底本：某文庫\r\n";
+        let document = AozoraDocument::parse(text);
+        assert_eq!(document.header.title.as_deref(), Some("題名"));
+        assert_eq!(document.header.author.as_deref(), Some("著者"));
+        assert_eq!(document.legend, vec!["《》：ルビ"]);
+        assert_eq!(document.legend_info().entries[0].meaning, "ルビ");
+
+        assert_eq!(document.body.len(), 1);
+        let line = &document.body[0];
+        assert_eq!(line.line, 6);
+        assert_eq!(line.text, "私の東京《とうきょう》");
+        assert!(matches!(&line.nodes[1].value, Node::Ruby { .. }));
+        // 位置は文書全体でのバイト位置
+        let ruby = line.nodes[1].span;
+        assert_eq!(&text[ruby.start..ruby.end], "東京《とうきょう》");
+
+        let after: Vec<_> = document
+            .after_text
+            .iter()
+            .map(|l| l.text.as_str())
+            .collect();
+        assert_eq!(after, vec!["後記"]);
+        assert_eq!(document.bibliography[0].text, "底本：某文庫");
+        assert_eq!(
+            document.bibliography[0].to_nodes(),
+            vec![Node::Text("底本：某文庫".to_string())]
+        );
+        let biblio = document.biblio_info();
+        assert_eq!(biblio.base_texts[0].publisher.as_deref(), Some("某文庫"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_aozora_document_serde() {
+        let document = AozoraDocument::parse("題名\n著者\n\n本文《ほんぶん》\n底本：某文庫");
+        let json = serde_json::to_value(&document).unwrap();
+        assert_eq!(json["header"]["title"], "題名");
+        assert_eq!(json["body"][0]["line"], 3);
+        let restored: AozoraDocument = serde_json::from_value(json).unwrap();
+        assert_eq!(restored, document);
+    }
 }
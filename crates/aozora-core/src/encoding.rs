@@ -1,13 +1,112 @@
 //! エンコーディング判定・変換
 
-use encoding_rs::SHIFT_JIS;
+use std::io::{self, BufRead};
+
+use encoding_rs::{
+    EncoderResult, Encoding, EUC_JP, ISO_2022_JP, SHIFT_JIS, UTF_16BE, UTF_16LE, UTF_8,
+};
+
+/// UTF-8 BOM
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+
+/// UTF-16LE BOM
+const UTF16LE_BOM: &[u8] = &[0xFF, 0xFE];
+
+/// UTF-16BE BOM
+const UTF16BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+/// エスケープ（ISO-2022-JPの文字集合の切り替え）
+const ESC: u8 = 0x1B;
+
+/// UTF-16の判定に使う先頭のバイト数
+const UTF16_SNIFF_LEN: usize = 4096;
+
+/// BOMのないUTF-16とみなすのに必要な符号単位の数
+const UTF16_MIN_UNITS: usize = 4;
+
+/// BOMのないUTF-16とみなすのに必要なゼロのバイトの数
+const UTF16_MIN_ZEROS: usize = 2;
+
+/// 判定したエンコーディング
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TextEncoding {
+    /// UTF-8
+    Utf8,
+    /// UTF-16（リトルエンディアン）
+    Utf16Le,
+    /// UTF-16（ビッグエンディアン）
+    Utf16Be,
+    /// Shift_JIS
+    ShiftJis,
+    /// CP932（NEC特殊文字・IBM拡張文字を含むShift_JIS）
+    Cp932,
+    /// EUC-JP
+    EucJp,
+    /// ISO-2022-JP
+    Iso2022Jp,
+}
+
+impl TextEncoding {
+    /// エンコーディング名
+    pub fn name(self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Utf16Le => "UTF-16LE",
+            TextEncoding::Utf16Be => "UTF-16BE",
+            TextEncoding::ShiftJis => "Shift_JIS",
+            TextEncoding::Cp932 => "CP932",
+            TextEncoding::EucJp => "EUC-JP",
+            TextEncoding::Iso2022Jp => "ISO-2022-JP",
+        }
+    }
+
+    /// BOMのバイト列（BOMのないエンコーディングでは空）
+    pub fn bom(self) -> &'static [u8] {
+        match self {
+            TextEncoding::Utf8 => UTF8_BOM,
+            TextEncoding::Utf16Le => UTF16LE_BOM,
+            TextEncoding::Utf16Be => UTF16BE_BOM,
+            _ => &[],
+        }
+    }
+
+    /// デコードに使うエンコーディング
+    ///
+    /// encoding_rs のShift_JISはCP932の拡張文字も扱えます。
+    fn encoding(self) -> &'static Encoding {
+        match self {
+            TextEncoding::Utf8 => UTF_8,
+            TextEncoding::Utf16Le => UTF_16LE,
+            TextEncoding::Utf16Be => UTF_16BE,
+            TextEncoding::ShiftJis | TextEncoding::Cp932 => SHIFT_JIS,
+            TextEncoding::EucJp => EUC_JP,
+            TextEncoding::Iso2022Jp => ISO_2022_JP,
+        }
+    }
+}
+
+/// デコード結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decoded {
+    /// デコードしたテキスト（BOMは除く）
+    pub text: String,
+    /// 判定したエンコーディング
+    pub encoding: TextEncoding,
+    /// BOMがあったかどうか
+    pub bom: bool,
+    /// 判定の確かさ（0.0〜1.0）
+    ///
+    /// BOMやUTF-8としての妥当性で判定できた場合は1.0、それ以外は
+    /// デコード結果の非ASCII文字のうち日本語の文字（かな・漢字・全角記号）の割合です。
+    pub confidence: f32,
+    /// 不正なバイト列を置換文字（U+FFFD）に置き換えたかどうか
+    pub lossy: bool,
+}
 
 /// バイト列のエンコーディングを判定してUTF-8文字列に変換
 ///
-/// # 判定ロジック
-/// 1. UTF-8 BOMがあればUTF-8
-/// 2. UTF-8として妥当ならUTF-8
-/// 3. それ以外はShift_JIS
+/// 判定の詳細は [`decode`] を参照してください。
 ///
 /// # Examples
 ///
@@ -18,21 +117,371 @@ use encoding_rs::SHIFT_JIS;
 /// assert_eq!(decode_to_utf8(utf8_bytes), "こんにちは");
 /// ```
 pub fn decode_to_utf8(bytes: &[u8]) -> String {
-    // BOMチェック
-    let bytes = if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
-        &bytes[3..] // BOMをスキップ
+    decode(bytes).text
+}
+
+/// バイト列のエンコーディングを判定してデコード
+///
+/// # 判定ロジック
+/// 1. BOMがあればUTF-8、UTF-16LE、UTF-16BE
+/// 2. ゼロのバイトが偶数番目か奇数番目の一方だけにあればUTF-16（BOMなし）
+/// 3. ASCIIのみでエスケープシーケンスを含めばISO-2022-JP
+/// 4. UTF-8として妥当ならUTF-8
+/// 5. Shift_JISとEUC-JPのうち、不正なバイト列がなく日本語の文字の割合が高いほう
+///    （同じならShift_JIS）。Shift_JISでNEC特殊文字・IBM拡張文字を含めばCP932
+///
+/// # Examples
+///
+/// ```
+/// use aozora_core::encoding::{decode, TextEncoding};
+///
+/// let (bytes, _, _) = encoding_rs::EUC_JP.encode("吾輩は猫である");
+/// let decoded = decode(&bytes);
+/// assert_eq!(decoded.text, "吾輩は猫である");
+/// assert_eq!(decoded.encoding, TextEncoding::EucJp);
+/// assert!(!decoded.lossy);
+/// ```
+pub fn decode(bytes: &[u8]) -> Decoded {
+    for (bom, encoding) in [
+        (UTF8_BOM, TextEncoding::Utf8),
+        (UTF16LE_BOM, TextEncoding::Utf16Le),
+        (UTF16BE_BOM, TextEncoding::Utf16Be),
+    ] {
+        if let Some(rest) = bytes.strip_prefix(bom) {
+            let mut decoded = decode_as(rest, encoding);
+            decoded.bom = true;
+            decoded.confidence = 1.0;
+            return decoded;
+        }
+    }
+
+    if let Some(encoding) = sniff_utf16(bytes) {
+        return decode_as(bytes, encoding);
+    }
+
+    if is_iso_2022_jp(bytes) {
+        let mut decoded = decode_as(bytes, TextEncoding::Iso2022Jp);
+        if !decoded.lossy {
+            decoded.confidence = 1.0;
+        }
+        return decoded;
+    }
+
+    if let Ok(s) = std::str::from_utf8(bytes) {
+        return Decoded {
+            text: s.to_owned(),
+            encoding: TextEncoding::Utf8,
+            bom: false,
+            confidence: 1.0,
+            lossy: false,
+        };
+    }
+
+    // Shift_JISとEUC-JPを比べる（置換なしを優先し、同点ならShift_JIS）
+    let shift_jis = decode_as(bytes, TextEncoding::ShiftJis);
+    let euc_jp = decode_as(bytes, TextEncoding::EucJp);
+    let rank = |d: &Decoded| (!d.lossy, d.confidence);
+    let mut decoded = if rank(&euc_jp) > rank(&shift_jis) {
+        euc_jp
     } else {
-        bytes
+        shift_jis
     };
+    if decoded.encoding == TextEncoding::ShiftJis && has_cp932_extension(bytes) {
+        decoded.encoding = TextEncoding::Cp932;
+    }
+    decoded
+}
 
-    // UTF-8として妥当かチェック
-    if let Ok(s) = std::str::from_utf8(bytes) {
-        return s.to_owned();
+/// 指定のエンコーディングでデコード（BOMは扱わない）
+fn decode_as(bytes: &[u8], encoding: TextEncoding) -> Decoded {
+    let (cow, lossy) = encoding.encoding().decode_without_bom_handling(bytes);
+    let text = cow.into_owned();
+    let confidence = japanese_ratio(&text);
+    Decoded {
+        text,
+        encoding,
+        bom: false,
+        confidence: if lossy { confidence / 2.0 } else { confidence },
+        lossy,
     }
+}
 
-    // Shift_JISとしてデコード
-    let (cow, _, _) = SHIFT_JIS.decode(bytes);
-    cow.into_owned()
+/// テキストを指定のエンコーディングで追記（BOMは含まない）
+///
+/// そのエンコーディングで表せない文字は、文字ごとに `replace` の返す文字列に置き換えます。
+/// 置き換えた文字列にも表せない文字があれば数値文字参照（`&#N;`）になります。
+/// `replace` がエラーを返した場合はそのエラーを返します。
+///
+/// # Examples
+///
+/// ```
+/// use aozora_core::encoding::{encode, TextEncoding};
+///
+/// let mut output = Vec::new();
+/// encode("猫😀", TextEncoding::ShiftJis, &mut output, |_| Ok("〓".to_string())).unwrap();
+/// assert_eq!(output, encoding_rs::SHIFT_JIS.encode("猫〓").0.as_ref());
+///
+/// let mut output = Vec::new();
+/// encode("猫", TextEncoding::Utf16Be, &mut output, |_| unreachable!()).unwrap();
+/// assert_eq!(output, [0x73, 0x2B]);
+/// ```
+pub fn encode<F>(
+    text: &str,
+    encoding: TextEncoding,
+    output: &mut Vec<u8>,
+    mut replace: F,
+) -> io::Result<()>
+where
+    F: FnMut(char) -> io::Result<String>,
+{
+    // encoding_rs はUTF-16へのエンコードに対応しない（UTF-8になる）
+    match encoding {
+        TextEncoding::Utf8 => {
+            output.extend_from_slice(text.as_bytes());
+            return Ok(());
+        }
+        TextEncoding::Utf16Le => {
+            output.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+            return Ok(());
+        }
+        TextEncoding::Utf16Be => {
+            output.extend(text.encode_utf16().flat_map(u16::to_be_bytes));
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let (encoded, _, had_unmappable) = encoding.encoding().encode(text);
+    if !had_unmappable {
+        output.extend_from_slice(&encoded);
+        return Ok(());
+    }
+
+    // 表せない文字を置き換えてからエンコードし直す
+    // （ISO-2022-JPは状態を持つため、エンコードの途中では置き換えない）
+    let mut replaced = String::with_capacity(text.len());
+    let mut encoder = encoding.encoding().new_encoder();
+    let mut scratch = Vec::new();
+    let mut rest = text;
+    loop {
+        scratch.clear();
+        scratch.reserve(rest.len() + 16);
+        let (result, read) =
+            encoder.encode_from_utf8_to_vec_without_replacement(rest, &mut scratch, false);
+        match result {
+            EncoderResult::InputEmpty => {
+                replaced.push_str(rest);
+                break;
+            }
+            EncoderResult::OutputFull => replaced.push_str(&rest[..read]),
+            EncoderResult::Unmappable(c) => {
+                replaced.push_str(&rest[..read - c.len_utf8()]);
+                replaced.push_str(&replace(c)?);
+            }
+        }
+        rest = &rest[read..];
+    }
+    output.extend_from_slice(&encoding.encoding().encode(&replaced).0);
+    Ok(())
+}
+
+/// 非ASCII文字のうち日本語の文字（かな・漢字・全角記号）の割合
+///
+/// 非ASCII文字がなければ1.0を返します。
+fn japanese_ratio(text: &str) -> f32 {
+    let (japanese, total) =
+        text.chars()
+            .filter(|c| !c.is_ascii())
+            .fold((0usize, 0usize), |(japanese, total), c| {
+                let is_japanese = matches!(c,
+                    '\u{3000}'..='\u{30FF}' // 全角記号、ひらがな、カタカナ
+                    | '\u{4E00}'..='\u{9FFF}' // 漢字
+                    | '\u{FF01}'..='\u{FF5E}' // 全角英数字・記号
+                );
+                (japanese + usize::from(is_japanese), total + 1)
+            });
+    if total == 0 {
+        1.0
+    } else {
+        japanese as f32 / total as f32
+    }
+}
+
+/// BOMのないUTF-16を判定
+///
+/// 改行などのASCII文字はUTF-16では上位か下位のバイトがゼロになるため、
+/// ゼロのバイトの位置が偶数番目か奇数番目の一方に偏っていればUTF-16とみなします。
+/// 偶然のゼロのバイトで誤判定しないよう、先頭に [`UTF16_MIN_UNITS`] 以上の符号単位と、
+/// 一方の位置だけに [`UTF16_MIN_ZEROS`] 以上のゼロのバイトがあることを条件にします。
+fn sniff_utf16(bytes: &[u8]) -> Option<TextEncoding> {
+    let head = &bytes[..bytes.len().min(UTF16_SNIFF_LEN)];
+    if head.len() / 2 < UTF16_MIN_UNITS {
+        return None;
+    }
+    let zeros = |parity: usize| {
+        head.iter()
+            .skip(parity)
+            .step_by(2)
+            .filter(|&&b| b == 0)
+            .count()
+    };
+    match (zeros(0), zeros(1)) {
+        (0, n) if n >= UTF16_MIN_ZEROS => Some(TextEncoding::Utf16Le),
+        (n, 0) if n >= UTF16_MIN_ZEROS => Some(TextEncoding::Utf16Be),
+        _ => None,
+    }
+}
+
+/// ISO-2022-JP（ASCIIのみで、JIS X 0208への切り替えを含む）かどうか
+fn is_iso_2022_jp(bytes: &[u8]) -> bool {
+    bytes.is_ascii()
+        && bytes
+            .windows(3)
+            .any(|w| w[0] == ESC && w[1] == b'$' && matches!(w[2], b'@' | b'B'))
+}
+
+/// Shift_JISとして読んだときにCP932の拡張文字を含むかどうか
+///
+/// NEC特殊文字（第1バイト0x87）、NEC選定IBM拡張文字（0xED、0xEE）、
+/// IBM拡張文字（0xFA〜0xFC）を調べます。
+fn has_cp932_extension(bytes: &[u8]) -> bool {
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            0x87 | 0xED | 0xEE | 0xFA..=0xFC => return true,
+            0x81..=0x9F | 0xE0..=0xFC => i += 2,
+            _ => i += 1,
+        }
+    }
+    false
+}
+
+/// 入力を1行ずつUTF-8文字列に変換するイテレータ
+///
+/// [`decode_lines`] で作成します。
+pub struct DecodedLines<R> {
+    reader: R,
+    buf: Vec<u8>,
+    /// 判定済みのエンコーディング
+    encoding: Option<TextEncoding>,
+    /// 置換文字に置き換えたバイト列があったか
+    lossy: bool,
+    /// 最初の行か（BOMの除去とUTF-16の判定に使う）
+    first: bool,
+    /// UTF-16の入力をまとめてデコードした残りの行
+    buffered: Option<std::vec::IntoIter<String>>,
+}
+
+/// バイト列の入力を1行ずつUTF-8文字列に変換
+///
+/// 入力全体を読み込まずに変換します。行の区切りは [`str::lines`] と同じく
+/// `\n` と `\r\n` で、返す行に改行は含みません。
+///
+/// エンコーディングは最初の非ASCII文字（またはエスケープ）を含む行を
+/// [`decode`] で判定し、以降の行にも同じエンコーディングを使います。
+/// UTF-16の入力は行単位で区切れないため、全体を読み込んでから変換します。
+///
+/// # Examples
+///
+/// ```
+/// use aozora_core::encoding::decode_lines;
+///
+/// let input = "題名\r\n本文\n".as_bytes();
+/// let lines: Vec<String> = decode_lines(input).collect::<Result<_, _>>().unwrap();
+/// assert_eq!(lines, vec!["題名", "本文"]);
+/// ```
+pub fn decode_lines<R: BufRead>(reader: R) -> DecodedLines<R> {
+    DecodedLines {
+        reader,
+        buf: Vec::new(),
+        encoding: None,
+        lossy: false,
+        first: true,
+        buffered: None,
+    }
+}
+
+impl<R: BufRead> DecodedLines<R> {
+    /// 判定したエンコーディング（非ASCII文字の行をまだ読んでいなければ `None`）
+    pub fn encoding(&self) -> Option<TextEncoding> {
+        self.encoding
+    }
+
+    /// これまでに読んだ行で置換文字に置き換えたバイト列があったかどうか
+    pub fn lossy(&self) -> bool {
+        self.lossy
+    }
+
+    /// 入力がUTF-16なら全体を読み込んで行に分ける
+    fn read_utf16(&mut self) -> io::Result<()> {
+        let head = self.reader.fill_buf()?;
+        let is_utf16 = head.starts_with(UTF16LE_BOM)
+            || head.starts_with(UTF16BE_BOM)
+            || sniff_utf16(head).is_some();
+        if !is_utf16 {
+            return Ok(());
+        }
+
+        let mut bytes = Vec::new();
+        self.reader.read_to_end(&mut bytes)?;
+        let decoded = decode(&bytes);
+        self.encoding = Some(decoded.encoding);
+        self.lossy = decoded.lossy;
+        let lines: Vec<String> = decoded.text.lines().map(str::to_owned).collect();
+        self.buffered = Some(lines.into_iter());
+        Ok(())
+    }
+
+    /// 1行分のバイト列をデコード
+    fn decode(&mut self, mut bytes: &[u8]) -> String {
+        if std::mem::take(&mut self.first) {
+            bytes = bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes);
+        }
+
+        match self.encoding {
+            Some(encoding) => {
+                let (cow, lossy) = encoding.encoding().decode_without_bom_handling(bytes);
+                self.lossy |= lossy;
+                cow.into_owned()
+            }
+            None if !bytes.is_ascii() || bytes.contains(&ESC) => {
+                let decoded = decode(bytes);
+                self.encoding = Some(decoded.encoding);
+                self.lossy |= decoded.lossy;
+                decoded.text
+            }
+            None => String::from_utf8_lossy(bytes).into_owned(),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for DecodedLines<R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.first && self.buffered.is_none() {
+            if let Err(e) = self.read_utf16() {
+                return Some(Err(e));
+            }
+        }
+        if let Some(lines) = &mut self.buffered {
+            return lines.next().map(Ok);
+        }
+
+        let mut buf = std::mem::take(&mut self.buf);
+        buf.clear();
+        match self.reader.read_until(b'\n', &mut buf) {
+            Ok(0) => return None,
+            Ok(_) => {}
+            Err(e) => return Some(Err(e)),
+        }
+
+        let line = buf.strip_suffix(b"\n").unwrap_or(&buf);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let decoded = self.decode(line);
+        self.buf = buf;
+        Some(Ok(decoded))
+    }
 }
 
 #[cfg(test)]
@@ -58,4 +507,176 @@ mod tests {
         let bytes = vec![0x82, 0xB1, 0x82, 0xF1, 0x82, 0xC9, 0x82, 0xBF, 0x82, 0xCD];
         assert_eq!(decode_to_utf8(&bytes), "こんにちは");
     }
+
+    #[test]
+    fn test_decode_lines_shift_jis() {
+        let (bytes, _, _) = SHIFT_JIS.encode("題名\r\n\r\n本文です\r\n");
+        let lines: Vec<String> = decode_lines(&bytes[..]).collect::<io::Result<_>>().unwrap();
+        assert_eq!(lines, vec!["題名", "", "本文です"]);
+    }
+
+    #[test]
+    fn test_decode_lines_matches_str_lines() {
+        let mut bytes = UTF8_BOM.to_vec();
+        bytes.extend_from_slice("一行目\n\n三行目\r\n最終行".as_bytes());
+        let lines: Vec<String> = decode_lines(&bytes[..]).collect::<io::Result<_>>().unwrap();
+        let text = decode_to_utf8(&bytes);
+        assert_eq!(lines, text.lines().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_decode_reports_encoding() {
+        let decoded = decode("吾輩は猫である".as_bytes());
+        assert_eq!(decoded.encoding, TextEncoding::Utf8);
+        assert_eq!(decoded.confidence, 1.0);
+        assert!(!decoded.bom && !decoded.lossy);
+
+        let (bytes, _, _) = SHIFT_JIS.encode("吾輩は猫である。名前はまだ無い。");
+        let decoded = decode(&bytes);
+        assert_eq!(decoded.encoding, TextEncoding::ShiftJis);
+        assert_eq!(decoded.text, "吾輩は猫である。名前はまだ無い。");
+        assert!(decoded.confidence > 0.9);
+    }
+
+    #[test]
+    fn test_euc_jp() {
+        let (bytes, _, _) = EUC_JP.encode("吾輩は猫である。名前はまだ無い。");
+        let decoded = decode(&bytes);
+        assert_eq!(decoded.encoding, TextEncoding::EucJp);
+        assert_eq!(decoded.text, "吾輩は猫である。名前はまだ無い。");
+        assert!(!decoded.lossy);
+    }
+
+    #[test]
+    fn test_iso_2022_jp() {
+        let (bytes, _, _) = ISO_2022_JP.encode("題名\r\n吾輩は猫である\r\n");
+        let decoded = decode(&bytes);
+        assert_eq!(decoded.encoding, TextEncoding::Iso2022Jp);
+        assert_eq!(decoded.text, "題名\r\n吾輩は猫である\r\n");
+        assert_eq!(decoded.confidence, 1.0);
+    }
+
+    #[test]
+    fn test_utf16() {
+        let text = "題名\r\n本文";
+        let le: Vec<u8> = text.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let be: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+
+        let decoded = decode(&le);
+        assert_eq!(
+            (decoded.encoding, decoded.bom),
+            (TextEncoding::Utf16Le, false)
+        );
+        assert_eq!(decoded.text, text);
+        let decoded = decode(&be);
+        assert_eq!(
+            (decoded.encoding, decoded.bom),
+            (TextEncoding::Utf16Be, false)
+        );
+        assert_eq!(decoded.text, text);
+
+        let mut with_bom = UTF16LE_BOM.to_vec();
+        with_bom.extend_from_slice(&le);
+        let decoded = decode(&with_bom);
+        assert_eq!(
+            (decoded.encoding, decoded.bom),
+            (TextEncoding::Utf16Le, true)
+        );
+        assert_eq!(decoded.text, text);
+        let mut with_bom = UTF16BE_BOM.to_vec();
+        with_bom.extend_from_slice(&be);
+        assert_eq!(decode(&with_bom).encoding, TextEncoding::Utf16Be);
+    }
+
+    #[test]
+    fn test_utf16_needs_consistent_zeros() {
+        // 1つだけのゼロのバイトではUTF-16とみなさない
+        let mut bytes = SHIFT_JIS.encode("題名\r\n本文").0.into_owned();
+        bytes.insert(4, 0);
+        assert_eq!(decode(&bytes).encoding, TextEncoding::ShiftJis);
+        // ゼロのバイトが偶数番目と奇数番目の両方にある
+        assert_eq!(sniff_utf16(b"a\0\0b\r\0\n\0"), None);
+        // 符号単位が少なすぎる
+        assert_eq!(sniff_utf16(b"\r\0\n\0"), None);
+        assert_eq!(sniff_utf16(b"\r\0\n\0a\0b\0"), Some(TextEncoding::Utf16Le));
+    }
+
+    #[test]
+    fn test_cp932_extension() {
+        // 「①」はNEC特殊文字（0x8740）、「髙」はIBM拡張文字（0xFB5C）
+        let (bytes, _, _) = SHIFT_JIS.encode("丸数字①と髙島屋");
+        let decoded = decode(&bytes);
+        assert_eq!(decoded.encoding, TextEncoding::Cp932);
+        assert_eq!(decoded.text, "丸数字①と髙島屋");
+        assert!(!decoded.lossy);
+    }
+
+    #[test]
+    fn test_lossy() {
+        // Shift_JISでもEUC-JPでも不正なバイト列
+        let mut bytes = SHIFT_JIS.encode("吾輩は猫である").0.into_owned();
+        bytes.push(0x82);
+        let decoded = decode(&bytes);
+        assert!(decoded.lossy);
+        assert!(decoded.text.ends_with('\u{FFFD}'));
+        assert!(decoded.confidence < 1.0);
+    }
+
+    #[test]
+    fn test_decode_lines_reports_encoding() {
+        let (bytes, _, _) = EUC_JP.encode("Title\n吾輩は猫である\n名前はまだ無い\n");
+        let mut lines = decode_lines(&bytes[..]);
+        assert_eq!(lines.next().unwrap().unwrap(), "Title");
+        assert_eq!(lines.encoding(), None);
+        let rest: Vec<String> = lines.by_ref().collect::<io::Result<_>>().unwrap();
+        assert_eq!(rest, vec!["吾輩は猫である", "名前はまだ無い"]);
+        assert_eq!(lines.encoding(), Some(TextEncoding::EucJp));
+        assert!(!lines.lossy());
+    }
+
+    #[test]
+    fn test_decode_lines_utf16() {
+        let mut bytes = UTF16LE_BOM.to_vec();
+        bytes.extend("題名\r\n\r\n本文".encode_utf16().flat_map(u16::to_le_bytes));
+        let mut lines = decode_lines(&bytes[..]);
+        let collected: Vec<String> = lines.by_ref().collect::<io::Result<_>>().unwrap();
+        assert_eq!(collected, vec!["題名", "", "本文"]);
+        assert_eq!(lines.encoding(), Some(TextEncoding::Utf16Le));
+    }
+
+    #[test]
+    fn test_encode_roundtrip() {
+        let text = "吾輩は猫である。①\r\n";
+        for encoding in [
+            TextEncoding::Utf8,
+            TextEncoding::Utf16Le,
+            TextEncoding::Utf16Be,
+            TextEncoding::Cp932,
+            TextEncoding::EucJp,
+            TextEncoding::Iso2022Jp,
+        ] {
+            let mut bytes = encoding.bom().to_vec();
+            encode(text, encoding, &mut bytes, |c| panic!("{c}")).unwrap();
+            let decoded = decode(&bytes);
+            assert_eq!(decoded.text, text, "{}", encoding.name());
+            assert_eq!(decoded.encoding, encoding);
+        }
+    }
+
+    #[test]
+    fn test_encode_unmappable() {
+        let mut output = Vec::new();
+        encode("猫😀犬", TextEncoding::Iso2022Jp, &mut output, |c| {
+            Ok(format!("※［＃U+{:04X}］", u32::from(c)))
+        })
+        .unwrap();
+        assert_eq!(decode(&output).text, "猫※［＃U+1F600］犬");
+        assert!(output.ends_with(b"\x1b(B"));
+
+        let error = encode("😀", TextEncoding::EucJp, &mut Vec::new(), |c| {
+            Err(io::Error::new(io::ErrorKind::InvalidData, c.to_string()))
+        })
+        .unwrap_err();
+        assert_eq!(error.to_string(), "😀");
+    }
 }
@@ -79,6 +79,163 @@ pub fn parse_gaiji(description: &str) -> GaijiResult {
     GaijiResult::Unconvertible
 }
 
+/// 外字注記の説明を正規形に整える
+///
+/// `「文字の説明」、第3水準1-85-7` の形にそろえます。
+///
+/// - 水準やUnicodeの指定がある場合は文字の説明を「」で囲む
+/// - 区切りを「、」に、全角の数字・ハイフンを半角に、漢数字の水準を算用数字にする
+/// - 面区点番号の先頭の0を除き、水準がなければ第2面のみ第4水準を補う（書かれている水準は変えない）
+/// - Unicode指定は `U+` と大文字の16進数にする
+///
+/// 面区点番号もUnicode指定も含まない説明はそのまま返します。
+///
+/// # Examples
+///
+/// ```
+/// use aozora_core::gaiji::canonicalize_description;
+///
+/// assert_eq!(
+///     canonicalize_description("てへん＋劣、第三水準１－８４－７７"),
+///     "「てへん＋劣」、第3水準1-84-77"
+/// );
+/// assert_eq!(
+///     canonicalize_description("「木＋吉」,2-15-12"),
+///     "「木＋吉」、第4水準2-15-12"
+/// );
+/// assert_eq!(canonicalize_description("縦線、1-1-35"), "縦線、1-1-35");
+/// ```
+pub fn canonicalize_description(description: &str) -> String {
+    let description = description.trim();
+    let (name, bracketed, rest) = split_description_name(description);
+
+    let parts: Vec<(String, DescriptionPart)> = rest
+        .split(['、', ',', '，'])
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(canonicalize_description_part)
+        .collect();
+
+    if !parts
+        .iter()
+        .any(|(_, kind)| !matches!(kind, DescriptionPart::Other))
+    {
+        return description.to_string();
+    }
+
+    let quote = bracketed
+        || parts.iter().any(|(_, kind)| {
+            matches!(
+                kind,
+                DescriptionPart::Jis { has_level: true } | DescriptionPart::Unicode
+            )
+        });
+    let mut result: Vec<String> = Vec::new();
+    if let Some(name) = name {
+        result.push(if quote {
+            format!("「{name}」")
+        } else {
+            name.to_string()
+        });
+    }
+    result.extend(parts.into_iter().map(|(part, _)| part));
+    result.join("、")
+}
+
+/// 外字説明の区切られた各部分の種類
+enum DescriptionPart {
+    /// 面区点番号（水準付きかどうか）
+    Jis { has_level: bool },
+    /// Unicode指定
+    Unicode,
+    /// その他（ページ数-行数など）
+    Other,
+}
+
+/// 外字説明を（文字の説明, 「」で囲まれていたか, 残り）に分ける
+fn split_description_name(description: &str) -> (Option<&str>, bool, &str) {
+    if let Some(inner) = description.strip_prefix('「') {
+        // 入れ子の「」を考慮して対応する 」 を探す
+        let mut depth = 1;
+        for (i, c) in inner.char_indices() {
+            match c {
+                '「' => depth += 1,
+                '」' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return (Some(&inner[..i]), true, &inner[i + '」'.len_utf8()..]);
+                    }
+                }
+                _ => {}
+            }
+        }
+        return (None, false, description);
+    }
+
+    let (first, rest) = match description.find(['、', ',', '，']) {
+        Some(pos) => {
+            let sep = description[pos..].chars().next().map_or(0, char::len_utf8);
+            (&description[..pos], &description[pos + sep..])
+        }
+        None => (description, ""),
+    };
+    match canonicalize_description_part(first.trim()).1 {
+        DescriptionPart::Other => (Some(first.trim()), false, rest),
+        _ => (None, false, description),
+    }
+}
+
+/// 外字説明の1部分を正規化
+fn canonicalize_description_part(part: &str) -> (String, DescriptionPart) {
+    let normalized: String = part
+        .chars()
+        .map(|c| match c {
+            '０'..='９' => char::from_u32(c as u32 - '０' as u32 + '0' as u32).unwrap_or(c),
+            '－' | '‐' | '−' => '-',
+            'Ｕ' | 'ｕ' | 'u' => 'U',
+            '＋' => '+',
+            '三' => '3',
+            '四' => '4',
+            _ => c,
+        })
+        .collect();
+
+    if let Some(hex) = normalized.strip_prefix("U+") {
+        if !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return (
+                format!("U+{}", hex.to_ascii_uppercase()),
+                DescriptionPart::Unicode,
+            );
+        }
+    }
+
+    let (level, code) = match normalized
+        .strip_prefix('第')
+        .and_then(|s| s.split_once("水準"))
+    {
+        Some((level, code)) if level.len() == 1 => (Some(level), code),
+        _ => (None, normalized.as_str()),
+    };
+    let numbers: Vec<u32> = code.split('-').filter_map(|n| n.parse().ok()).collect();
+    if let [plane @ (1 | 2), row, cell] = numbers[..] {
+        if code.split('-').count() == 3 {
+            let level = level.or((plane == 2).then_some("4"));
+            let part = match level {
+                Some(level) => format!("第{level}水準{plane}-{row}-{cell}"),
+                None => format!("{plane}-{row}-{cell}"),
+            };
+            return (
+                part,
+                DescriptionPart::Jis {
+                    has_level: level.is_some(),
+                },
+            );
+        }
+    }
+
+    (part.to_string(), DescriptionPart::Other)
+}
+
 /// "U+XXXX" パターンからUnicode文字を抽出
 fn extract_unicode(description: &str) -> Option<char> {
     // "U+XXXX" または "u+XXXX" を探す
@@ -224,6 +381,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_canonicalize_description() {
+        assert_eq!(
+            canonicalize_description("「てへん＋劣」、第3水準1-84-77"),
+            "「てへん＋劣」、第3水準1-84-77"
+        );
+        assert_eq!(
+            canonicalize_description("「てへん＋劣」、第３水準１－８４－７７"),
+            "「てへん＋劣」、第3水準1-84-77"
+        );
+        assert_eq!(
+            canonicalize_description("「さんずい＋昔」、第4水準2-78-03"),
+            "「さんずい＋昔」、第4水準2-78-3"
+        );
+        assert_eq!(
+            canonicalize_description("丸印、u+25cb、123-4"),
+            "「丸印」、U+25CB、123-4"
+        );
+        assert_eq!(
+            canonicalize_description("半濁点付き片仮名カ、1-05-87"),
+            "半濁点付き片仮名カ、1-5-87"
+        );
+        assert_eq!(canonicalize_description("1-2-22"), "1-2-22");
+        // 書かれている水準は面番号から決め直さない
+        assert_eq!(
+            canonicalize_description("「あ」、第１水準1-4-2"),
+            "「あ」、第1水準1-4-2"
+        );
+        assert_eq!(
+            canonicalize_description("「亜」、第2水準1-16-1"),
+            "「亜」、第2水準1-16-1"
+        );
+        assert_eq!(canonicalize_description("不明な外字"), "不明な外字");
+    }
+
     #[test]
     fn test_parse_gaiji_jis() {
         match parse_gaiji("1-05-87") {
@@ -0,0 +1,464 @@
+//! 編集に追従する文書
+//!
+//! エディタのプレビューや言語サーバー向けに、テキストの編集を受け取って
+//! 影響を受けた行だけを解析し直します。編集されていない行は解析済みの
+//! ノードをそのまま使い、位置だけをずらします。
+//!
+//! 行をまたぐ構造は「ここから」〜「ここで終わり」のブロックだけなので、
+//! 編集後に対応が変わったブロックの範囲も再描画の対象として返します。
+//!
+//! 診断も行内で完結するものは行ごとに保持し、ブロックの対応だけを
+//! 解析済みのノードから検査し直します。
+
+use std::ops::Range;
+
+use crate::block_tree::{build_block_tree, BlockClose, TreeNode};
+use crate::diagnostic::{check_line_local, Checker, Diagnostic};
+use crate::document::{
+    after_text_line_range, bibliographical_line_range, body_line_range, extract_header_info,
+    legend_line_range, AozoraDocument, DocumentLine,
+};
+use crate::node::{BlockParams, BlockType};
+use crate::span::Span;
+
+/// 編集に追従する文書
+///
+/// # Examples
+///
+/// ```
+/// use aozora_core::incremental::IncrementalDocument;
+/// use aozora_core::span::Span;
+///
+/// let mut document = IncrementalDocument::new("題名\n\n本文\n［＃ここから２字下げ］\n一\n二\n");
+/// // 「本文」を書き換えても、解析し直すのはその行だけ
+/// let changed = document.edit(Span::new(8, 14), "吾輩《わがはい》");
+/// assert_eq!(changed, 2..3);
+/// assert_eq!(document.text(), "題名\n\n吾輩《わがはい》\n［＃ここから２字下げ］\n一\n二\n");
+///
+/// // 字下げの終わりを追加すると、対応が変わったブロック全体が対象になる
+/// let end = document.text().len();
+/// let changed = document.edit(Span::new(end, end), "［＃ここで字下げ終わり］\n");
+/// assert_eq!(changed, 3..7);
+/// ```
+#[derive(Debug, Clone)]
+pub struct IncrementalDocument {
+    /// 文書全体のテキスト
+    text: String,
+    /// 各行の開始バイト位置
+    line_starts: Vec<usize>,
+    /// 解析済みの行（`str::lines` と同じ行分け）
+    lines: Vec<DocumentLine>,
+    /// 各行の行内で完結する診断
+    line_diagnostics: Vec<Vec<Diagnostic>>,
+    /// 本文の行の範囲
+    body: Range<usize>,
+    /// 本文の「ここから」のブロックの対応
+    blocks: Vec<BlockPair>,
+}
+
+/// 「ここから」のブロックの対応（開始行から閉じられた行まで）
+#[derive(Debug, Clone)]
+struct BlockPair {
+    block_type: BlockType,
+    params: BlockParams,
+    close: BlockClose,
+    start_line: usize,
+    end_line: usize,
+}
+
+impl IncrementalDocument {
+    /// テキスト全体を解析して作成
+    pub fn new(text: &str) -> Self {
+        let line_starts = line_starts(text);
+        let (lines, line_diagnostics) = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| check_line_local(i, line_starts[i], line))
+            .unzip();
+        let mut document = Self {
+            text: text.to_string(),
+            line_starts,
+            lines,
+            line_diagnostics,
+            body: 0..0,
+            blocks: Vec::new(),
+        };
+        document.body = body_line_range(&document.line_texts());
+        document.blocks = document.block_pairs();
+        document
+    }
+
+    /// 文書全体のテキスト
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// 解析済みの行
+    pub fn lines(&self) -> &[DocumentLine] {
+        &self.lines
+    }
+
+    /// 本文の行の範囲
+    pub fn body_range(&self) -> Range<usize> {
+        self.body.clone()
+    }
+
+    /// `span`（バイト位置）の範囲を `new_text` で置き換える
+    ///
+    /// 編集後の文書で描画し直す必要のある行の範囲を返します。範囲には
+    /// 書き換えられた行と、ブロックの対応が変わった行が含まれます。
+    /// 本文の範囲が変わった場合は文書全体を返します。
+    ///
+    /// `span` は文字境界上にある必要があります。
+    pub fn edit(&mut self, span: Span, new_text: &str) -> Range<usize> {
+        let first = self.line_of(span.start);
+        let last = self.line_of(span.end);
+        let old_len = self.lines.len();
+        // 編集の影響を受けない後ろの行
+        let suffix = (last + 1).min(old_len);
+        let suffix_start = self.line_starts.get(suffix).copied();
+        let delta = new_text.len() as isize - span.len() as isize;
+
+        self.text.replace_range(span.start..span.end, new_text);
+        self.line_starts = line_starts(&self.text);
+
+        // 書き換えられた行を解析し直す
+        let middle_start = self.line_starts[first];
+        let middle_end = match suffix_start {
+            Some(start) if suffix < old_len => shift(start, delta),
+            _ => self.text.len(),
+        };
+        let (middle, middle_diagnostics): (Vec<DocumentLine>, Vec<Vec<Diagnostic>>) = self.text
+            [middle_start..middle_end]
+            .lines()
+            .enumerate()
+            .map(|(i, line)| check_line_local(first + i, self.line_starts[first + i], line))
+            .unzip();
+        let line_delta = middle.len() as isize - (suffix - first) as isize;
+        let edited = first..first + middle.len();
+
+        // 後ろの行は解析済みのノードの位置だけをずらす
+        let mut tail = self.lines.split_off(suffix);
+        self.lines.truncate(first);
+        for line in &mut tail {
+            line.line = shift(line.line, line_delta);
+            for node in &mut line.nodes {
+                node.span = Span::new(shift(node.span.start, delta), shift(node.span.end, delta));
+            }
+        }
+        self.lines.extend(middle);
+        self.lines.extend(tail);
+
+        let mut tail = self.line_diagnostics.split_off(suffix);
+        self.line_diagnostics.truncate(first);
+        for diagnostic in tail.iter_mut().flatten() {
+            diagnostic.span = Span::new(
+                shift(diagnostic.span.start, delta),
+                shift(diagnostic.span.end, delta),
+            );
+            diagnostic.start.line = shift(diagnostic.start.line, line_delta);
+            diagnostic.end.line = shift(diagnostic.end.line, line_delta);
+        }
+        self.line_diagnostics.extend(middle_diagnostics);
+        self.line_diagnostics.extend(tail);
+        let new_len = self.lines.len();
+
+        // 編集前の行番号を編集後の行番号に変換（書き換えられた行は None）
+        let map_old = |line: usize| {
+            if line < first {
+                Some(line)
+            } else if line > last {
+                Some(shift(line, line_delta))
+            } else {
+                None
+            }
+        };
+
+        // 本文の範囲が変わった場合は全体を描画し直す
+        let map_bound = |line: usize| {
+            if line == old_len {
+                new_len
+            } else {
+                map_old(line).unwrap_or(first)
+            }
+        };
+        let body = body_line_range(&self.line_texts());
+        let old_body = std::mem::replace(&mut self.body, body);
+        let blocks = self.block_pairs();
+        let old_blocks = std::mem::replace(&mut self.blocks, blocks);
+        if map_bound(old_body.start) != self.body.start || map_bound(old_body.end) != self.body.end
+        {
+            return 0..new_len;
+        }
+
+        // 対応が変わったブロックの範囲を加える
+        let map_new = |line: usize| (!edited.contains(&line)).then_some(line);
+        let old_keys: Vec<BlockKey> = old_blocks.iter().map(|b| b.key(map_old)).collect();
+        let new_keys: Vec<BlockKey> = self.blocks.iter().map(|b| b.key(map_new)).collect();
+        let changed_keys = old_keys
+            .iter()
+            .filter(|key| !new_keys.contains(key))
+            .chain(new_keys.iter().filter(|key| !old_keys.contains(key)));
+
+        let mut changed = edited.clone();
+        for key in changed_keys {
+            let start = key.start_line.unwrap_or(edited.start);
+            let end = key.end_line.map_or(edited.end, |end| end + 1);
+            changed = changed.start.min(start)..changed.end.max(end);
+        }
+        changed.start.min(new_len)..changed.end.min(new_len)
+    }
+
+    /// 現在の内容を [`AozoraDocument`] として返す
+    ///
+    /// [`AozoraDocument::parse`] と同じ結果になります。
+    pub fn document(&self) -> AozoraDocument {
+        let texts = self.line_texts();
+        let lines_in = |range: Range<usize>| self.lines[range].to_vec();
+        AozoraDocument {
+            header: extract_header_info(&texts),
+            legend: texts[legend_line_range(&texts)]
+                .iter()
+                .map(|line| line.to_string())
+                .collect(),
+            body: lines_in(self.body.clone()),
+            after_text: lines_in(after_text_line_range(&texts)),
+            bibliography: lines_in(bibliographical_line_range(&texts)),
+        }
+    }
+
+    /// 本文と本文終わり後のテキストの診断（位置順）
+    ///
+    /// [`check_document`](crate::diagnostic::check_document) と同じ診断を返します。
+    /// 行内の診断は保持しているものを使い、ブロックの対応だけを検査し直します。
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        let texts = self.line_texts();
+        let mut diagnostics = Vec::new();
+        for range in [self.body.clone(), after_text_line_range(&texts)] {
+            let mut checker = Checker::new();
+            for i in range {
+                diagnostics.extend_from_slice(&self.line_diagnostics[i]);
+                checker.check_blocks(&self.lines[i], self.line_starts[i]);
+            }
+            diagnostics.extend(checker.finish());
+        }
+        diagnostics.sort_by_key(|diagnostic| (diagnostic.span.start, diagnostic.span.end));
+        diagnostics
+    }
+
+    /// 本文のブロックの木構造
+    pub fn body_tree(&self) -> Vec<TreeNode> {
+        build_block_tree(&self.lines[self.body.clone()])
+    }
+
+    /// 各行のテキスト
+    fn line_texts(&self) -> Vec<&str> {
+        self.lines.iter().map(|line| line.text.as_str()).collect()
+    }
+
+    /// バイト位置を含む行の行番号
+    fn line_of(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= offset) - 1
+    }
+
+    /// 本文の「ここから」のブロックの対応を集める
+    fn block_pairs(&self) -> Vec<BlockPair> {
+        let mut pairs = Vec::new();
+        self.collect_block_pairs(&self.body_tree(), &mut pairs);
+        pairs
+    }
+
+    fn collect_block_pairs(&self, nodes: &[TreeNode], pairs: &mut Vec<BlockPair>) {
+        for node in nodes {
+            if let TreeNode::Block(block) = node {
+                if block.params.is_block {
+                    let start_line = self.line_of(block.start_span.start);
+                    let end_line = match block.end_span {
+                        Some(span) => self.line_of(span.start),
+                        None => last_line(&block.children).unwrap_or(start_line),
+                    };
+                    pairs.push(BlockPair {
+                        block_type: block.block_type,
+                        params: block.params.clone(),
+                        close: block.close,
+                        start_line,
+                        end_line,
+                    });
+                }
+                self.collect_block_pairs(&block.children, pairs);
+            }
+        }
+    }
+}
+
+impl BlockPair {
+    /// 行番号を変換した比較用のキー
+    fn key(&self, map: impl Fn(usize) -> Option<usize>) -> BlockKey<'_> {
+        BlockKey {
+            block_type: self.block_type,
+            params: &self.params,
+            close: self.close,
+            start_line: map(self.start_line),
+            end_line: map(self.end_line),
+        }
+    }
+}
+
+/// 編集前後のブロックの対応を比べるためのキー
+///
+/// 書き換えられた行の行番号は `None` にします。
+#[derive(Debug, PartialEq)]
+struct BlockKey<'a> {
+    block_type: BlockType,
+    params: &'a BlockParams,
+    close: BlockClose,
+    start_line: Option<usize>,
+    end_line: Option<usize>,
+}
+
+/// 各行の開始バイト位置
+fn line_starts(text: &str) -> Vec<usize> {
+    let mut starts = vec![0];
+    starts.extend(text.match_indices('\n').map(|(i, _)| i + 1));
+    starts
+}
+
+/// 位置を差分だけずらす
+fn shift(value: usize, delta: isize) -> usize {
+    value.saturating_add_signed(delta)
+}
+
+/// 木構造の最後の行の行番号
+fn last_line(nodes: &[TreeNode]) -> Option<usize> {
+    nodes.iter().rev().find_map(|node| match node {
+        TreeNode::LineBreak { line } => Some(*line),
+        TreeNode::Block(block) => last_line(&block.children),
+        TreeNode::Node(_) => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostic::{check_document, DiagnosticCode};
+
+    const TEXT: &str = "題名\n著者\n\n\
+                        本文《ほんぶん》\n\
+                        ［＃ここから２字下げ］\n\
+                        一\n\
+                        二［＃「二」に傍点］\n\
+                        ［＃ここで字下げ終わり］\n\
+                        三\n\
+                        \n\
+                        底本：青空文庫\n";
+
+    /// 文字列の範囲を置き換える
+    fn edit(document: &mut IncrementalDocument, target: &str, new_text: &str) -> Range<usize> {
+        let start = document.text().find(target).unwrap();
+        document.edit(Span::new(start, start + target.len()), new_text)
+    }
+
+    fn assert_same(document: &IncrementalDocument) {
+        assert_eq!(document.document(), AozoraDocument::parse(document.text()));
+        let mut diagnostics = check_document(document.text()).diagnostics;
+        diagnostics.sort_by_key(|diagnostic| (diagnostic.span.start, diagnostic.span.end));
+        assert_eq!(document.diagnostics(), diagnostics);
+        assert_eq!(
+            document.body_tree(),
+            AozoraDocument::parse(document.text()).body_tree()
+        );
+    }
+
+    #[test]
+    fn test_edit_within_line() {
+        let mut document = IncrementalDocument::new(TEXT);
+        let before = document.lines()[8].clone();
+        assert_eq!(edit(&mut document, "一", "いち《イチ》"), 5..6);
+        assert_same(&document);
+        // 後ろの行はノードの位置だけがずれる
+        let after = &document.lines()[8];
+        assert_eq!(after.nodes[0].span.start, before.nodes[0].span.start + 15);
+    }
+
+    #[test]
+    fn test_edit_lines() {
+        let mut document = IncrementalDocument::new(TEXT);
+        // 行の追加
+        assert_eq!(edit(&mut document, "一", "一\n一の二"), 5..7);
+        assert_same(&document);
+        // 行の結合
+        assert_eq!(edit(&mut document, "一\n一の二", "一一の二"), 5..6);
+        assert_same(&document);
+        // 文書の末尾への追加
+        let end = document.text().len();
+        assert_eq!(document.edit(Span::new(end, end), "入力：某\n"), 11..12);
+        assert_same(&document);
+    }
+
+    #[test]
+    fn test_edit_block_pairing() {
+        let mut document = IncrementalDocument::new(TEXT);
+        // 終わりの注記を消すと、ブロックが文書の終わりまで続く
+        assert_eq!(edit(&mut document, "［＃ここで字下げ終わり］", ""), 4..10);
+        assert_same(&document);
+        // 元に戻す
+        assert_eq!(
+            edit(
+                &mut document,
+                "二［＃「二」に傍点］\n\n",
+                "二［＃「二」に傍点］\n［＃ここで字下げ終わり］\n"
+            ),
+            4..10
+        );
+        assert_same(&document);
+        // 字数だけを変えると、ブロック全体が対象になる
+        assert_eq!(edit(&mut document, "２字下げ", "３字下げ"), 4..8);
+        assert_same(&document);
+    }
+
+    #[test]
+    fn test_edit_diagnostics() {
+        let mut document = IncrementalDocument::new(TEXT);
+        assert!(document.diagnostics().is_empty());
+        // 行内の診断は編集した行だけ検査し直す
+        edit(&mut document, "「二」", "「犬」");
+        assert_same(&document);
+        let codes: Vec<_> = document.diagnostics().iter().map(|d| d.code).collect();
+        assert_eq!(codes, vec![DiagnosticCode::UnresolvedReference]);
+        // 前の行の追加で後ろの行の診断の位置がずれる
+        edit(&mut document, "一", "一\n［＃未知の注記］");
+        assert_same(&document);
+        assert_eq!(document.diagnostics()[1].start.line, 7);
+        // ブロックの対応は毎回検査する
+        edit(&mut document, "［＃ここで字下げ終わり］", "");
+        assert_same(&document);
+        let codes: Vec<_> = document.diagnostics().iter().map(|d| d.code).collect();
+        assert_eq!(
+            codes,
+            vec![
+                DiagnosticCode::UnclosedBlock,
+                DiagnosticCode::UnknownCommand,
+                DiagnosticCode::UnresolvedReference
+            ]
+        );
+    }
+
+    #[test]
+    fn test_edit_sections() {
+        let mut document = IncrementalDocument::new(TEXT);
+        assert_eq!(document.body_range(), 3..10);
+        // 本文の範囲が変わると文書全体が対象になる
+        assert_eq!(edit(&mut document, "三", "底本：三"), 0..11);
+        assert_eq!(document.body_range(), 3..8);
+        assert_same(&document);
+    }
+
+    #[test]
+    fn test_edit_empty_document() {
+        let mut document = IncrementalDocument::new("");
+        assert_eq!(document.edit(Span::new(0, 0), "題名\n\n本文"), 0..3);
+        assert_same(&document);
+        assert_eq!(document.edit(Span::new(0, 14), ""), 0..0);
+        assert_same(&document);
+    }
+}
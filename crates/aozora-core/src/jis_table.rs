@@ -1,6 +1,6 @@
 //! JISコード→Unicode変換テーブル
 //!
-//! JIS X 0213の文字コードからUnicode文字列への変換テーブルを提供します。
+//! JIS X 0213の文字コードからUnicode文字列への変換テーブル（と、その逆引き）を提供します。
 //! このモジュールは `gaiji` と `accent` モジュールの両方から使用されます。
 
 use once_cell::sync::Lazy;
@@ -11,6 +11,20 @@ use std::collections::HashMap;
 static JIS2UCS: Lazy<HashMap<&'static str, &'static str>> =
     Lazy::new(|| include!(concat!(env!("OUT_DIR"), "/jis2ucs_table.rs")));
 
+/// Unicode文字列→JISコードの逆引きテーブル
+///
+/// 同じ文字列に複数のJISコードがある場合は最も小さいコードを使います。
+static UCS2JIS: Lazy<HashMap<&'static str, &'static str>> = Lazy::new(|| {
+    let mut table: HashMap<&'static str, &'static str> = HashMap::with_capacity(JIS2UCS.len());
+    for (&jis, &ucs) in JIS2UCS.iter() {
+        table
+            .entry(ucs)
+            .and_modify(|code| *code = (*code).min(jis))
+            .or_insert(jis);
+    }
+    table
+});
+
 /// JISコードからUnicode文字列に変換
 ///
 /// # Arguments
@@ -32,6 +46,20 @@ pub fn jis_to_unicode(jis_code: &str) -> Option<String> {
     JIS2UCS.get(normalized.as_str()).map(|&s| s.to_string())
 }
 
+/// Unicode文字列からJISコードに変換（[`jis_to_unicode`] の逆）
+///
+/// # Examples
+///
+/// ```
+/// use aozora_core::jis_table::unicode_to_jis;
+///
+/// assert_eq!(unicode_to_jis("カ゚"), Some("1-05-87".to_string()));
+/// assert_eq!(unicode_to_jis("😀"), None);
+/// ```
+pub fn unicode_to_jis(unicode: &str) -> Option<String> {
+    UCS2JIS.get(unicode).map(|&s| s.to_string())
+}
+
 /// JISコードを正規化（区・点を2桁ゼロ埋め）
 ///
 /// # Examples
@@ -68,6 +96,14 @@ mod tests {
         assert_eq!(jis_to_unicode("1-05-87"), Some("カ゚".to_string()));
     }
 
+    #[test]
+    fn test_unicode_to_jis_round_trip() {
+        for code in ["1-05-87", "1-85-07", "2-14-75"] {
+            let unicode = jis_to_unicode(code).unwrap();
+            assert_eq!(unicode_to_jis(&unicode).as_deref(), Some(code));
+        }
+    }
+
     #[test]
     fn test_jis_to_unicode_not_found() {
         assert_eq!(jis_to_unicode("99-99-99"), None);
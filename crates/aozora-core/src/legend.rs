@@ -0,0 +1,247 @@
+//! 記号説明の解析
+//!
+//! ヘッダーの後の `-------` で囲まれた【テキスト中に現れる記号について】を、
+//! 記号と意味の組の一覧にします。
+//!
+//! ```text
+//! 【テキスト中に現れる記号について】
+//!
+//! 《》：ルビ
+//! （例）吾輩《わがはい》
+//!
+//! ［＃］：入力者注　主に外字の説明や、傍点の位置の指定
+//! 　　　（数字は、JIS X 0213の面区点番号、または底本のページと行数）
+//! （例）※［＃「言＋墟のつくり」、第4水準2-88-74］
+//! ```
+
+use crate::document::legend_line_range;
+
+/// 記号と意味の区切り
+const SEPARATOR: char = '：';
+
+/// 用例の行の始まり
+const EXAMPLE_PREFIX: &str = "（例）";
+
+/// 記号として扱う最大の文字数（これより長いものは説明文とみなす）
+const MAX_SYMBOL_CHARS: usize = 8;
+
+/// 本文で使われる記法と、記号説明での表記
+const NOTATIONS: &[(&str, &str)] = &[
+    ("《》", "《"),
+    ("｜", "｜"),
+    ("［＃］", "［＃"),
+    ("〔〕", "〔"),
+];
+
+/// 記号説明
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Legend {
+    /// 見出し（例: `【テキスト中に現れる記号について】`）
+    pub heading: Option<String>,
+    /// 記号ごとの説明
+    pub entries: Vec<LegendEntry>,
+    /// 記号の説明以外の文
+    pub notes: Vec<String>,
+}
+
+/// 記号の説明
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LegendEntry {
+    /// 記号（例: `《》`）
+    pub symbol: String,
+    /// 意味（続きの行は改行でつなぐ）
+    pub meaning: String,
+    /// 用例（`（例）` を除いた部分）
+    pub examples: Vec<String>,
+}
+
+impl Legend {
+    /// 記号が説明されているかどうか
+    ///
+    /// 例: `legend.declares("《》")`
+    pub fn declares(&self, symbol: &str) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| entry.symbol.contains(symbol))
+    }
+
+    /// 本文で使われているのに説明のない記法
+    ///
+    /// ルビ `《》`、ルビの始まり `｜`、注記 `［＃］`、アクセント分解 `〔〕` を調べます。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aozora_core::legend::extract_legend;
+    ///
+    /// let lines = vec!["題名", "", "-------", "《》：ルビ", "-------", "一番｜獰悪《どうあく》"];
+    /// let legend = extract_legend(&lines);
+    /// assert_eq!(legend.undeclared_notations(&lines[5..]), vec!["｜"]);
+    /// ```
+    pub fn undeclared_notations(&self, body_lines: &[&str]) -> Vec<&'static str> {
+        NOTATIONS
+            .iter()
+            .filter(|(symbol, marker)| {
+                !self.declares(symbol) && body_lines.iter().any(|line| line.contains(marker))
+            })
+            .map(|&(symbol, _)| symbol)
+            .collect()
+    }
+}
+
+/// 文書から記号説明を解析
+///
+/// `lines` は文書全体の行です。記号説明がなければ空の [`Legend`] を返します。
+///
+/// # Examples
+///
+/// ```
+/// use aozora_core::legend::extract_legend;
+///
+/// let lines = vec![
+///     "題名", "",
+///     "-------",
+///     "【テキスト中に現れる記号について】",
+///     "《》：ルビ",
+///     "（例）吾輩《わがはい》",
+///     "-------",
+///     "本文",
+/// ];
+/// let legend = extract_legend(&lines);
+/// assert_eq!(legend.entries[0].symbol, "《》");
+/// assert_eq!(legend.entries[0].meaning, "ルビ");
+/// assert_eq!(legend.entries[0].examples, vec!["吾輩《わがはい》"]);
+/// ```
+pub fn extract_legend(lines: &[&str]) -> Legend {
+    parse_legend(&lines[legend_line_range(lines)])
+}
+
+/// 記号説明の行（`-------` の内側）を解析
+pub fn parse_legend(lines: &[&str]) -> Legend {
+    let mut legend = Legend::default();
+    // 直前の行が記号の説明の続きかどうか
+    let mut in_entry = false;
+
+    for line in lines {
+        if line.trim().is_empty() {
+            in_entry = false;
+            continue;
+        }
+
+        if let Some(example) = line.strip_prefix(EXAMPLE_PREFIX) {
+            match legend.entries.last_mut() {
+                Some(entry) if in_entry => entry.examples.push(example.to_string()),
+                _ => legend.notes.push(line.to_string()),
+            }
+            continue;
+        }
+
+        if line.starts_with('【') && line.ends_with('】') && legend.heading.is_none() {
+            legend.heading = Some(line.to_string());
+            continue;
+        }
+
+        // 全角空白で始まる行は直前の説明の続き
+        if let Some(rest) = line.strip_prefix('　') {
+            if let Some(entry) = legend.entries.last_mut().filter(|_| in_entry) {
+                entry.meaning.push('\n');
+                entry.meaning.push_str(rest.trim_start_matches('　'));
+                continue;
+            }
+        }
+
+        match split_entry(line) {
+            Some((symbol, meaning)) => {
+                legend.entries.push(LegendEntry {
+                    symbol: symbol.to_string(),
+                    meaning: meaning.to_string(),
+                    examples: Vec::new(),
+                });
+                in_entry = true;
+            }
+            None => {
+                legend.notes.push(line.to_string());
+                in_entry = false;
+            }
+        }
+    }
+
+    legend
+}
+
+/// `記号：意味` の行を記号と意味に分ける
+fn split_entry(line: &str) -> Option<(&str, &str)> {
+    let (symbol, meaning) = line.split_once(SEPARATOR)?;
+    let is_symbol = !symbol.is_empty()
+        && symbol.chars().count() <= MAX_SYMBOL_CHARS
+        && !symbol.chars().any(char::is_whitespace);
+    is_symbol.then_some((symbol, meaning))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 青空文庫の一般的な記号説明
+    const LEGEND: &[&str] = &[
+        "【テキスト中に現れる記号について】",
+        "",
+        "《》：ルビ",
+        "（例）吾輩《わがはい》",
+        "",
+        "｜：ルビの付く文字列の始まりを特定する記号",
+        "（例）一番｜獰悪《どうあく》",
+        "",
+        "［＃］：入力者注　主に外字の説明や、傍点の位置の指定",
+        "　　　（数字は、JIS X 0213の面区点番号、または底本のページと行数）",
+        "（例）※［＃「言＋墟のつくり」、第4水準2-88-74］",
+        "",
+        "アクセント分解についての詳細は下記URLを参照してください",
+    ];
+
+    #[test]
+    fn test_parse_legend() {
+        let legend = parse_legend(LEGEND);
+        assert_eq!(
+            legend.heading.as_deref(),
+            Some("【テキスト中に現れる記号について】")
+        );
+        let symbols: Vec<_> = legend.entries.iter().map(|e| e.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["《》", "｜", "［＃］"]);
+
+        let note = &legend.entries[2];
+        assert_eq!(
+            note.meaning,
+            "入力者注　主に外字の説明や、傍点の位置の指定\n\
+             （数字は、JIS X 0213の面区点番号、または底本のページと行数）"
+        );
+        assert_eq!(
+            note.examples,
+            vec!["※［＃「言＋墟のつくり」、第4水準2-88-74］"]
+        );
+        assert_eq!(
+            legend.notes,
+            vec!["アクセント分解についての詳細は下記URLを参照してください"]
+        );
+    }
+
+    #[test]
+    fn test_extract_legend() {
+        let mut lines = vec!["題名", "著者", "", "-------"];
+        lines.extend_from_slice(LEGEND);
+        lines.extend_from_slice(&["-------", "本文"]);
+        assert_eq!(extract_legend(&lines), parse_legend(LEGEND));
+        assert_eq!(extract_legend(&["題名", "", "本文"]), Legend::default());
+    }
+
+    #[test]
+    fn test_undeclared_notations() {
+        let legend = parse_legend(LEGEND);
+        assert!(legend.declares("［＃］"));
+        assert!(!legend.declares("〔〕"));
+        let body = ["〔E'difice〕と｜獰悪《どうあく》", "［＃改ページ］"];
+        assert_eq!(legend.undeclared_notations(&body), vec!["〔〕"]);
+    }
+}
@@ -4,6 +4,7 @@
 //!
 //! - トークナイザ（字句解析）
 //! - パーサー（構文解析）
+//! - 青空文庫形式への書き出し
 //! - 文字種別判定
 //! - 外字（JIS外文字）変換
 //! - アクセント記号変換
@@ -39,41 +40,67 @@
 //! - `tokenizer` - 字句解析（トークナイザ）
 //! - `node` - ASTノード型の定義
 //! - `parser` - 構文解析（パーサー）
+//! - `serializer` - 青空文庫形式への書き出し（パーサーの逆変換）
+//! - `block_tree` - ブロックの入れ子構造
 //! - `char_type` - 文字種別判定
 //! - `gaiji` - 外字変換
 //! - `accent` - アクセント記号変換
+//! - `span` - ソース位置情報
+//! - `diagnostic` - 診断情報（記法の誤りの検出）
 //! - `document` - 文書構造解析
+//! - `legend` - 記号説明の解析
+//! - `biblio` - 底本情報の解析
+//! - `incremental` - 編集に追従する文書（差分解析）
 //! - `encoding` - エンコーディング検出・変換
 //! - `zip` - ZIPファイル処理
 
 pub mod accent;
+pub mod biblio;
+pub mod block_tree;
 pub mod char_type;
 pub mod delimiters;
+pub mod diagnostic;
 pub mod document;
 pub mod encoding;
 pub mod gaiji;
+pub mod incremental;
 pub mod jis_table;
+pub mod legend;
 pub mod node;
+pub mod outline;
 pub mod parser;
+pub mod serializer;
+pub mod span;
 pub mod token;
 pub mod tokenizer;
 pub mod zip;
 
 // Re-exports for convenience
+pub use biblio::{extract_biblio_info, BiblioInfo, BiblioSource, PublicationDate};
 pub use char_type::{CharType, CharTypeExt};
 pub use delimiters::{
     ACCENT_BEGIN, ACCENT_END, ACCENT_MARKS, COMMAND_BEGIN, COMMAND_END, GAIJI_MARK, IGETA,
     RUBY_BEGIN, RUBY_END, RUBY_PREFIX,
 };
+pub use diagnostic::{
+    check_document, check_text, CheckResult, Diagnostic, DiagnosticCode, Severity,
+};
 pub use document::{
-    extract_bibliographical_lines, extract_body_lines, extract_header_info, HeaderInfo,
+    extract_bibliographical_lines, extract_body_lines, extract_header_info, AozoraDocument,
+    Confidence, Contributor, ContributorRole, DocumentLine, HeaderInfo, HeaderLine, HeaderLineKind,
 };
+pub use incremental::IncrementalDocument;
+pub use legend::{extract_legend, Legend, LegendEntry};
 pub use node::{
-    BlockParams, BlockType, MidashiLevel, MidashiStyle, Node, RubyDirection, StyleType,
+    BlockParams, BlockType, BreakKind, MidashiLevel, MidashiStyle, Node, RubyDirection, StyleType,
+    Visitor, VisitorMut,
 };
-pub use parser::parse;
-pub use token::Token;
-pub use tokenizer::{tokenize, Tokenizer};
+pub use outline::{build_outline, OutlineEntry};
+pub use parser::{parse, parse_with_spans};
+pub use serializer::{serialize, serialize_lines, Serializer};
+pub use span::{LineIndex, Position, Span, Spanned};
+pub use token::{Token, TokenRef};
+pub use tokenizer::{tokenize, tokenize_ref, tokenize_with_spans, Tokenizer};
 
 #[cfg(test)]
 mod tests {
@@ -4,6 +4,7 @@ use super::{MidashiLevel, MidashiStyle};
 
 /// ブロックタイプ
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BlockType {
     /// 字下げ
     Jisage,
@@ -84,6 +85,7 @@ impl BlockType {
 
 /// ブロックパラメータ
 #[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BlockParams {
     /// 幅（字下げの字数など）
     pub width: Option<u32>,
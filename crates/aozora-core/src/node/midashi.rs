@@ -2,6 +2,7 @@
 
 /// 見出しレベル
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MidashiLevel {
     /// 大見出し → h3
     O,
@@ -24,10 +25,32 @@ impl MidashiLevel {
             None
         }
     }
+
+    /// 見出しIDの増分
+    ///
+    /// 見出しIDは文書の先頭から、大見出しで100、中見出しで10、小見出しで1ずつ増やします
+    /// （aozora2htmlの `midashi100` などのアンカーと同じ番号）。
+    pub fn id_step(&self) -> u32 {
+        match self {
+            MidashiLevel::O => 100,
+            MidashiLevel::Naka => 10,
+            MidashiLevel::Ko => 1,
+        }
+    }
+
+    /// 注記での名前（大見出し、中見出し、小見出し）
+    pub fn command_name(&self) -> &'static str {
+        match self {
+            MidashiLevel::O => "大見出し",
+            MidashiLevel::Naka => "中見出し",
+            MidashiLevel::Ko => "小見出し",
+        }
+    }
 }
 
 /// 見出しスタイル
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MidashiStyle {
     /// 通常（独立行）
     #[default]
@@ -49,6 +72,15 @@ impl MidashiStyle {
             MidashiStyle::Normal
         }
     }
+
+    /// 注記で見出し名の前に付く接頭辞（同行、窓）
+    pub fn command_prefix(&self) -> &'static str {
+        match self {
+            MidashiStyle::Normal => "",
+            MidashiStyle::Dogyo => "同行",
+            MidashiStyle::Mado => "窓",
+        }
+    }
 }
 
 #[cfg(test)]
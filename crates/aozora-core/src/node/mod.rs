@@ -5,15 +5,18 @@
 mod block;
 mod midashi;
 mod style;
+mod visit;
 
 pub use block::{BlockParams, BlockType};
 pub use midashi::{MidashiLevel, MidashiStyle};
 pub use style::StyleType;
+pub use visit::{walk_node, walk_node_mut, Visitor, VisitorMut};
 
 use crate::char_type::CharType;
 
 /// ASTノード
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Node {
     /// プレーンテキスト
     Text(String),
@@ -147,6 +150,22 @@ pub enum Node {
         params: BlockParams,
     },
 
+    /// 改ページ・改丁・改見開き・改段
+    Break {
+        /// 区切りの種類
+        kind: BreakKind,
+    },
+
+    /// 校正注記（「底本では」「ママ」）
+    Correction {
+        /// 注記の対象のノード列（本文に書かれている読み）
+        target: Vec<Node>,
+        /// 底本の読み（「底本では「…」」の場合）
+        original: Option<String>,
+        /// 校正注記の種類
+        kind: CorrectionKind,
+    },
+
     /// 注記（編集者注）
     Note(String),
 
@@ -179,6 +198,7 @@ pub enum Node {
 
 /// ルビの方向
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RubyDirection {
     /// 通常（縦書き右、横書き上）
     #[default]
@@ -189,6 +209,7 @@ pub enum RubyDirection {
 
 /// フォントサイズタイプ
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FontSizeType {
     /// 大きな文字
     Dai,
@@ -215,6 +236,101 @@ impl FontSizeType {
     }
 }
 
+/// 改ページなどの区切りの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BreakKind {
+    /// 改ページ（次のページから）
+    Page,
+    /// 改丁（次の奇数ページから）
+    Recto,
+    /// 改見開き（次の見開きから）
+    Spread,
+    /// 改段（次の段から）
+    Column,
+}
+
+impl BreakKind {
+    /// すべての区切りの種類
+    pub const ALL: &'static [BreakKind] = &[
+        BreakKind::Page,
+        BreakKind::Recto,
+        BreakKind::Spread,
+        BreakKind::Column,
+    ];
+
+    /// コマンド文字列から区切りの種類を判定
+    ///
+    /// 例: "改ページ" → Some(Page)
+    pub fn from_command(command: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.command_name() == command)
+    }
+
+    /// コマンド名（注記に書く文字列）
+    pub fn command_name(self) -> &'static str {
+        match self {
+            BreakKind::Page => "改ページ",
+            BreakKind::Recto => "改丁",
+            BreakKind::Spread => "改見開き",
+            BreakKind::Column => "改段",
+        }
+    }
+
+    /// ページが変わる区切りかどうか（改段以外）
+    pub fn is_page_break(self) -> bool {
+        self != BreakKind::Column
+    }
+}
+
+/// 校正注記の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CorrectionKind {
+    /// 底本の誤りを訂正した（「対象」は底本では「…」）
+    Corrected,
+    /// 底本のまま（「対象」はママ）
+    Sic,
+    /// ルビが底本のまま（ルビの「対象」はママ）
+    RubySic,
+}
+
+impl CorrectionKind {
+    /// 校正注記の文字列（`［＃` と `］` の内側）
+    ///
+    /// 例: `「灯」は底本では「燈」`、`ルビの「かじ」はママ`
+    pub fn command(self, target: &[Node], original: Option<&str>) -> String {
+        match (self, original) {
+            (CorrectionKind::RubySic, _) => {
+                let ruby: String = target
+                    .iter()
+                    .filter_map(|n| match n {
+                        Node::Ruby { ruby, .. } => {
+                            Some(ruby.iter().map(Node::to_text).collect::<String>())
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                format!("ルビの「{ruby}」はママ")
+            }
+            (_, Some(original)) => {
+                format!("「{}」は底本では「{original}」", plain_text(target))
+            }
+            (_, None) => format!("「{}」はママ", plain_text(target)),
+        }
+    }
+}
+
+/// 前方参照で照合されるテキスト
+fn plain_text(nodes: &[Node]) -> String {
+    nodes
+        .iter()
+        .map(crate::parser::reference_resolver::extract_plain_text)
+        .collect()
+}
+
 /// コマンド文字列から段階レベルを抽出
 fn extract_level(command: &str) -> Option<u32> {
     // "１段階" "２段階" などを検出
@@ -269,10 +385,12 @@ impl Node {
                 format!("{u}（{l}）")
             }
             Node::FontSize { children, .. } => children.iter().map(|n| n.to_text()).collect(),
+            Node::Correction { target, .. } => target.iter().map(|n| n.to_text()).collect(),
             Node::Kaeriten(s) => s.clone(),
             Node::Okurigana(s) => s.clone(),
             Node::BlockStart { .. }
             | Node::BlockEnd { .. }
+            | Node::Break { .. }
             | Node::Note(_)
             | Node::AnnotationEnd { .. } => String::new(),
             Node::UnresolvedReference {
@@ -360,4 +478,21 @@ mod tests {
         };
         assert_eq!(node.last_char_type(), Some(CharType::Kanji));
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_roundtrip() {
+        use crate::parser::parse;
+        use crate::tokenizer::tokenize;
+
+        let nodes = parse(&tokenize(
+            "［＃ここから２字下げ］｜東京《とうきょう》［＃「東京」に傍点］※［＃「丸印」、U+25CB］",
+        ));
+        let json = serde_json::to_string(&nodes).unwrap();
+        let restored: Vec<Node> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, nodes);
+
+        let json = serde_json::to_value(Node::text("本文")).unwrap();
+        assert_eq!(json, serde_json::json!({ "Text": "本文" }));
+    }
 }
@@ -2,6 +2,7 @@
 
 /// 装飾タイプ
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum StyleType {
     // 傍点系（右・上）
     SesameDot,
@@ -47,6 +48,42 @@ pub enum StyleType {
 }
 
 impl StyleType {
+    /// すべての装飾タイプ
+    pub const ALL: &'static [StyleType] = &[
+        StyleType::SesameDot,
+        StyleType::WhiteSesameDot,
+        StyleType::BlackCircle,
+        StyleType::WhiteCircle,
+        StyleType::BlackTriangle,
+        StyleType::WhiteTriangle,
+        StyleType::Bullseye,
+        StyleType::Fisheye,
+        StyleType::Saltire,
+        StyleType::SesameDotAfter,
+        StyleType::WhiteSesameDotAfter,
+        StyleType::BlackCircleAfter,
+        StyleType::WhiteCircleAfter,
+        StyleType::BlackTriangleAfter,
+        StyleType::WhiteTriangleAfter,
+        StyleType::BullseyeAfter,
+        StyleType::FisheyeAfter,
+        StyleType::SaltireAfter,
+        StyleType::UnderlineSolid,
+        StyleType::UnderlineDouble,
+        StyleType::UnderlineDotted,
+        StyleType::UnderlineDashed,
+        StyleType::UnderlineWave,
+        StyleType::OverlineSolid,
+        StyleType::OverlineDouble,
+        StyleType::OverlineDotted,
+        StyleType::OverlineDashed,
+        StyleType::OverlineWave,
+        StyleType::Bold,
+        StyleType::Italic,
+        StyleType::Subscript,
+        StyleType::Superscript,
+    ];
+
     /// コマンド名から装飾タイプを取得
     pub fn from_command(command: &str) -> Option<Self> {
         match command {
@@ -164,4 +201,14 @@ mod tests {
         assert_eq!(StyleType::from_command("太字"), Some(StyleType::Bold));
         assert_eq!(StyleType::from_command("未知"), None);
     }
+
+    #[test]
+    fn test_style_type_all_roundtrip() {
+        for &style_type in StyleType::ALL {
+            assert_eq!(
+                StyleType::from_command(style_type.command_name()),
+                Some(style_type)
+            );
+        }
+    }
 }
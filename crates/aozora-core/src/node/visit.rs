@@ -0,0 +1,546 @@
+//! ノード列の走査
+//!
+//! [`Visitor`] / [`VisitorMut`] は、すべてのノードの種類に対応するメソッドを持ち、
+//! 既定の実装は `children`、`ruby`、`upper`/`lower`、`content`、`target` を再帰的に走査します。
+//! 必要な種類のメソッドだけを上書きして使います。
+//!
+//! # Examples
+//!
+//! ```
+//! use aozora_core::node::{Node, Visitor};
+//! use aozora_core::parser::parse;
+//! use aozora_core::tokenizer::tokenize;
+//!
+//! /// 外字の説明を集める
+//! struct GaijiCollector(Vec<String>);
+//!
+//! impl Visitor for GaijiCollector {
+//!     fn visit_gaiji(&mut self, description: &str, _: Option<&str>, _: Option<&str>) {
+//!         self.0.push(description.to_string());
+//!     }
+//! }
+//!
+//! let nodes = parse(&tokenize("｜※［＃「丸印」、U+25CB］《まる》"));
+//! let mut collector = GaijiCollector(Vec::new());
+//! collector.visit_nodes(&nodes);
+//! assert_eq!(collector.0, vec!["「丸印」、U+25CB"]);
+//! ```
+
+use super::{
+    BlockParams, BlockType, BreakKind, CorrectionKind, FontSizeType, MidashiLevel, MidashiStyle,
+    Node, RubyDirection, StyleType,
+};
+
+/// ノードを参照で走査するトレイト
+pub trait Visitor {
+    /// ノード列を走査
+    fn visit_nodes(&mut self, nodes: &[Node]) {
+        for node in nodes {
+            self.visit_node(node);
+        }
+    }
+
+    /// ノードを走査（種類ごとのメソッドに振り分ける）
+    fn visit_node(&mut self, node: &Node) {
+        walk_node(self, node);
+    }
+
+    /// プレーンテキスト
+    fn visit_text(&mut self, _text: &str) {}
+
+    /// ルビ
+    fn visit_ruby(&mut self, children: &[Node], ruby: &[Node], _direction: RubyDirection) {
+        self.visit_nodes(children);
+        self.visit_nodes(ruby);
+    }
+
+    /// 装飾
+    fn visit_style(&mut self, children: &[Node], _style_type: StyleType, _class_name: &str) {
+        self.visit_nodes(children);
+    }
+
+    /// 見出し
+    fn visit_midashi(&mut self, children: &[Node], _level: MidashiLevel, _style: MidashiStyle) {
+        self.visit_nodes(children);
+    }
+
+    /// 外字
+    fn visit_gaiji(&mut self, _description: &str, _unicode: Option<&str>, _jis_code: Option<&str>) {
+    }
+
+    /// アクセント文字
+    fn visit_accent(&mut self, _code: &str, _name: &str, _unicode: Option<&str>) {}
+
+    /// 画像
+    fn visit_img(
+        &mut self,
+        _filename: &str,
+        _alt: &str,
+        _css_class: &str,
+        _width: Option<u32>,
+        _height: Option<u32>,
+    ) {
+    }
+
+    /// 縦中横
+    fn visit_tcy(&mut self, children: &[Node]) {
+        self.visit_nodes(children);
+    }
+
+    /// 罫囲み
+    fn visit_keigakomi(&mut self, children: &[Node]) {
+        self.visit_nodes(children);
+    }
+
+    /// 横組み
+    fn visit_yokogumi(&mut self, children: &[Node]) {
+        self.visit_nodes(children);
+    }
+
+    /// キャプション
+    fn visit_caption(&mut self, children: &[Node]) {
+        self.visit_nodes(children);
+    }
+
+    /// 割書き
+    fn visit_warigaki(&mut self, upper: &[Node], lower: &[Node]) {
+        self.visit_nodes(upper);
+        self.visit_nodes(lower);
+    }
+
+    /// フォントサイズ
+    fn visit_font_size(&mut self, children: &[Node], _size_type: FontSizeType, _level: u32) {
+        self.visit_nodes(children);
+    }
+
+    /// 返り点
+    fn visit_kaeriten(&mut self, _text: &str) {}
+
+    /// 訓点送り仮名
+    fn visit_okurigana(&mut self, _text: &str) {}
+
+    /// ブロック開始
+    fn visit_block_start(&mut self, _block_type: BlockType, _params: &BlockParams) {}
+
+    /// ブロック終了
+    fn visit_block_end(&mut self, _block_type: BlockType, _params: &BlockParams) {}
+
+    /// 改ページなどの区切り
+    fn visit_break(&mut self, _kind: BreakKind) {}
+
+    /// 校正注記
+    fn visit_correction(
+        &mut self,
+        target: &[Node],
+        _original: Option<&str>,
+        _kind: CorrectionKind,
+    ) {
+        self.visit_nodes(target);
+    }
+
+    /// 注記
+    fn visit_note(&mut self, _text: &str) {}
+
+    /// 注記付き範囲の終了マーカー
+    fn visit_annotation_end(&mut self, _prefix: &str, content: &[Node], _suffix: &str) {
+        self.visit_nodes(content);
+    }
+
+    /// 未解決の前方参照
+    fn visit_unresolved_reference(&mut self, _target: &str, _spec: &str, _connector: &str) {}
+
+    /// 濁点カタカナ参照
+    fn visit_dakuten_katakana(&mut self, _num: &str) {}
+}
+
+/// ノードの種類に応じて [`Visitor`] のメソッドを呼び出す
+pub fn walk_node<V: Visitor + ?Sized>(visitor: &mut V, node: &Node) {
+    match node {
+        Node::Text(s) => visitor.visit_text(s),
+        Node::Ruby {
+            children,
+            ruby,
+            direction,
+        } => visitor.visit_ruby(children, ruby, *direction),
+        Node::Style {
+            children,
+            style_type,
+            class_name,
+        } => visitor.visit_style(children, *style_type, class_name),
+        Node::Midashi {
+            children,
+            level,
+            style,
+        } => visitor.visit_midashi(children, *level, *style),
+        Node::Gaiji {
+            description,
+            unicode,
+            jis_code,
+        } => visitor.visit_gaiji(description, unicode.as_deref(), jis_code.as_deref()),
+        Node::Accent {
+            code,
+            name,
+            unicode,
+        } => visitor.visit_accent(code, name, unicode.as_deref()),
+        Node::Img {
+            filename,
+            alt,
+            css_class,
+            width,
+            height,
+        } => visitor.visit_img(filename, alt, css_class, *width, *height),
+        Node::Tcy { children } => visitor.visit_tcy(children),
+        Node::Keigakomi { children } => visitor.visit_keigakomi(children),
+        Node::Yokogumi { children } => visitor.visit_yokogumi(children),
+        Node::Caption { children } => visitor.visit_caption(children),
+        Node::Warigaki { upper, lower } => visitor.visit_warigaki(upper, lower),
+        Node::FontSize {
+            children,
+            size_type,
+            level,
+        } => visitor.visit_font_size(children, *size_type, *level),
+        Node::Kaeriten(s) => visitor.visit_kaeriten(s),
+        Node::Okurigana(s) => visitor.visit_okurigana(s),
+        Node::BlockStart { block_type, params } => visitor.visit_block_start(*block_type, params),
+        Node::BlockEnd { block_type, params } => visitor.visit_block_end(*block_type, params),
+        Node::Break { kind } => visitor.visit_break(*kind),
+        Node::Correction {
+            target,
+            original,
+            kind,
+        } => visitor.visit_correction(target, original.as_deref(), *kind),
+        Node::Note(s) => visitor.visit_note(s),
+        Node::AnnotationEnd {
+            prefix,
+            content,
+            suffix,
+        } => visitor.visit_annotation_end(prefix, content, suffix),
+        Node::UnresolvedReference {
+            target,
+            spec,
+            connector,
+        } => visitor.visit_unresolved_reference(target, spec, connector),
+        Node::DakutenKatakana { num } => visitor.visit_dakuten_katakana(num),
+    }
+}
+
+/// ノードを可変参照で走査・書き換えるトレイト
+///
+/// [`VisitorMut::visit_nodes_mut`] はノード列そのものを受け取るため、
+/// 上書きすればノードの追加・削除・置き換えもできます。
+pub trait VisitorMut {
+    /// ノード列を走査
+    fn visit_nodes_mut(&mut self, nodes: &mut Vec<Node>) {
+        for node in nodes.iter_mut() {
+            self.visit_node_mut(node);
+        }
+    }
+
+    /// ノードを走査（種類ごとのメソッドに振り分ける）
+    fn visit_node_mut(&mut self, node: &mut Node) {
+        walk_node_mut(self, node);
+    }
+
+    /// プレーンテキスト
+    fn visit_text_mut(&mut self, _text: &mut String) {}
+
+    /// ルビ
+    fn visit_ruby_mut(
+        &mut self,
+        children: &mut Vec<Node>,
+        ruby: &mut Vec<Node>,
+        _direction: &mut RubyDirection,
+    ) {
+        self.visit_nodes_mut(children);
+        self.visit_nodes_mut(ruby);
+    }
+
+    /// 装飾
+    fn visit_style_mut(
+        &mut self,
+        children: &mut Vec<Node>,
+        _style_type: &mut StyleType,
+        _class_name: &mut String,
+    ) {
+        self.visit_nodes_mut(children);
+    }
+
+    /// 見出し
+    fn visit_midashi_mut(
+        &mut self,
+        children: &mut Vec<Node>,
+        _level: &mut MidashiLevel,
+        _style: &mut MidashiStyle,
+    ) {
+        self.visit_nodes_mut(children);
+    }
+
+    /// 外字
+    fn visit_gaiji_mut(
+        &mut self,
+        _description: &mut String,
+        _unicode: &mut Option<String>,
+        _jis_code: &mut Option<String>,
+    ) {
+    }
+
+    /// アクセント文字
+    fn visit_accent_mut(
+        &mut self,
+        _code: &mut String,
+        _name: &mut String,
+        _unicode: &mut Option<String>,
+    ) {
+    }
+
+    /// 画像
+    fn visit_img_mut(
+        &mut self,
+        _filename: &mut String,
+        _alt: &mut String,
+        _css_class: &mut String,
+        _width: &mut Option<u32>,
+        _height: &mut Option<u32>,
+    ) {
+    }
+
+    /// 縦中横
+    fn visit_tcy_mut(&mut self, children: &mut Vec<Node>) {
+        self.visit_nodes_mut(children);
+    }
+
+    /// 罫囲み
+    fn visit_keigakomi_mut(&mut self, children: &mut Vec<Node>) {
+        self.visit_nodes_mut(children);
+    }
+
+    /// 横組み
+    fn visit_yokogumi_mut(&mut self, children: &mut Vec<Node>) {
+        self.visit_nodes_mut(children);
+    }
+
+    /// キャプション
+    fn visit_caption_mut(&mut self, children: &mut Vec<Node>) {
+        self.visit_nodes_mut(children);
+    }
+
+    /// 割書き
+    fn visit_warigaki_mut(&mut self, upper: &mut Vec<Node>, lower: &mut Vec<Node>) {
+        self.visit_nodes_mut(upper);
+        self.visit_nodes_mut(lower);
+    }
+
+    /// フォントサイズ
+    fn visit_font_size_mut(
+        &mut self,
+        children: &mut Vec<Node>,
+        _size_type: &mut FontSizeType,
+        _level: &mut u32,
+    ) {
+        self.visit_nodes_mut(children);
+    }
+
+    /// 返り点
+    fn visit_kaeriten_mut(&mut self, _text: &mut String) {}
+
+    /// 訓点送り仮名
+    fn visit_okurigana_mut(&mut self, _text: &mut String) {}
+
+    /// ブロック開始
+    fn visit_block_start_mut(&mut self, _block_type: &mut BlockType, _params: &mut BlockParams) {}
+
+    /// ブロック終了
+    fn visit_block_end_mut(&mut self, _block_type: &mut BlockType, _params: &mut BlockParams) {}
+
+    /// 改ページなどの区切り
+    fn visit_break_mut(&mut self, _kind: &mut BreakKind) {}
+
+    /// 校正注記
+    fn visit_correction_mut(
+        &mut self,
+        target: &mut Vec<Node>,
+        _original: &mut Option<String>,
+        _kind: &mut CorrectionKind,
+    ) {
+        self.visit_nodes_mut(target);
+    }
+
+    /// 注記
+    fn visit_note_mut(&mut self, _text: &mut String) {}
+
+    /// 注記付き範囲の終了マーカー
+    fn visit_annotation_end_mut(
+        &mut self,
+        _prefix: &mut String,
+        content: &mut Vec<Node>,
+        _suffix: &mut String,
+    ) {
+        self.visit_nodes_mut(content);
+    }
+
+    /// 未解決の前方参照
+    fn visit_unresolved_reference_mut(
+        &mut self,
+        _target: &mut String,
+        _spec: &mut String,
+        _connector: &mut String,
+    ) {
+    }
+
+    /// 濁点カタカナ参照
+    fn visit_dakuten_katakana_mut(&mut self, _num: &mut String) {}
+}
+
+/// ノードの種類に応じて [`VisitorMut`] のメソッドを呼び出す
+pub fn walk_node_mut<V: VisitorMut + ?Sized>(visitor: &mut V, node: &mut Node) {
+    match node {
+        Node::Text(s) => visitor.visit_text_mut(s),
+        Node::Ruby {
+            children,
+            ruby,
+            direction,
+        } => visitor.visit_ruby_mut(children, ruby, direction),
+        Node::Style {
+            children,
+            style_type,
+            class_name,
+        } => visitor.visit_style_mut(children, style_type, class_name),
+        Node::Midashi {
+            children,
+            level,
+            style,
+        } => visitor.visit_midashi_mut(children, level, style),
+        Node::Gaiji {
+            description,
+            unicode,
+            jis_code,
+        } => visitor.visit_gaiji_mut(description, unicode, jis_code),
+        Node::Accent {
+            code,
+            name,
+            unicode,
+        } => visitor.visit_accent_mut(code, name, unicode),
+        Node::Img {
+            filename,
+            alt,
+            css_class,
+            width,
+            height,
+        } => visitor.visit_img_mut(filename, alt, css_class, width, height),
+        Node::Tcy { children } => visitor.visit_tcy_mut(children),
+        Node::Keigakomi { children } => visitor.visit_keigakomi_mut(children),
+        Node::Yokogumi { children } => visitor.visit_yokogumi_mut(children),
+        Node::Caption { children } => visitor.visit_caption_mut(children),
+        Node::Warigaki { upper, lower } => visitor.visit_warigaki_mut(upper, lower),
+        Node::FontSize {
+            children,
+            size_type,
+            level,
+        } => visitor.visit_font_size_mut(children, size_type, level),
+        Node::Kaeriten(s) => visitor.visit_kaeriten_mut(s),
+        Node::Okurigana(s) => visitor.visit_okurigana_mut(s),
+        Node::BlockStart { block_type, params } => {
+            visitor.visit_block_start_mut(block_type, params)
+        }
+        Node::BlockEnd { block_type, params } => visitor.visit_block_end_mut(block_type, params),
+        Node::Break { kind } => visitor.visit_break_mut(kind),
+        Node::Correction {
+            target,
+            original,
+            kind,
+        } => visitor.visit_correction_mut(target, original, kind),
+        Node::Note(s) => visitor.visit_note_mut(s),
+        Node::AnnotationEnd {
+            prefix,
+            content,
+            suffix,
+        } => visitor.visit_annotation_end_mut(prefix, content, suffix),
+        Node::UnresolvedReference {
+            target,
+            spec,
+            connector,
+        } => visitor.visit_unresolved_reference_mut(target, spec, connector),
+        Node::DakutenKatakana { num } => visitor.visit_dakuten_katakana_mut(num),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<Node> {
+        vec![
+            Node::text("前"),
+            Node::Ruby {
+                children: vec![Node::text("漢字")],
+                ruby: vec![Node::text("かんじ")],
+                direction: RubyDirection::Right,
+            },
+            Node::Warigaki {
+                upper: vec![Node::text("上")],
+                lower: vec![Node::Style {
+                    children: vec![Node::text("下")],
+                    style_type: StyleType::Bold,
+                    class_name: "futoji".to_string(),
+                }],
+            },
+            Node::Note("注記".to_string()),
+        ]
+    }
+
+    #[test]
+    fn test_visitor_walks_children() {
+        struct TextCollector(Vec<String>);
+        impl Visitor for TextCollector {
+            fn visit_text(&mut self, text: &str) {
+                self.0.push(text.to_string());
+            }
+        }
+
+        let mut collector = TextCollector(Vec::new());
+        collector.visit_nodes(&sample());
+        assert_eq!(collector.0, vec!["前", "漢字", "かんじ", "上", "下"]);
+    }
+
+    #[test]
+    fn test_visitor_override_stops_recursion() {
+        /// ルビ文字を除いたテキスト
+        struct BaseText(String);
+        impl Visitor for BaseText {
+            fn visit_text(&mut self, text: &str) {
+                self.0.push_str(text);
+            }
+            fn visit_ruby(&mut self, children: &[Node], _: &[Node], _: RubyDirection) {
+                self.visit_nodes(children);
+            }
+        }
+
+        let mut base = BaseText(String::new());
+        base.visit_nodes(&sample());
+        assert_eq!(base.0, "前漢字上下");
+    }
+
+    #[test]
+    fn test_visitor_mut() {
+        /// テキストを書き換え、注記を削除する
+        struct Rewriter;
+        impl VisitorMut for Rewriter {
+            fn visit_nodes_mut(&mut self, nodes: &mut Vec<Node>) {
+                nodes.retain(|n| !matches!(n, Node::Note(_)));
+                for node in nodes.iter_mut() {
+                    self.visit_node_mut(node);
+                }
+            }
+            fn visit_text_mut(&mut self, text: &mut String) {
+                text.insert(0, '*');
+            }
+        }
+
+        let mut nodes = sample();
+        Rewriter.visit_nodes_mut(&mut nodes);
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(nodes[0], Node::text("*前"));
+        let text: String = nodes.iter().map(|n| n.to_text()).collect();
+        assert_eq!(text, "*前*漢字*上（*下）");
+    }
+}
@@ -0,0 +1,205 @@
+//! 見出しの階層（目次）
+//!
+//! 本文の大見出し・中見出し・小見出しを集め、階層にします。
+//! 見出しのIDはHTML変換で付けるアンカー（`midashi100` など）と同じです。
+
+use crate::document::DocumentLine;
+use crate::node::{walk_node, BlockType, MidashiLevel, Node, Visitor};
+
+/// 見出しの階層の項目
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OutlineEntry {
+    /// 見出しのレベル
+    pub level: MidashiLevel,
+    /// 見出しのID（例: `midashi100`）
+    pub id: String,
+    /// 見出しのテキスト（ルビは除く）
+    pub text: String,
+    /// 見出しの始まる行（0始まり）
+    pub line: usize,
+    /// 下位の見出し
+    pub children: Vec<OutlineEntry>,
+}
+
+/// 本文の行から見出しの階層を作成
+///
+/// 見出しは、より上位のレベルの直前の見出しの下に入ります。
+///
+/// # Examples
+///
+/// ```
+/// use aozora_core::document::AozoraDocument;
+/// use aozora_core::node::MidashiLevel;
+///
+/// let document = AozoraDocument::parse(
+///     "題名\n\n第一章［＃「第一章」は大見出し］\n一［＃「一」は中見出し］\n本文\n第二章［＃「第二章」は大見出し］",
+/// );
+/// let outline = document.outline();
+/// assert_eq!(outline.len(), 2);
+/// assert_eq!(outline[0].text, "第一章");
+/// assert_eq!(outline[0].id, "midashi100");
+/// assert_eq!(outline[0].children[0].level, MidashiLevel::Naka);
+/// assert_eq!(outline[0].children[0].id, "midashi110");
+/// assert_eq!(outline[1].id, "midashi210");
+/// ```
+pub fn build_outline(lines: &[DocumentLine]) -> Vec<OutlineEntry> {
+    let mut collector = OutlineCollector::default();
+    for line in lines {
+        collector.line = line.line;
+        if collector.open.is_some() {
+            // 複数行にわたる見出しは行を空白でつなぐ
+            collector.append(" ");
+        }
+        collector.visit_nodes(&line.to_nodes());
+    }
+    collector.close();
+
+    let mut outline = Vec::new();
+    for entry in collector.entries {
+        insert_entry(&mut outline, entry);
+    }
+    outline
+}
+
+/// 項目を、より上位のレベルの直前の項目の下に追加
+fn insert_entry(entries: &mut Vec<OutlineEntry>, entry: OutlineEntry) {
+    match entries.last_mut() {
+        Some(parent) if rank(parent.level) < rank(entry.level) => {
+            insert_entry(&mut parent.children, entry)
+        }
+        _ => entries.push(entry),
+    }
+}
+
+/// レベルの順位（大見出しが0）
+fn rank(level: MidashiLevel) -> u8 {
+    match level {
+        MidashiLevel::O => 0,
+        MidashiLevel::Naka => 1,
+        MidashiLevel::Ko => 2,
+    }
+}
+
+/// 見出しを文書の順に集める
+#[derive(Default)]
+struct OutlineCollector {
+    /// 集めた見出し（階層なし）
+    entries: Vec<OutlineEntry>,
+    /// 見出しIDの番号
+    counter: u32,
+    /// 走査中の行
+    line: usize,
+    /// テキストを集めている見出しブロック（`entries` の位置）
+    open: Option<usize>,
+}
+
+impl OutlineCollector {
+    /// 見出しを追加し、その位置を返す
+    fn push(&mut self, level: MidashiLevel, text: String) -> usize {
+        self.counter += level.id_step();
+        self.entries.push(OutlineEntry {
+            level,
+            id: format!("midashi{}", self.counter),
+            text,
+            line: self.line,
+            children: Vec::new(),
+        });
+        self.entries.len() - 1
+    }
+
+    /// 見出しブロックのテキストに追加
+    fn append(&mut self, text: &str) {
+        if let Some(index) = self.open {
+            self.entries[index].text.push_str(text);
+        }
+    }
+
+    /// 見出しブロックを閉じる
+    fn close(&mut self) {
+        if let Some(index) = self.open.take() {
+            let entry = &mut self.entries[index];
+            entry.text = entry.text.trim().to_string();
+        }
+    }
+}
+
+impl Visitor for OutlineCollector {
+    fn visit_node(&mut self, node: &Node) {
+        match node {
+            Node::Midashi {
+                children, level, ..
+            } => {
+                let text = children.iter().map(Node::to_text).collect();
+                self.push(*level, text);
+            }
+            Node::BlockStart {
+                block_type: BlockType::Midashi,
+                params,
+            } => {
+                self.close();
+                let index = self.push(params.level.unwrap_or(MidashiLevel::O), String::new());
+                self.open = Some(index);
+            }
+            Node::BlockEnd {
+                block_type: BlockType::Midashi,
+                ..
+            } => self.close(),
+            _ if self.open.is_some() => self.append(&node.to_text()),
+            _ => walk_node(self, node),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::AozoraDocument;
+
+    fn outline(text: &str) -> Vec<OutlineEntry> {
+        AozoraDocument::parse(text).outline()
+    }
+
+    #[test]
+    fn test_hierarchy() {
+        let outline = outline(
+            "題名\n\n\
+             一［＃「一」は中見出し］\n\
+             上［＃「上」は大見出し］\n\
+             １［＃「１」は小見出し］\n\
+             二［＃「二」は中見出し］\n\
+             ２［＃「２」は小見出し］\n\
+             下［＃「下」は大見出し］\n",
+        );
+        let summary: Vec<_> = outline
+            .iter()
+            .map(|e| {
+                let children: Vec<_> = e.children.iter().map(|c| c.text.as_str()).collect();
+                (e.text.as_str(), e.id.as_str(), children)
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("一", "midashi10", vec![]),
+                ("上", "midashi110", vec!["１", "二"]),
+                ("下", "midashi222", vec![]),
+            ]
+        );
+        assert_eq!(outline[1].children[1].children[0].text, "２");
+        assert_eq!(outline[1].children[1].children[0].line, 6);
+    }
+
+    #[test]
+    fn test_block_midashi() {
+        let outline = outline(
+            "題名\n\n\
+             ［＃大見出し］第一章　｜猫《ねこ》［＃大見出し終わり］\n\
+             ［＃ここから中見出し］\n前編\n吾輩は\n［＃ここで中見出し終わり］\n本文\n",
+        );
+        assert_eq!(outline.len(), 1);
+        assert_eq!(outline[0].text, "第一章　猫");
+        assert_eq!(outline[0].children[0].text, "前編 吾輩は");
+        assert_eq!(outline[0].children[0].id, "midashi110");
+    }
+}
@@ -10,8 +10,10 @@ use super::utils::extract_number;
 /// ブロック開始を解析
 pub fn parse_block_start(content: &str) -> CommandResult {
     let content = content.trim_start_matches("ここから");
-    let mut params = BlockParams::default();
-    params.is_block = true; // ここから pattern is block-level
+    let mut params = BlockParams {
+        is_block: true, // ここから pattern is block-level
+        ..Default::default()
+    };
 
     // ぶら下げパターン: 「N字下げ、折り返してM字下げ」または「改行天付き、折り返してN字下げ」
     if content.contains("折り返して") {
@@ -40,7 +42,7 @@ pub fn parse_block_start(content: &str) -> CommandResult {
         }
         CommandResult::BlockStart { block_type, params }
     } else {
-        CommandResult::Note(format!("ここから{content}"))
+        CommandResult::Unknown(format!("ここから{content}"))
     }
 }
 
@@ -82,7 +84,7 @@ pub fn parse_block_end(content: &str) -> CommandResult {
     if let Some(block_type) = BlockType::from_command(content) {
         CommandResult::BlockEnd { block_type }
     } else {
-        CommandResult::Note(format!("ここで{content}終わり"))
+        CommandResult::Unknown(format!("ここで{content}終わり"))
     }
 }
 
@@ -108,7 +110,7 @@ pub fn parse_inline_end(content: &str) -> CommandResult {
         return CommandResult::BlockEnd { block_type };
     }
 
-    CommandResult::Note(format!("{content}終わり"))
+    CommandResult::Unknown(format!("{content}終わり"))
 }
 
 /// 行単位字下げを解析
@@ -139,9 +141,11 @@ pub fn try_parse_line_chitsuki(content: &str) -> Option<CommandResult> {
 pub fn try_parse_midashi_start(content: &str) -> Option<CommandResult> {
     let level = MidashiLevel::from_command(content)?;
     let style = MidashiStyle::from_command(content);
-    let mut params = BlockParams::default();
-    params.level = Some(level);
-    params.midashi_style = Some(style);
+    let params = BlockParams {
+        level: Some(level),
+        midashi_style: Some(style),
+        ..Default::default()
+    };
     Some(CommandResult::BlockStart {
         block_type: BlockType::Midashi,
         params,
@@ -151,8 +155,10 @@ pub fn try_parse_midashi_start(content: &str) -> Option<CommandResult> {
 /// インラインフォントサイズ開始を解析
 pub fn try_parse_font_size_start(content: &str) -> Option<CommandResult> {
     let (size_type, level) = FontSizeType::from_command(content)?;
-    let mut params = BlockParams::default();
-    params.font_size = Some(level);
+    let params = BlockParams {
+        font_size: Some(level),
+        ..Default::default()
+    };
     Some(CommandResult::BlockStart {
         block_type: match size_type {
             FontSizeType::Dai => BlockType::FontDai,
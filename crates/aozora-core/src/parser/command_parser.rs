@@ -2,7 +2,10 @@
 //!
 //! `［＃...］` 形式のコマンド内容を解析し、適切なノードまたはコマンド情報を返します。
 
-use crate::node::{BlockParams, BlockType, FontSizeType, MidashiLevel, MidashiStyle, StyleType};
+use crate::node::{
+    BlockParams, BlockType, BreakKind, CorrectionKind, FontSizeType, MidashiLevel, MidashiStyle,
+    StyleType,
+};
 
 use super::block_parser::{
     parse_block_end, parse_block_start, parse_inline_end, try_parse_font_size_start,
@@ -50,6 +53,9 @@ pub enum CommandResult {
     /// 行単位地付き/地から
     LineChitsuki { width: u32 },
 
+    /// 改ページ・改丁・改見開き・改段
+    Break { kind: BreakKind },
+
     /// 注記
     Note(String),
 
@@ -124,10 +130,38 @@ pub enum CommandResult {
     /// 傍記（工場に「×」の傍記）
     SideNote { target: String, annotation: String },
 
+    /// 校正注記（「対象」は底本では「原文」、「対象」はママ）
+    Correction {
+        target: String,
+        original: Option<String>,
+        kind: CorrectionKind,
+    },
+
     /// 未知のコマンド
     Unknown(String),
 }
 
+impl CommandResult {
+    /// 前方の文字列を参照するコマンド（「対象」は〜 形式）かどうか
+    ///
+    /// これらは参照解決で対象が見つからなければ注記として出力されます。
+    pub fn is_reference(&self) -> bool {
+        matches!(
+            self,
+            CommandResult::Style { .. }
+                | CommandResult::Midashi { .. }
+                | CommandResult::FontSize { .. }
+                | CommandResult::AnnotationRuby { .. }
+                | CommandResult::InlineTcy { .. }
+                | CommandResult::InlineKeigakomi { .. }
+                | CommandResult::InlineYokogumi { .. }
+                | CommandResult::InlineCaption { .. }
+                | CommandResult::SideNote { .. }
+                | CommandResult::Correction { .. }
+        )
+    }
+}
+
 /// コマンド文字列を解析
 pub fn parse_command(content: &str) -> CommandResult {
     let content = content.trim();
@@ -201,17 +235,22 @@ pub fn parse_command(content: &str) -> CommandResult {
         }
     }
 
-    // 12. 縦中横
+    // 12. 改ページ・改丁・改見開き・改段
+    if let Some(kind) = BreakKind::from_command(content) {
+        return CommandResult::Break { kind };
+    }
+
+    // 13. 縦中横
     if content == "縦中横" {
         return CommandResult::TcyStart;
     }
 
-    // 13. 割り注
+    // 14. 割り注
     if content == "割り注" {
         return CommandResult::WarigakiStart;
     }
 
-    // 13.5. 罫囲み（インライン）
+    // 14.5. 罫囲み（インライン）
     if content == "罫囲み" {
         return CommandResult::BlockStart {
             block_type: BlockType::Keigakomi,
@@ -219,7 +258,7 @@ pub fn parse_command(content: &str) -> CommandResult {
         };
     }
 
-    // 13.6. 横組み（インライン）
+    // 14.6. 横組み（インライン）
     if content == "横組み" {
         return CommandResult::BlockStart {
             block_type: BlockType::Yokogumi,
@@ -227,28 +266,28 @@ pub fn parse_command(content: &str) -> CommandResult {
         };
     }
 
-    // 14. 装飾開始
+    // 15. 装飾開始
     if let Some(style_type) = StyleType::from_command(content) {
         return CommandResult::StyleStart { style_type };
     }
 
-    // 15. キャプション開始
+    // 16. キャプション開始
     if content == "キャプション" {
         return CommandResult::CaptionStart;
     }
 
-    // 16. 見出し開始
+    // 17. 見出し開始
     if let Some(result) = try_parse_midashi_start(content) {
         return result;
     }
 
-    // 17. インラインフォントサイズ開始
+    // 18. インラインフォントサイズ開始
     if let Some(result) = try_parse_font_size_start(content) {
         return result;
     }
 
-    // その他は注記
-    CommandResult::Note(content.to_string())
+    // その他は未知のコマンド（注記として扱われる）
+    CommandResult::Unknown(content.to_string())
 }
 
 /// 注記付き範囲パターンを解析
@@ -428,10 +467,38 @@ mod tests {
         assert_eq!(parse_command("割り注終わり"), CommandResult::WarigakiEnd);
     }
 
+    #[test]
+    fn test_parse_break() {
+        assert_eq!(
+            parse_command("改ページ"),
+            CommandResult::Break {
+                kind: BreakKind::Page
+            }
+        );
+        assert_eq!(
+            parse_command("改丁"),
+            CommandResult::Break {
+                kind: BreakKind::Recto
+            }
+        );
+        assert_eq!(
+            parse_command("改見開き"),
+            CommandResult::Break {
+                kind: BreakKind::Spread
+            }
+        );
+        assert_eq!(
+            parse_command("改段"),
+            CommandResult::Break {
+                kind: BreakKind::Column
+            }
+        );
+    }
+
     #[test]
     fn test_parse_unknown() {
-        let result = parse_command("改ページ");
-        assert_eq!(result, CommandResult::Note("改ページ".to_string()));
+        let result = parse_command("改行なし");
+        assert_eq!(result, CommandResult::Unknown("改行なし".to_string()));
     }
 
     #[test]
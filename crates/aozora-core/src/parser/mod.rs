@@ -10,13 +10,15 @@ pub mod reference_resolver;
 pub mod ruby_parser;
 mod utils;
 
-use crate::node::{
-    BlockParams, BlockType, FontSizeType, MidashiLevel, MidashiStyle, Node, RubyDirection,
-};
+use crate::node::{BlockParams, BlockType, CorrectionKind, FontSizeType, Node, RubyDirection};
+use crate::span::Spanned;
 use crate::token::Token;
 
 pub use command_parser::{parse_command, CommandResult};
-pub use reference_resolver::{resolve_inline_ruby, resolve_references};
+pub use reference_resolver::{
+    resolve_inline_ruby, resolve_inline_ruby_with_spans, resolve_references,
+    resolve_references_with_spans,
+};
 pub use ruby_parser::extract_ruby_base;
 
 /// トークン列をノード列にパース
@@ -35,7 +37,7 @@ pub fn parse(tokens: &[Token]) -> Vec<Node> {
     let mut nodes = Vec::new();
 
     for (i, token) in tokens.iter().enumerate() {
-        let parsed = parse_token_with_context(token, &nodes, tokens, i);
+        let parsed = parse_token_with_context(token, nodes.last(), tokens.get(i + 1));
         nodes.extend(parsed);
     }
 
@@ -45,9 +47,40 @@ pub fn parse(tokens: &[Token]) -> Vec<Node> {
     nodes
 }
 
+/// 位置情報付きトークン列を位置情報付きノード列にパース
+///
+/// 1つのトークンから生成されたノードはそのトークンの範囲を持ちます。
+/// 前方参照で生成された装飾ノードやルビノードは、対象テキストの範囲を持ちます。
+///
+/// # Examples
+///
+/// ```
+/// use aozora_core::tokenizer::tokenize_with_spans;
+/// use aozora_core::parser::parse_with_spans;
+/// use aozora_core::span::Span;
+///
+/// let nodes = parse_with_spans(&tokenize_with_spans("私の東京《とうきょう》"));
+/// assert_eq!(nodes[1].span, Span::new(6, 33));
+/// ```
+pub fn parse_with_spans(tokens: &[Spanned<Token>]) -> Vec<Spanned<Node>> {
+    let mut nodes: Vec<Spanned<Node>> = Vec::new();
+
+    for (i, token) in tokens.iter().enumerate() {
+        let prev = nodes.last().map(|n| &n.value);
+        let next = tokens.get(i + 1).map(|t| &t.value);
+        let parsed = parse_token_with_context(&token.value, prev, next);
+        nodes.extend(parsed.into_iter().map(|n| Spanned::new(n, token.span)));
+    }
+
+    // 前方参照の解決
+    resolve_references_with_spans(&mut nodes);
+
+    nodes
+}
+
 /// 直前のノードがテキストで `（` で終わるかチェック
-fn has_open_paren_before(nodes: &[Node]) -> bool {
-    nodes.last().map_or(false, |node| {
+fn has_open_paren_before(prev: Option<&Node>) -> bool {
+    prev.is_some_and(|node| {
         if let Node::Text(s) = node {
             s.ends_with('（')
         } else {
@@ -57,8 +90,8 @@ fn has_open_paren_before(nodes: &[Node]) -> bool {
 }
 
 /// 直後のトークンがテキストで `）` で始まるかチェック
-fn has_close_paren_after(tokens: &[Token], current_index: usize) -> bool {
-    tokens.get(current_index + 1).map_or(false, |token| {
+fn has_close_paren_after(next: Option<&Token>) -> bool {
+    next.is_some_and(|token| {
         if let Token::Text(s) = token {
             s.starts_with('）')
         } else {
@@ -68,20 +101,10 @@ fn has_close_paren_after(tokens: &[Token], current_index: usize) -> bool {
 }
 
 /// コンテキスト付きでトークンをパース
-fn parse_token_with_context(
-    token: &Token,
-    nodes: &[Node],
-    tokens: &[Token],
-    current_index: usize,
-) -> Vec<Node> {
+fn parse_token_with_context(token: &Token, prev: Option<&Node>, next: Option<&Token>) -> Vec<Node> {
     match token {
         Token::Command { content } => {
-            vec![parse_command_to_node_with_context(
-                content,
-                nodes,
-                tokens,
-                current_index,
-            )]
+            vec![parse_command_to_node_with_context(content, prev, next)]
         }
         _ => parse_token(token),
     }
@@ -175,20 +198,10 @@ fn parse_command_to_node(content: &str) -> Node {
         } => {
             // 後方参照見出し: 「対象」は見出し
             // specにはスタイル情報も含める
-            let spec = match (level, style) {
-                (MidashiLevel::O, MidashiStyle::Dogyo) => "同行大見出し",
-                (MidashiLevel::O, MidashiStyle::Mado) => "窓大見出し",
-                (MidashiLevel::O, MidashiStyle::Normal) => "大見出し",
-                (MidashiLevel::Naka, MidashiStyle::Dogyo) => "同行中見出し",
-                (MidashiLevel::Naka, MidashiStyle::Mado) => "窓中見出し",
-                (MidashiLevel::Naka, MidashiStyle::Normal) => "中見出し",
-                (MidashiLevel::Ko, MidashiStyle::Dogyo) => "同行小見出し",
-                (MidashiLevel::Ko, MidashiStyle::Mado) => "窓小見出し",
-                (MidashiLevel::Ko, MidashiStyle::Normal) => "小見出し",
-            };
+            let spec = format!("{}{}", style.command_prefix(), level.command_name());
             Node::UnresolvedReference {
                 target,
-                spec: spec.to_string(),
+                spec,
                 connector: "は".to_string(),
             }
         }
@@ -234,6 +247,8 @@ fn parse_command_to_node(content: &str) -> Node {
             },
         },
 
+        CommandResult::Break { kind } => Node::Break { kind },
+
         CommandResult::Note(text) => Node::Note(text),
 
         CommandResult::Image {
@@ -372,6 +387,25 @@ fn parse_command_to_node(content: &str) -> Node {
             }
         }
 
+        CommandResult::Correction {
+            target,
+            original,
+            kind,
+        } => {
+            // 校正注記: 「対象」は底本では「原文」/はママ → 後方参照として解決
+            // ルビのママはルビ文字を対象にするため専用のspecにする
+            let spec = match (kind, original) {
+                (CorrectionKind::RubySic, _) => "ruby_sic".to_string(),
+                (_, Some(original)) => format!("底本では「{original}」"),
+                (_, None) => "ママ".to_string(),
+            };
+            Node::UnresolvedReference {
+                target,
+                spec,
+                connector: "は".to_string(),
+            }
+        }
+
         CommandResult::Unknown(text) => Node::Note(text),
     }
 }
@@ -379,16 +413,17 @@ fn parse_command_to_node(content: &str) -> Node {
 /// コマンドをノードに変換（コンテキスト付き）
 fn parse_command_to_node_with_context(
     content: &str,
-    nodes: &[Node],
-    tokens: &[Token],
-    current_index: usize,
+    prev: Option<&Node>,
+    next: Option<&Token>,
 ) -> Node {
     use command_parser::CommandResult;
 
     match parse_command(content) {
         CommandResult::WarigakiStart => {
-            let mut params = BlockParams::default();
-            params.has_open_paren = has_open_paren_before(nodes);
+            let params = BlockParams {
+                has_open_paren: has_open_paren_before(prev),
+                ..Default::default()
+            };
             Node::BlockStart {
                 block_type: BlockType::Warigaki,
                 params,
@@ -396,8 +431,10 @@ fn parse_command_to_node_with_context(
         }
 
         CommandResult::WarigakiEnd => {
-            let mut params = BlockParams::default();
-            params.has_close_paren = has_close_paren_after(tokens, current_index);
+            let params = BlockParams {
+                has_close_paren: has_close_paren_after(next),
+                ..Default::default()
+            };
             Node::BlockEnd {
                 block_type: BlockType::Warigaki,
                 params,
@@ -513,4 +550,17 @@ mod tests {
             panic!("Expected Gaiji node");
         }
     }
+
+    #[test]
+    fn test_parse_with_spans() {
+        use crate::span::Span;
+        use crate::tokenizer::tokenize_with_spans;
+
+        let input = "猫である［＃「である」に傍点］";
+        let nodes = parse_with_spans(&tokenize_with_spans(input));
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].span, Span::new(0, 3));
+        assert!(matches!(&nodes[1].value, Node::Style { .. }));
+        assert_eq!(&input[nodes[1].span.start..nodes[1].span.end], "である");
+    }
 }
@@ -2,7 +2,7 @@
 //!
 //! 「対象」に/は/の 装飾 形式のコマンドを解析します。
 
-use crate::node::{FontSizeType, MidashiLevel, MidashiStyle, StyleType};
+use crate::node::{CorrectionKind, FontSizeType, MidashiLevel, MidashiStyle, StyleType};
 
 use super::command_parser::CommandResult;
 
@@ -15,9 +15,15 @@ pub fn try_parse_reference(content: &str) -> Option<CommandResult> {
         return None;
     }
 
+    let prefix = &content[..start];
     let target = &content[start + '「'.len_utf8()..end];
     let rest = &content[end + '」'.len_utf8()..];
 
+    // 校正注記（「対象」は底本では「…」、「対象」はママ）
+    if let Some(result) = try_parse_correction(prefix, target, rest) {
+        return Some(result);
+    }
+
     // 接続詞を探す
     // 「の左に」パターンを優先的にチェック
     let (connector, spec, is_left) = parse_connector(target, rest)?;
@@ -93,6 +99,26 @@ fn try_parse_annotation_ruby(target: &str, spec: &str) -> Option<CommandResult>
     })
 }
 
+/// 校正注記を解析
+///
+/// 「対象」は底本では「原文」、「対象」はママ、ルビの「対象」はママ の3形式です。
+fn try_parse_correction(prefix: &str, target: &str, rest: &str) -> Option<CommandResult> {
+    let (original, kind) = match (prefix, rest) {
+        ("", "はママ") => (None, CorrectionKind::Sic),
+        ("ルビの", "はママ") => (None, CorrectionKind::RubySic),
+        ("", _) => {
+            let original = rest.strip_prefix("は底本では「")?.strip_suffix('」')?;
+            (Some(original.to_string()), CorrectionKind::Corrected)
+        }
+        _ => return None,
+    };
+    Some(CommandResult::Correction {
+        target: target.to_string(),
+        original,
+        kind,
+    })
+}
+
 /// 接続詞を解析し、(接続詞, 仕様部分, 左ルビフラグ) を返す
 fn parse_connector<'a>(_target: &str, rest: &'a str) -> Option<(&'static str, &'a str, bool)> {
     // 「の左に」パターンを優先的にチェック
@@ -208,6 +234,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_correction() {
+        assert_eq!(
+            try_parse_reference("「灯」は底本では「燈」"),
+            Some(CommandResult::Correction {
+                target: "灯".to_string(),
+                original: Some("燈".to_string()),
+                kind: CorrectionKind::Corrected,
+            })
+        );
+        assert_eq!(
+            try_parse_reference("「云ふ」はママ"),
+            Some(CommandResult::Correction {
+                target: "云ふ".to_string(),
+                original: None,
+                kind: CorrectionKind::Sic,
+            })
+        );
+        assert_eq!(
+            try_parse_reference("ルビの「かんじ」はママ"),
+            Some(CommandResult::Correction {
+                target: "かんじ".to_string(),
+                original: None,
+                kind: CorrectionKind::RubySic,
+            })
+        );
+        // ルビの底本注記は扱わない
+        assert_eq!(
+            try_parse_reference("ルビの「かんじ」は底本では「かじ」"),
+            None
+        );
+    }
+
     #[test]
     fn test_parse_left_ruby() {
         let result = try_parse_left_ruby("「親文字」の左に「ルビ」のルビ");
@@ -4,15 +4,25 @@
 //! これらのコマンドは前方のテキストを参照し、装飾を適用します。
 
 use crate::node::{
-    BlockType, FontSizeType, MidashiLevel, MidashiStyle, Node, RubyDirection, StyleType,
+    BlockType, CorrectionKind, FontSizeType, MidashiLevel, MidashiStyle, Node, RubyDirection,
+    StyleType,
 };
-use crate::parser::ruby_parser::extract_ruby_base_from_nodes;
+use crate::parser::ruby_parser::extract_ruby_base_from_spanned_nodes;
+use crate::span::{Span, Spanned};
 use crate::tokenizer::tokenize;
 
 /// ノード列の前方参照を解決
 ///
 /// ルビの親文字抽出と、「〇〇」に傍点 形式の装飾コマンドを解決します。
 pub fn resolve_references(nodes: &mut Vec<Node>) {
+    with_dummy_spans(nodes, resolve_references_with_spans);
+}
+
+/// 位置情報付きノード列の前方参照を解決
+///
+/// [`resolve_references`] と同じ解決を行い、生成されたノードには
+/// 元になったソース範囲（親文字や対象テキストの範囲）を設定します。
+pub fn resolve_references_with_spans(nodes: &mut Vec<Spanned<Node>>) {
     // 1. ルビの親文字を解決
     resolve_ruby_bases(nodes);
 
@@ -28,85 +38,48 @@ pub fn resolve_references(nodes: &mut Vec<Node>) {
 /// 「漢字《かんじ》」形式のルビの親文字を解決します。
 /// 外字ノードも漢字として親文字に含めます。
 pub fn resolve_inline_ruby(nodes: &mut Vec<Node>) {
-    let mut i = 0;
-    while i < nodes.len() {
-        if let Node::Ruby {
-            children,
-            ruby,
-            direction,
-        } = &nodes[i]
-        {
-            if children.is_empty() && !ruby.is_empty() && i > 0 {
-                let ruby_clone = ruby.clone();
-                let direction_clone = *direction;
-
-                // 直前のノード列から親文字を抽出（外字も含む）
-                let preceding_nodes: Vec<Node> = nodes[..i].to_vec();
-                if let Some((remaining, base)) = extract_ruby_base_from_nodes(&preceding_nodes) {
-                    // 残りのノード数を計算
-                    let nodes_to_remove = preceding_nodes.len() - remaining.len();
-
-                    // 前半を残りのノードで置き換え
-                    let start_idx = i - nodes_to_remove;
-                    nodes.splice(start_idx..i, std::iter::empty());
-
-                    // 新しいインデックスを計算
-                    let new_i = start_idx;
-
-                    // 前半部分を挿入
-                    nodes.splice(..new_i, remaining.into_iter());
-
-                    // Rubyノードを更新（インデックスが変わっているので再計算）
-                    let ruby_idx = nodes
-                        .iter()
-                        .position(|n| matches!(n, Node::Ruby { children: c, .. } if c.is_empty()));
-
-                    if let Some(idx) = ruby_idx {
-                        nodes[idx] = Node::Ruby {
-                            children: base,
-                            ruby: ruby_clone,
-                            direction: direction_clone,
-                        };
-                    }
-                    continue; // iを増やさない（ノードを操作したので）
-                }
-            }
-        }
-        i += 1;
-    }
+    with_dummy_spans(nodes, resolve_inline_ruby_with_spans);
+}
+
+/// 位置情報付きノード列の行内ルビ親文字解決
+///
+/// 解決されたRubyノードの範囲は親文字の先頭からルビの末尾までになります。
+pub fn resolve_inline_ruby_with_spans(nodes: &mut Vec<Spanned<Node>>) {
+    resolve_ruby_bases(nodes);
+}
+
+/// 位置情報なしのノード列に対して位置情報付きの処理を適用
+fn with_dummy_spans(nodes: &mut Vec<Node>, f: impl FnOnce(&mut Vec<Spanned<Node>>)) {
+    let mut spanned: Vec<Spanned<Node>> = nodes
+        .drain(..)
+        .map(|n| Spanned::new(n, Span::default()))
+        .collect();
+    f(&mut spanned);
+    nodes.extend(spanned.into_iter().map(|n| n.value));
 }
 
 /// ルビの親文字を解決
-fn resolve_ruby_bases(nodes: &mut Vec<Node>) {
+fn resolve_ruby_bases(nodes: &mut Vec<Spanned<Node>>) {
     let mut i = 0;
     while i < nodes.len() {
         // 親文字が空のRubyノードを探す
-        if let Node::Ruby {
-            children,
-            ruby,
-            direction: _,
-        } = &nodes[i]
-        {
-            if children.is_empty() && !ruby.is_empty() {
-                // 直前のノードから親文字を抽出
-                if i > 0 {
-                    let preceding_nodes: Vec<Node> = nodes[..i].to_vec();
-                    if let Some((remaining, base)) = extract_ruby_base_from_nodes(&preceding_nodes)
-                    {
-                        // 直前のノードを更新
-                        let to_remove = i - (preceding_nodes.len() - remaining.len());
-
-                        // 残りのノードで前半を置き換え
-                        nodes.splice(..i, remaining.into_iter());
-
-                        // 新しいインデックスを計算
-                        let new_i = nodes.len() - (nodes.len() - to_remove);
-
-                        // Rubyノードを更新
-                        if let Some(Node::Ruby { children: c, .. }) = nodes.get_mut(new_i) {
-                            *c = base;
-                        }
+        if let Node::Ruby { children, ruby, .. } = &nodes[i].value {
+            if children.is_empty() && !ruby.is_empty() && i > 0 {
+                // 直前のノードから親文字を抽出（外字も含む）
+                if let Some((remaining, base)) = extract_ruby_base_from_spanned_nodes(&nodes[..i]) {
+                    let ruby_idx = remaining.len();
+                    let base_span = base[0].span;
+
+                    // 残りのノードで前半を置き換え
+                    nodes.splice(..i, remaining);
+
+                    // Rubyノードを更新
+                    let ruby_node = &mut nodes[ruby_idx];
+                    ruby_node.span = base_span.merge(ruby_node.span);
+                    if let Node::Ruby { children: c, .. } = &mut ruby_node.value {
+                        *c = base.into_iter().map(|n| n.value).collect();
                     }
+                    i = ruby_idx;
                 }
             }
         }
@@ -117,11 +90,11 @@ fn resolve_ruby_bases(nodes: &mut Vec<Node>) {
 /// 注記付き範囲を解決（BlockStart/BlockEnd → Ruby）
 ///
 /// `［＃注記付き］内容［＃「注記」の注記付き終わり］` を `<ruby><rb>内容</rb><rt>注記</rt></ruby>` に変換
-fn resolve_annotation_ranges(nodes: &mut Vec<Node>) {
+fn resolve_annotation_ranges(nodes: &mut Vec<Spanned<Node>>) {
     let mut i = 0;
     while i < nodes.len() {
         // 注記付き範囲の開始を探す
-        if let Node::BlockStart { block_type, .. } = &nodes[i] {
+        if let Node::BlockStart { block_type, .. } = &nodes[i].value {
             if *block_type == BlockType::AnnotationRange
                 || *block_type == BlockType::LeftAnnotationRange
             {
@@ -130,11 +103,11 @@ fn resolve_annotation_ranges(nodes: &mut Vec<Node>) {
                 // 対応する終了を探す
                 let mut end_idx = None;
                 let mut annotation = None;
-                for j in (i + 1)..nodes.len() {
+                for (j, node) in nodes.iter().enumerate().skip(i + 1) {
                     if let Node::BlockEnd {
                         block_type: bt,
                         params,
-                    } = &nodes[j]
+                    } = &node.value
                     {
                         if (*bt == BlockType::AnnotationRange && !is_left)
                             || (*bt == BlockType::LeftAnnotationRange && is_left)
@@ -147,8 +120,10 @@ fn resolve_annotation_ranges(nodes: &mut Vec<Node>) {
                 }
 
                 if let (Some(end_idx), Some(annotation)) = (end_idx, annotation) {
+                    let start_span = nodes[i].span;
+                    let end_span = nodes[end_idx].span;
                     // 開始から終了までの間のノードを収集
-                    let children: Vec<Node> = nodes[(i + 1)..end_idx].to_vec();
+                    let children: Vec<Spanned<Node>> = nodes[(i + 1)..end_idx].to_vec();
                     // 注記テキストをパース（外字を含む場合があるため）
                     let annotation_nodes = parse_annotation_text(&annotation);
 
@@ -156,26 +131,35 @@ fn resolve_annotation_ranges(nodes: &mut Vec<Node>) {
                         // 左注記の場合は注記として出力（Ruby版と同様）
                         // 開始マーカー + 内容ノード + 終了マーカー（外字を含む）
                         let mut new_nodes = Vec::new();
-                        new_nodes.push(Node::Note("左に注記付き".to_string()));
+                        new_nodes.push(Spanned::new(
+                            Node::Note("左に注記付き".to_string()),
+                            start_span,
+                        ));
                         new_nodes.extend(children);
                         // 終了マーカーは外字を含む可能性があるのでAnnotationEndノードを使用
-                        new_nodes.push(Node::AnnotationEnd {
-                            prefix: "左に「".to_string(),
-                            content: annotation_nodes,
-                            suffix: "」の注記付き終わり".to_string(),
-                        });
+                        new_nodes.push(Spanned::new(
+                            Node::AnnotationEnd {
+                                prefix: "左に「".to_string(),
+                                content: annotation_nodes,
+                                suffix: "」の注記付き終わり".to_string(),
+                            },
+                            end_span,
+                        ));
 
                         // 範囲を新しいノード列で置き換え
-                        nodes.splice(i..=end_idx, new_nodes.into_iter());
+                        nodes.splice(i..=end_idx, new_nodes);
                     } else {
                         // 通常の注記付きはRubyとして出力
                         let new_node = Node::Ruby {
-                            children,
+                            children: children.into_iter().map(|n| n.value).collect(),
                             ruby: annotation_nodes,
                             direction: RubyDirection::Right,
                         };
                         // 範囲を新しいノードで置き換え
-                        nodes.splice(i..=end_idx, std::iter::once(new_node));
+                        nodes.splice(
+                            i..=end_idx,
+                            std::iter::once(Spanned::new(new_node, start_span.merge(end_span))),
+                        );
                     }
                     // iを増やさない（置き換えたので次のノードは同じインデックス）
                     continue;
@@ -187,19 +171,33 @@ fn resolve_annotation_ranges(nodes: &mut Vec<Node>) {
 }
 
 /// 装飾の前方参照を解決
-fn resolve_style_references(nodes: &mut Vec<Node>) {
+fn resolve_style_references(nodes: &mut Vec<Spanned<Node>>) {
     let mut i = 0;
     while i < nodes.len() {
         if let Node::UnresolvedReference {
             target,
             spec,
             connector,
-        } = &nodes[i]
+        } = &nodes[i].value
         {
             let target_clone = target.clone();
             let spec_clone = spec.clone();
             let connector_clone = connector.clone();
 
+            // ルビのママは前方のルビをルビ文字で探して包む
+            if spec_clone == RUBY_SIC_SPEC {
+                if let Some(ruby_idx) = find_ruby_in_preceding(&nodes[..i], &target_clone) {
+                    let ruby = nodes[ruby_idx].value.clone();
+                    nodes[ruby_idx].value = Node::Correction {
+                        target: vec![ruby],
+                        original: None,
+                        kind: CorrectionKind::RubySic,
+                    };
+                    nodes.remove(i);
+                    continue;
+                }
+            }
+
             // 前方のノードから対象テキストを探す
             if let Some((_, found_node_idx, split_info)) =
                 find_target_in_preceding(&nodes[..i], &target_clone)
@@ -219,7 +217,11 @@ fn resolve_style_references(nodes: &mut Vec<Node>) {
             }
 
             // 解決できなかった場合はNoteノードに変換
-            nodes[i] = Node::Note(format!("「{target_clone}」{connector_clone}{spec_clone}"));
+            nodes[i].value = Node::Note(reference_command(
+                &target_clone,
+                &connector_clone,
+                &spec_clone,
+            ));
         }
         i += 1;
     }
@@ -227,7 +229,7 @@ fn resolve_style_references(nodes: &mut Vec<Node>) {
 
 /// 解決結果をノード列に適用
 fn apply_resolution(
-    nodes: &mut Vec<Node>,
+    nodes: &mut Vec<Spanned<Node>>,
     i: &mut usize,
     found_node_idx: usize,
     split_info: SplitInfo,
@@ -236,21 +238,26 @@ fn apply_resolution(
 ) {
     match split_info {
         SplitInfo::ExactMatch => {
-            let new_node = kind.create_node(target);
-            nodes[found_node_idx] = new_node;
+            nodes[found_node_idx].value = kind.create_node(target);
             nodes.remove(*i);
         }
         SplitInfo::Split { before, after } => {
+            let span = nodes[found_node_idx].span;
+            let text = nodes[found_node_idx].value.to_text();
+            let target_end = before.len() + target.len();
             let new_node = kind.create_node(target);
             let mut new_nodes = Vec::new();
             if !before.is_empty() {
-                new_nodes.push(Node::text(&before));
+                let before_span = span.sub_span(&text, 0..before.len());
+                new_nodes.push(Spanned::new(Node::text(&before), before_span));
             }
-            new_nodes.push(new_node);
+            let target_span = span.sub_span(&text, before.len()..target_end);
+            new_nodes.push(Spanned::new(new_node, target_span));
             if !after.is_empty() {
-                new_nodes.push(Node::text(&after));
+                let after_span = span.sub_span(&text, target_end..text.len());
+                new_nodes.push(Spanned::new(Node::text(&after), after_span));
             }
-            nodes.splice(found_node_idx..found_node_idx + 1, new_nodes.into_iter());
+            nodes.splice(found_node_idx..found_node_idx + 1, new_nodes);
             let adjustment =
                 if before.is_empty() { 0 } else { 1 } + if after.is_empty() { 0 } else { 1 };
             let new_i = *i + adjustment;
@@ -259,10 +266,17 @@ fn apply_resolution(
             }
         }
         SplitInfo::MultiNodeExact { start_idx, end_idx } => {
-            let children: Vec<Node> = nodes[start_idx..=end_idx].to_vec();
+            let span = nodes[start_idx].span.merge(nodes[end_idx].span);
+            let children: Vec<Node> = nodes[start_idx..=end_idx]
+                .iter()
+                .map(|n| n.value.clone())
+                .collect();
             let new_node = kind.create_node_with_children(children);
             let nodes_removed = end_idx - start_idx + 1;
-            nodes.splice(start_idx..=end_idx, std::iter::once(new_node));
+            nodes.splice(
+                start_idx..=end_idx,
+                std::iter::once(Spanned::new(new_node, span)),
+            );
             let new_i = *i - (nodes_removed - 1);
             if new_i < nodes.len() {
                 nodes.remove(new_i);
@@ -271,11 +285,38 @@ fn apply_resolution(
     }
 }
 
+/// ルビのママ（ルビの「対象」はママ）の参照spec
+pub(crate) const RUBY_SIC_SPEC: &str = "ruby_sic";
+
+/// 未解決の前方参照を注記の文字列（`［＃` `］` の内側）に戻す
+pub(crate) fn reference_command(target: &str, connector: &str, spec: &str) -> String {
+    if let Some(annotation) = spec.strip_prefix("annotation_ruby:") {
+        format!("「{target}」に「{annotation}」の注記")
+    } else if let Some(annotation) = spec.strip_prefix("side_note:") {
+        format!("「{target}」に「{annotation}」の傍記")
+    } else if spec == RUBY_SIC_SPEC {
+        format!("ルビの「{target}」はママ")
+    } else {
+        format!("「{target}」{connector}{spec}")
+    }
+}
+
+/// 前方のノードからルビ文字が一致するルビを探す（後ろから）
+fn find_ruby_in_preceding(nodes: &[Spanned<Node>], ruby_text: &str) -> Option<usize> {
+    nodes.iter().rposition(|node| {
+        matches!(&node.value, Node::Ruby { ruby, .. }
+            if ruby.iter().map(Node::to_text).collect::<String>() == ruby_text)
+    })
+}
+
 /// 前方のノードから対象テキストを探す
-fn find_target_in_preceding(nodes: &[Node], target: &str) -> Option<(usize, usize, SplitInfo)> {
+fn find_target_in_preceding(
+    nodes: &[Spanned<Node>],
+    target: &str,
+) -> Option<(usize, usize, SplitInfo)> {
     // まず単一ノード内で探す（後ろから）
     for (i, node) in nodes.iter().enumerate().rev() {
-        match node {
+        match &node.value {
             Node::Text(text) => {
                 if text == target {
                     return Some((i, i, SplitInfo::ExactMatch));
@@ -294,8 +335,9 @@ fn find_target_in_preceding(nodes: &[Node], target: &str) -> Option<(usize, usiz
             | Node::Keigakomi { .. }
             | Node::Yokogumi { .. }
             | Node::Caption { .. }
-            | Node::Midashi { .. } => {
-                let content = extract_plain_text(node);
+            | Node::Midashi { .. }
+            | Node::Correction { .. } => {
+                let content = extract_plain_text(&node.value);
                 if content == target {
                     // ノード全体をラップ対象として返す
                     return Some((
@@ -319,7 +361,7 @@ fn find_target_in_preceding(nodes: &[Node], target: &str) -> Option<(usize, usiz
 
         // 末尾から連結していく
         for start_idx in (0..=end_idx).rev() {
-            let text = extract_plain_text(&nodes[start_idx]);
+            let text = extract_plain_text(&nodes[start_idx].value);
             combined = format!("{}{}", text, combined);
 
             // 対象テキストが含まれていれば
@@ -352,7 +394,7 @@ fn find_target_in_preceding(nodes: &[Node], target: &str) -> Option<(usize, usiz
 }
 
 /// ノードからプレーンテキストを抽出
-fn extract_plain_text(node: &Node) -> String {
+pub(crate) fn extract_plain_text(node: &Node) -> String {
     match node {
         Node::Text(text) => text.clone(),
         Node::Ruby { children, .. } => {
@@ -366,6 +408,7 @@ fn extract_plain_text(node: &Node) -> String {
         Node::Yokogumi { children } => children.iter().map(extract_plain_text).collect(),
         Node::Caption { children } => children.iter().map(extract_plain_text).collect(),
         Node::Midashi { children, .. } => children.iter().map(extract_plain_text).collect(),
+        Node::Correction { target, .. } => target.iter().map(extract_plain_text).collect(),
         _ => String::new(),
     }
 }
@@ -388,6 +431,11 @@ enum ResolvedKind {
     AnnotationRuby { annotation: String },
     /// 傍記（ルビとして表示）
     SideNote { annotation: String },
+    /// 校正注記
+    Correction {
+        original: Option<String>,
+        kind: CorrectionKind,
+    },
 }
 
 impl ResolvedKind {
@@ -407,6 +455,23 @@ impl ResolvedKind {
             });
         }
 
+        // 校正注記（底本では「原文」、ママ）
+        if spec == "ママ" {
+            return Some(ResolvedKind::Correction {
+                original: None,
+                kind: CorrectionKind::Sic,
+            });
+        }
+        if let Some(original) = spec
+            .strip_prefix("底本では「")
+            .and_then(|s| s.strip_suffix('」'))
+        {
+            return Some(ResolvedKind::Correction {
+                original: Some(original.to_string()),
+                kind: CorrectionKind::Corrected,
+            });
+        }
+
         // スタイル
         if let Some(style_type) = StyleType::from_command(spec) {
             return Some(ResolvedKind::Style(style_type));
@@ -460,12 +525,16 @@ impl ResolvedKind {
                 ruby: vec![Node::text(annotation)],
                 direction: RubyDirection::Right,
             },
+            ResolvedKind::Correction { original, kind } => Node::Correction {
+                target: children,
+                original: original.clone(),
+                kind: *kind,
+            },
             ResolvedKind::SideNote { annotation } => {
                 // 親文字の文字数を数える
                 let char_count: usize = children.iter().map(|n| n.to_text().chars().count()).sum();
                 // 注記を文字数分繰り返し、&nbsp;で区切る
-                let repeated: String = std::iter::repeat(annotation.as_str())
-                    .take(char_count.max(1))
+                let repeated: String = std::iter::repeat_n(annotation.as_str(), char_count.max(1))
                     .collect::<Vec<_>>()
                     .join("\u{00a0}"); // non-breaking space
                 Node::Ruby {
@@ -572,6 +641,13 @@ mod tests {
     use super::*;
     use crate::node::RubyDirection;
 
+    fn spanned(nodes: Vec<Node>) -> Vec<Spanned<Node>> {
+        nodes
+            .into_iter()
+            .map(|n| Spanned::new(n, Span::default()))
+            .collect()
+    }
+
     #[test]
     fn test_resolve_inline_ruby() {
         let mut nodes = vec![
@@ -619,14 +695,14 @@ mod tests {
 
     #[test]
     fn test_resolve_style_reference() {
-        let mut nodes = vec![
+        let mut nodes = spanned(vec![
             Node::text("重要なこと"),
             Node::UnresolvedReference {
                 target: "重要".to_string(),
                 spec: "sesame_dot".to_string(),
                 connector: "に".to_string(),
             },
-        ];
+        ]);
 
         resolve_style_references(&mut nodes);
 
@@ -636,11 +712,11 @@ mod tests {
 
     #[test]
     fn test_find_target_exact() {
-        let nodes = vec![
+        let nodes = spanned(vec![
             Node::text("前の文"),
             Node::text("重要"),
             Node::text("後の文"),
-        ];
+        ]);
 
         let result = find_target_in_preceding(&nodes, "重要");
         assert!(result.is_some());
@@ -651,7 +727,7 @@ mod tests {
 
     #[test]
     fn test_find_target_split() {
-        let nodes = vec![Node::text("これは重要なことだ")];
+        let nodes = spanned(vec![Node::text("これは重要なことだ")]);
 
         let result = find_target_in_preceding(&nodes, "重要");
         assert!(result.is_some());
@@ -664,4 +740,119 @@ mod tests {
             panic!("Expected Split");
         }
     }
+
+    #[test]
+    fn test_resolve_correction() {
+        let mut nodes = vec![
+            Node::text("灯がともる"),
+            Node::UnresolvedReference {
+                target: "灯".to_string(),
+                spec: "底本では「燈」".to_string(),
+                connector: "は".to_string(),
+            },
+        ];
+
+        resolve_references(&mut nodes);
+
+        assert_eq!(
+            nodes,
+            vec![
+                Node::Correction {
+                    target: vec![Node::text("灯")],
+                    original: Some("燈".to_string()),
+                    kind: CorrectionKind::Corrected,
+                },
+                Node::text("がともる"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_ruby_sic() {
+        let ruby = Node::Ruby {
+            children: vec![Node::text("漢字")],
+            ruby: vec![Node::text("かじ")],
+            direction: RubyDirection::Right,
+        };
+        let mut nodes = vec![
+            ruby.clone(),
+            Node::text("を書く"),
+            Node::UnresolvedReference {
+                target: "かじ".to_string(),
+                spec: RUBY_SIC_SPEC.to_string(),
+                connector: "は".to_string(),
+            },
+        ];
+
+        resolve_references(&mut nodes);
+
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(
+            nodes[0],
+            Node::Correction {
+                target: vec![ruby],
+                original: None,
+                kind: CorrectionKind::RubySic,
+            }
+        );
+
+        // ルビが見つからなければ元の注記に戻す
+        let mut nodes = vec![
+            Node::text("漢字"),
+            Node::UnresolvedReference {
+                target: "かじ".to_string(),
+                spec: RUBY_SIC_SPEC.to_string(),
+                connector: "は".to_string(),
+            },
+        ];
+        resolve_references(&mut nodes);
+        assert_eq!(nodes[1], Node::Note("ルビの「かじ」はママ".to_string()));
+    }
+
+    #[test]
+    fn test_resolve_inline_ruby_with_spans() {
+        // 私の東京《とうきょう》
+        let mut nodes = vec![
+            Spanned::new(Node::text("私の東京"), Span::new(0, 12)),
+            Spanned::new(
+                Node::Ruby {
+                    children: vec![],
+                    ruby: vec![Node::text("とうきょう")],
+                    direction: RubyDirection::Right,
+                },
+                Span::new(12, 33),
+            ),
+        ];
+
+        resolve_inline_ruby_with_spans(&mut nodes);
+
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].span, Span::new(0, 6));
+        assert!(matches!(&nodes[1].value, Node::Ruby { .. }));
+        assert_eq!(nodes[1].span, Span::new(6, 33));
+    }
+
+    #[test]
+    fn test_resolve_style_reference_with_spans() {
+        // これは重要なことだ［＃「重要」に傍点］
+        let mut nodes = vec![
+            Spanned::new(Node::text("これは重要なことだ"), Span::new(0, 27)),
+            Spanned::new(
+                Node::UnresolvedReference {
+                    target: "重要".to_string(),
+                    spec: "傍点".to_string(),
+                    connector: "に".to_string(),
+                },
+                Span::new(27, 57),
+            ),
+        ];
+
+        resolve_references_with_spans(&mut nodes);
+
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(nodes[0].span, Span::new(0, 9));
+        assert!(matches!(&nodes[1].value, Node::Style { .. }));
+        assert_eq!(nodes[1].span, Span::new(9, 15));
+        assert_eq!(nodes[2].span, Span::new(15, 27));
+    }
 }
@@ -5,6 +5,7 @@
 
 use crate::char_type::{CharType, CharTypeExt};
 use crate::node::Node;
+use crate::span::{Span, Spanned};
 
 /// ルビ親文字の抽出結果
 #[derive(Debug, Clone, PartialEq)]
@@ -72,13 +73,32 @@ pub fn extract_ruby_base(text: &str) -> Option<RubyBaseResult> {
 /// ノード列の最後から、親文字になりうるノードを抽出します。
 /// Textノードの場合は文字種別で分割し、Gaijiノードは漢字として扱います。
 pub fn extract_ruby_base_from_nodes(nodes: &[Node]) -> Option<(Vec<Node>, Vec<Node>)> {
+    let spanned: Vec<Spanned<Node>> = nodes
+        .iter()
+        .map(|n| Spanned::new(n.clone(), Span::default()))
+        .collect();
+    let (remaining, base) = extract_ruby_base_from_spanned_nodes(&spanned)?;
+    Some((
+        remaining.into_iter().map(|n| n.value).collect(),
+        base.into_iter().map(|n| n.value).collect(),
+    ))
+}
+
+/// 位置情報付きノード列の分割結果（残りのノード列, 親文字のノード列）
+pub type SpannedRubyBase = (Vec<Spanned<Node>>, Vec<Spanned<Node>>);
+
+/// 位置情報付きノード列からルビ親文字を抽出
+///
+/// [`extract_ruby_base_from_nodes`] と同じ規則で抽出し、
+/// 分割されたTextノードには分割後の範囲を設定します。
+pub fn extract_ruby_base_from_spanned_nodes(nodes: &[Spanned<Node>]) -> Option<SpannedRubyBase> {
     if nodes.is_empty() {
         return None;
     }
 
     // 最後のノードから文字種別を取得
     let last_node = nodes.last()?;
-    let last_char_type = last_node.last_char_type()?;
+    let last_char_type = last_node.value.last_char_type()?;
 
     if !last_char_type.can_be_ruby_base() {
         return None;
@@ -95,16 +115,19 @@ pub fn extract_ruby_base_from_nodes(nodes: &[Node]) -> Option<(Vec<Node>, Vec<No
             continue;
         }
 
-        match node {
+        match &node.value {
             Node::Text(text) => {
                 // テキストノードは文字種別で分割
                 if let Some(result) = extract_ruby_base(text) {
                     if result.char_type == last_char_type {
+                        let split = result.remaining.len();
                         if !result.base.is_empty() {
-                            base_nodes.push(Node::Text(result.base));
+                            let span = node.span.sub_span(text, split..text.len());
+                            base_nodes.push(Spanned::new(Node::Text(result.base), span));
                         }
                         if !result.remaining.is_empty() {
-                            remaining_nodes.push(Node::Text(result.remaining));
+                            let span = node.span.sub_span(text, 0..split);
+                            remaining_nodes.push(Spanned::new(Node::Text(result.remaining), span));
                             found_different_type = true;
                         }
                     } else {
@@ -260,4 +283,12 @@ mod tests {
         assert!(remaining.is_empty());
         assert_eq!(base.len(), 2);
     }
+
+    #[test]
+    fn test_extract_ruby_base_from_spanned_nodes() {
+        let nodes = vec![Spanned::new(Node::text("私の東京"), Span::new(0, 12))];
+        let (remaining, base) = extract_ruby_base_from_spanned_nodes(&nodes).unwrap();
+        assert_eq!(remaining[0].span, Span::new(0, 6));
+        assert_eq!(base[0].span, Span::new(6, 12));
+    }
 }
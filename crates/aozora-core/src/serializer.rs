@@ -0,0 +1,770 @@
+//! 青空文庫形式への書き出し
+//!
+//! ノード列を青空文庫形式のテキストに変換します（[`parse`](crate::parser::parse) の逆変換）。
+//! 整形式の入力であれば、パースして書き出すと元のテキストに戻ります。
+//!
+//! 出力は正規形です：
+//!
+//! - ルビの `｜` は親文字の範囲が曖昧な場合のみ付ける
+//! - 装飾・見出しなどは `［＃「対象」に傍点］` 形式の前方参照で書く
+//! - 注記内の数字は全角
+//! - 本文中の `《` や `｜` などのデリミタは `※［＃...］` の外字注記で書く
+//!
+//! 注記ルビ（「対象」に「注記」の注記）や注記付き範囲、傍記はルビとして解析されるため、
+//! `対象《注記》` 形式で書き出されます。
+
+use crate::accent::{accent_notation, parse_accent, AccentPart};
+use crate::delimiters::{
+    ACCENT_BEGIN, ACCENT_END, COMMAND_BEGIN, GAIJI_MARK, IGETA, RUBY_BEGIN, RUBY_END, RUBY_PREFIX,
+};
+use crate::node::{BlockParams, BlockType, FontSizeType, Node, RubyDirection, StyleType};
+use crate::parser::reference_resolver::{extract_plain_text, reference_command};
+use crate::parser::ruby_parser::extract_ruby_base_from_nodes;
+
+/// 1行分のノード列を青空文庫形式に変換
+///
+/// # Examples
+///
+/// ```
+/// use aozora_core::parser::parse;
+/// use aozora_core::serializer::serialize;
+/// use aozora_core::tokenizer::tokenize;
+///
+/// let source = "私の東京《とうきょう》は［＃「は」に傍点］";
+/// assert_eq!(serialize(&parse(&tokenize(source))), source);
+/// ```
+pub fn serialize(nodes: &[Node]) -> String {
+    Serializer::new().serialize_line(nodes)
+}
+
+/// 複数行のノード列を青空文庫形式に変換（行は `\n` で連結）
+///
+/// 行をまたぐブロック（`ここから〜`/`ここで〜終わり`）の対応を追跡します。
+pub fn serialize_lines<I>(lines: I) -> String
+where
+    I: IntoIterator,
+    I::Item: AsRef<[Node]>,
+{
+    let mut serializer = Serializer::new();
+    lines
+        .into_iter()
+        .map(|line| serializer.serialize_line(line.as_ref()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 行ごとにノード列を青空文庫形式に変換するシリアライザ
+///
+/// 開いているブロックを行をまたいで追跡し、
+/// ブロック終了注記を開始注記に合わせた形（`ここで〜終わり` または `〜終わり`）で書き出します。
+#[derive(Debug, Default)]
+pub struct Serializer {
+    /// 開いているブロック（種類と開始時のパラメータ）
+    open_blocks: Vec<(BlockType, BlockParams)>,
+}
+
+impl Serializer {
+    /// 新しいシリアライザを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 1行分のノード列を変換
+    pub fn serialize_line(&mut self, nodes: &[Node]) -> String {
+        let mut writer = LineWriter::new(&mut self.open_blocks, false);
+        writer.write_nodes(nodes);
+        let output = writer.finish();
+        // 行内ブロックは行末で閉じる
+        self.open_blocks.retain(|(_, params)| params.is_block);
+        output
+    }
+}
+
+/// 1行分の書き出し状態
+struct LineWriter<'a> {
+    /// 出力
+    out: String,
+    /// 直前に書き出した、ルビ親文字になりうるノード列
+    ///
+    /// 再パース時に `《` の前から親文字として取り込まれる範囲を判定するのに使います。
+    trail: Vec<Node>,
+    /// 開いているブロック
+    open_blocks: &'a mut Vec<(BlockType, BlockParams)>,
+    /// ルビ文字列の内側か（`》` もエスケープする）
+    in_ruby: bool,
+    /// テキスト中の `｜` の出力位置
+    prefixes: Vec<usize>,
+}
+
+impl<'a> LineWriter<'a> {
+    fn new(open_blocks: &'a mut Vec<(BlockType, BlockParams)>, in_ruby: bool) -> Self {
+        Self {
+            out: String::new(),
+            trail: Vec::new(),
+            open_blocks,
+            in_ruby,
+            prefixes: Vec::new(),
+        }
+    }
+
+    /// 出力を確定する
+    ///
+    /// テキスト中の `｜` は、後ろに `《` があると明示ルビと解釈されるため外字注記にします。
+    fn finish(mut self) -> String {
+        for &pos in self.prefixes.iter().rev() {
+            let end = pos + RUBY_PREFIX.len_utf8();
+            if self.out[end..].contains(RUBY_BEGIN) {
+                self.out
+                    .replace_range(pos..end, &format!("※［＃{ESCAPE_RUBY_PREFIX}］"));
+            }
+        }
+        self.out
+    }
+
+    /// ルビの内側など、独立した文脈でノード列を書き出す
+    fn nested(&mut self, nodes: &[Node], in_ruby: bool) -> String {
+        let mut writer = LineWriter::new(self.open_blocks, in_ruby);
+        writer.write_nodes(nodes);
+        writer.finish()
+    }
+
+    fn write_nodes(&mut self, nodes: &[Node]) {
+        let mut i = 0;
+        while i < nodes.len() {
+            if let Some(end) = accent_group_end(nodes, i) {
+                self.write_accent_group(&nodes[i..end]);
+                i = end;
+            } else {
+                self.write_node(&nodes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    fn write_node(&mut self, node: &Node) {
+        match node {
+            Node::Text(text) => {
+                self.write_text(text);
+                self.trail.push(node.clone());
+            }
+
+            Node::Ruby {
+                children,
+                ruby,
+                direction: RubyDirection::Right,
+            } => {
+                let ruby = self.nested(ruby, true);
+                if self.is_implicit_ruby_base(children) {
+                    self.write_nodes(children);
+                } else {
+                    let base = self.nested(children, false);
+                    self.out.push(RUBY_PREFIX);
+                    self.out.push_str(&base);
+                }
+                self.out.push(RUBY_BEGIN);
+                self.out.push_str(&ruby);
+                self.out.push(RUBY_END);
+                self.trail.clear();
+            }
+
+            Node::Ruby {
+                children,
+                ruby,
+                direction: RubyDirection::Left,
+            } => {
+                self.write_nodes(children);
+                let ruby: String = ruby.iter().map(Node::to_text).collect();
+                self.write_command(&format!(
+                    "「{}」の左に「{ruby}」のルビ",
+                    plain_text(children)
+                ));
+            }
+
+            Node::Style {
+                children,
+                style_type,
+                ..
+            } => {
+                self.write_nodes(children);
+                let (connector, spec) = style_reference(*style_type);
+                self.write_command(&format!("「{}」{connector}{spec}", plain_text(children)));
+            }
+
+            Node::Midashi {
+                children,
+                level,
+                style,
+            } => {
+                self.write_nodes(children);
+                self.write_command(&format!(
+                    "「{}」は{}{}",
+                    plain_text(children),
+                    style.command_prefix(),
+                    level.command_name()
+                ));
+            }
+
+            Node::FontSize {
+                children,
+                size_type,
+                level,
+            } => {
+                self.write_nodes(children);
+                self.write_command(&format!(
+                    "「{}」は{}段階{}",
+                    plain_text(children),
+                    zenkaku_number(*level),
+                    font_size_name(*size_type)
+                ));
+            }
+
+            Node::Correction {
+                target,
+                original,
+                kind,
+            } => {
+                self.write_nodes(target);
+                self.write_command(&kind.command(target, original.as_deref()));
+            }
+
+            Node::Tcy { children } => self.write_inline_reference(children, "縦中横"),
+            Node::Keigakomi { children } => self.write_inline_reference(children, "罫囲み"),
+            Node::Yokogumi { children } => self.write_inline_reference(children, "横組み"),
+            Node::Caption { children } => self.write_inline_reference(children, "キャプション"),
+
+            Node::Warigaki { upper, lower } => {
+                self.write_command("割り注");
+                self.write_nodes(upper);
+                self.write_command("改行");
+                self.write_nodes(lower);
+                self.write_command("割り注終わり");
+            }
+
+            Node::Gaiji { description, .. } => {
+                self.write_gaiji(description);
+                self.trail.push(node.clone());
+            }
+
+            Node::Accent { unicode, name, .. } => {
+                // 分解記法に戻せないものは文字のまま書き出す
+                self.out.push_str(unicode.as_deref().unwrap_or(name));
+                self.trail.push(node.clone());
+            }
+
+            Node::Img {
+                filename,
+                alt,
+                width,
+                height,
+                ..
+            } => {
+                let size = match (width, height) {
+                    (Some(w), Some(h)) => format!("、横{w}×縦{h}"),
+                    (Some(w), None) => format!("、横{w}×"),
+                    (None, Some(h)) => format!("、縦{h}"),
+                    (None, None) => String::new(),
+                };
+                self.write_command(&format!("{alt}（{filename}{size}）入る"));
+            }
+
+            Node::Kaeriten(s) => self.write_command(s),
+
+            Node::Okurigana(s) => self.write_command(&format!("（{s}）")),
+
+            Node::BlockStart { block_type, params } => {
+                self.write_command(&block_start_command(*block_type, params));
+                self.open_blocks.push((*block_type, params.clone()));
+            }
+
+            Node::BlockEnd { block_type, params } => {
+                let start = self.close_block(*block_type, params);
+                self.write_command(&block_end_command(*block_type, start.as_ref(), params));
+            }
+
+            Node::Break { kind } => self.write_command(kind.command_name()),
+
+            Node::Note(text) => self.write_command(text),
+
+            Node::AnnotationEnd {
+                prefix,
+                content,
+                suffix,
+            } => {
+                let content = self.nested(content, false);
+                self.write_command(&format!("{prefix}{content}{suffix}"));
+            }
+
+            Node::UnresolvedReference {
+                target,
+                spec,
+                connector,
+            } => {
+                self.write_command(&reference_command(target, connector, spec));
+            }
+
+            Node::DakutenKatakana { num } => {
+                let kana = match num.as_str() {
+                    "2" => "ワ",
+                    "3" => "ヰ",
+                    "4" => "ヱ",
+                    "5" => "ヲ",
+                    _ => return,
+                };
+                self.write_gaiji(&format!("濁点付き片仮名{kana}、1-7-8{num}"));
+                self.trail.push(node.clone());
+            }
+        }
+    }
+
+    /// 縦中横・罫囲みなどの前方参照を書き出す
+    fn write_inline_reference(&mut self, children: &[Node], spec: &str) {
+        self.write_nodes(children);
+        self.write_command(&format!("「{}」は{spec}", plain_text(children)));
+    }
+
+    /// アクセント分解のまとまりを `〔...〕` で書き出す
+    fn write_accent_group(&mut self, nodes: &[Node]) {
+        self.out.push(ACCENT_BEGIN);
+        for node in nodes {
+            match node {
+                Node::Text(text) => self.out.push_str(text),
+                Node::Accent { code, .. } => {
+                    self.out.push_str(accent_notation(code).unwrap_or_default());
+                }
+                _ => {}
+            }
+        }
+        self.out.push(ACCENT_END);
+        self.trail.extend_from_slice(nodes);
+    }
+
+    /// 注記 `［＃...］` を書き出す
+    fn write_command(&mut self, content: &str) {
+        // 直前の ※ と合わせて外字注記と解釈されないようにする
+        if self.out.ends_with(GAIJI_MARK) {
+            self.out.pop();
+            self.write_gaiji(ESCAPE_GAIJI_MARK);
+        }
+        self.out.push(COMMAND_BEGIN);
+        self.out.push(IGETA);
+        self.out.push_str(content);
+        self.out.push('］');
+        self.trail.clear();
+    }
+
+    /// 外字注記 `※［＃...］` を書き出す
+    fn write_gaiji(&mut self, description: &str) {
+        self.out.push(GAIJI_MARK);
+        self.out.push(COMMAND_BEGIN);
+        self.out.push(IGETA);
+        self.out.push_str(description);
+        self.out.push('］');
+    }
+
+    /// テキストを書き出す（デリミタとして解釈される文字は外字注記にする）
+    fn write_text(&mut self, text: &str) {
+        let chars: Vec<char> = text.chars().collect();
+        for (i, &c) in chars.iter().enumerate() {
+            let next = chars.get(i + 1).copied();
+            let escape = match c {
+                RUBY_BEGIN => Some(ESCAPE_RUBY_BEGIN),
+                RUBY_END if self.in_ruby => Some(ESCAPE_RUBY_END),
+                RUBY_PREFIX => {
+                    self.prefixes.push(self.out.len());
+                    None
+                }
+                COMMAND_BEGIN if next == Some(IGETA) => Some(ESCAPE_COMMAND_BEGIN),
+                GAIJI_MARK if next == Some(COMMAND_BEGIN) && chars.get(i + 2) == Some(&IGETA) => {
+                    Some(ESCAPE_GAIJI_MARK)
+                }
+                ACCENT_BEGIN if opens_accent(&chars[i + 1..]) => Some(ESCAPE_ACCENT_BEGIN),
+                // 直前のノード末尾の ［ と合わせて注記と解釈されないようにする
+                IGETA if i == 0 && self.out.ends_with(COMMAND_BEGIN) => {
+                    self.out.pop();
+                    self.write_gaiji(ESCAPE_COMMAND_BEGIN);
+                    None
+                }
+                _ => None,
+            };
+            match escape {
+                Some(description) => self.write_gaiji(description),
+                None => self.out.push(c),
+            }
+        }
+    }
+
+    /// ルビの親文字を `｜` なしで書けるか（再パースで同じ親文字が取り出されるか）
+    fn is_implicit_ruby_base(&self, children: &[Node]) -> bool {
+        if children.is_empty() {
+            // 親文字なしのルビは直前に親文字になりうる文字がない場合のみ
+            return extract_ruby_base_from_nodes(&self.trail).is_none();
+        }
+        let mut probe = self.trail.clone();
+        probe.extend_from_slice(children);
+        extract_ruby_base_from_nodes(&probe).is_some_and(|(_, base)| base == children)
+    }
+
+    /// ブロック終了に対応する開始を探して閉じる
+    fn close_block(&mut self, block_type: BlockType, params: &BlockParams) -> Option<BlockParams> {
+        let matches = |(open, open_params): &(BlockType, BlockParams), block_type| {
+            *open == block_type
+                && (block_type != BlockType::Style || open_params.style_type == params.style_type)
+        };
+        let index = self
+            .open_blocks
+            .iter()
+            .rposition(|open| matches(open, block_type))
+            .or_else(|| {
+                // 「ここで字下げ終わり」はぶら下げも閉じる
+                (block_type == BlockType::Jisage)
+                    .then(|| {
+                        self.open_blocks
+                            .iter()
+                            .rposition(|open| matches(open, BlockType::Burasage))
+                    })
+                    .flatten()
+            })?;
+        Some(self.open_blocks.remove(index).1)
+    }
+}
+
+/// デリミタを表す外字注記
+const ESCAPE_RUBY_BEGIN: &str = "始め二重山括弧、1-1-52";
+const ESCAPE_RUBY_END: &str = "終わり二重山括弧、1-1-53";
+const ESCAPE_RUBY_PREFIX: &str = "縦線、1-1-35";
+const ESCAPE_COMMAND_BEGIN: &str = "始め角括弧、1-1-46";
+const ESCAPE_GAIJI_MARK: &str = "米印、1-2-8";
+const ESCAPE_ACCENT_BEGIN: &str = "始め亀甲括弧、1-1-44";
+
+/// `〔` の後がアクセント分解として解釈されるか
+fn opens_accent(rest: &[char]) -> bool {
+    rest.iter()
+        .position(|&c| c == ACCENT_END)
+        .is_some_and(|end| {
+            rest[..end]
+                .iter()
+                .any(|&c| crate::accent::is_accent_mark(c))
+        })
+}
+
+/// `〔...〕` にまとめて書き出すノード範囲の終端を返す
+///
+/// アクセント文字と、その前後のアクセント分解しても変わらない半角テキストをまとめます。
+/// 次のまとまりの先頭になるテキストは含めません。
+fn accent_group_end(nodes: &[Node], start: usize) -> Option<usize> {
+    let is_accent = |i: usize| matches!(nodes.get(i), Some(Node::Accent { code, .. }) if accent_notation(code).is_some());
+    let is_plain = |i: usize| matches!(nodes.get(i), Some(Node::Text(s)) if is_accent_plain(s));
+
+    let first = if is_accent(start) {
+        start
+    } else if is_plain(start) && is_accent(start + 1) {
+        start + 1
+    } else {
+        return None;
+    };
+
+    let mut end = first + 1;
+    loop {
+        if is_accent(end) {
+            end += 1;
+        } else if is_plain(end) {
+            if is_accent(end + 1) {
+                end += 1;
+            } else if !(is_plain(end + 1) && is_accent(end + 2)) {
+                end += 1;
+                break;
+            } else {
+                break;
+            }
+        } else {
+            break;
+        }
+    }
+    Some(end)
+}
+
+/// `〔...〕` の内側に置いても同じテキストとして解析されるか
+fn is_accent_plain(text: &str) -> bool {
+    text.chars().all(|c| c.is_ascii() && !c.is_ascii_control())
+        && matches!(parse_accent(text).as_slice(), [AccentPart::Text(s)] if s == text)
+}
+
+/// 前方参照の対象テキスト（参照解決で照合されるテキスト）
+fn plain_text(nodes: &[Node]) -> String {
+    nodes.iter().map(extract_plain_text).collect()
+}
+
+/// 装飾の前方参照の接続詞と装飾名
+fn style_reference(style_type: StyleType) -> (&'static str, &'static str) {
+    let name = style_type.command_name();
+    if let Some(base) = name.strip_prefix("左に") {
+        return ("の左に", base);
+    }
+    match style_type {
+        StyleType::Bold | StyleType::Italic | StyleType::Subscript | StyleType::Superscript => {
+            ("は", name)
+        }
+        _ => ("に", name),
+    }
+}
+
+/// フォントサイズの注記名
+fn font_size_name(size_type: FontSizeType) -> &'static str {
+    match size_type {
+        FontSizeType::Dai => "大きな文字",
+        FontSizeType::Sho => "小さな文字",
+    }
+}
+
+/// ブロック開始注記の内容
+fn block_start_command(block_type: BlockType, params: &BlockParams) -> String {
+    let width = params.width.map(zenkaku_number).unwrap_or_default();
+    let body = match block_type {
+        BlockType::Jisage => format!("{width}字下げ"),
+        BlockType::Burasage => {
+            let wrap = params.wrap_width.map(zenkaku_number).unwrap_or_default();
+            if params.width == Some(0) {
+                format!("改行天付き、折り返して{wrap}字下げ")
+            } else {
+                format!("{width}字下げ、折り返して{wrap}字下げ")
+            }
+        }
+        BlockType::Chitsuki => match params.width {
+            Some(w) if w > 0 => format!("地から{}字上げ", zenkaku_number(w)),
+            _ => "地付き".to_string(),
+        },
+        BlockType::Jizume => format!("{width}字詰め"),
+        BlockType::Midashi => format!(
+            "{}{}",
+            params.midashi_style.map_or("", |s| s.command_prefix()),
+            params.level.map_or("見出し", |l| l.command_name())
+        ),
+        BlockType::FontDai | BlockType::FontSho => format!(
+            "{}段階{}",
+            zenkaku_number(params.font_size.unwrap_or(1)),
+            block_name(block_type, params)
+        ),
+        _ => block_name(block_type, params),
+    };
+    if params.is_block {
+        format!("ここから{body}")
+    } else {
+        body
+    }
+}
+
+/// ブロック終了注記の内容
+fn block_end_command(
+    block_type: BlockType,
+    start: Option<&BlockParams>,
+    params: &BlockParams,
+) -> String {
+    let body = match block_type {
+        BlockType::Chitsuki if start.and_then(|p| p.width).is_some_and(|w| w > 0) => {
+            "字上げ".to_string()
+        }
+        BlockType::Midashi => start.map_or_else(
+            || "見出し".to_string(),
+            |p| {
+                block_start_command(
+                    block_type,
+                    &BlockParams {
+                        is_block: false,
+                        ..p.clone()
+                    },
+                )
+            },
+        ),
+        BlockType::Style if params.style_type.is_none() => {
+            block_name(block_type, start.unwrap_or(params))
+        }
+        _ => block_name(block_type, params),
+    };
+    // 開始が見つからない場合は行をまたぐ種類のみ「ここで」を付ける
+    let is_block = start.map_or(
+        matches!(
+            block_type,
+            BlockType::Jisage
+                | BlockType::Chitsuki
+                | BlockType::Jizume
+                | BlockType::Burasage
+                | BlockType::Futoji
+                | BlockType::Shatai
+        ),
+        |p| p.is_block,
+    );
+    if is_block {
+        format!("ここで{body}終わり")
+    } else {
+        format!("{body}終わり")
+    }
+}
+
+/// 開始・終了で共通のブロック名
+fn block_name(block_type: BlockType, params: &BlockParams) -> String {
+    let annotation = params.annotation.as_deref().unwrap_or_default();
+    match block_type {
+        BlockType::Jisage | BlockType::Burasage => "字下げ".to_string(),
+        BlockType::Chitsuki => "地付き".to_string(),
+        BlockType::Jizume => "字詰め".to_string(),
+        BlockType::Keigakomi => "罫囲み".to_string(),
+        BlockType::Midashi => "見出し".to_string(),
+        BlockType::Yokogumi => "横組み".to_string(),
+        BlockType::Futoji => "太字".to_string(),
+        BlockType::Shatai => "斜体".to_string(),
+        BlockType::FontDai => "大きな文字".to_string(),
+        BlockType::FontSho => "小さな文字".to_string(),
+        BlockType::Tcy => "縦中横".to_string(),
+        BlockType::Caption => "キャプション".to_string(),
+        BlockType::Warigaki => "割り注".to_string(),
+        BlockType::Style => params
+            .style_type
+            .map_or("", |s| s.command_name())
+            .to_string(),
+        BlockType::AnnotationRange if params.annotation.is_some() => {
+            format!("「{annotation}」の注記付き")
+        }
+        BlockType::AnnotationRange => "注記付き".to_string(),
+        BlockType::LeftAnnotationRange if params.annotation.is_some() => {
+            format!("左に「{annotation}」の注記付き")
+        }
+        BlockType::LeftAnnotationRange => "左に注記付き".to_string(),
+    }
+}
+
+/// 数値を全角数字に変換
+fn zenkaku_number(n: u32) -> String {
+    n.to_string()
+        .chars()
+        .map(|c| char::from_u32(c as u32 - '0' as u32 + '０' as u32).unwrap_or(c))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use crate::tokenizer::tokenize;
+
+    /// パースして書き出すと元に戻ることを確認
+    fn assert_roundtrip(source: &str) {
+        assert_eq!(serialize(&parse(&tokenize(source))), source);
+    }
+
+    #[test]
+    fn test_roundtrip_ruby() {
+        assert_roundtrip("吾輩《わがはい》は猫である");
+        assert_roundtrip("私の東京《とうきょう》");
+        assert_roundtrip("日本｜東京《とうきょう》");
+        assert_roundtrip("｜東京タワー《とうきょうたわー》");
+        assert_roundtrip("※［＃「てへん＋劣」、第3水準1-84-77］《むし》る");
+        assert_roundtrip("《ルビのみ》");
+    }
+
+    #[test]
+    fn test_roundtrip_references() {
+        assert_roundtrip("猫である［＃「である」に傍点］");
+        assert_roundtrip("重要［＃「重要」の左に傍線］なこと");
+        assert_roundtrip("太い［＃「太い」は太字］");
+        assert_roundtrip("第一章［＃「第一章」は大見出し］");
+        assert_roundtrip("見出し［＃「見出し」は窓中見出し］");
+        assert_roundtrip("大きい［＃「大きい」は２段階大きな文字］");
+        assert_roundtrip("12［＃「12」は縦中横］月");
+        assert_roundtrip("東京《とうきょう》［＃「東京」に傍点］");
+        assert_roundtrip("灯［＃「灯」は底本では「燈」］がともる");
+        assert_roundtrip("云ふ［＃「云ふ」はママ］");
+        assert_roundtrip("漢字《かじ》［＃ルビの「かじ」はママ］");
+        assert_roundtrip("該当なし［＃ルビの「かじ」はママ］");
+    }
+
+    #[test]
+    fn test_roundtrip_commands() {
+        assert_roundtrip("［＃３字下げ］本文");
+        assert_roundtrip("［＃地付き］本文");
+        assert_roundtrip("本文［＃地から２字上げ］終わり");
+        assert_roundtrip("［＃縦中横］12［＃縦中横終わり］");
+        assert_roundtrip("［＃傍点］強調［＃傍点終わり］");
+        assert_roundtrip("［＃中見出し］第二章［＃中見出し終わり］");
+        assert_roundtrip("［＃１段階小さな文字］注［＃小さな文字終わり］");
+        assert_roundtrip("（［＃割り注］注記［＃割り注終わり］）");
+        assert_roundtrip("学［＃レ］而時習［＃（フ）］");
+        assert_roundtrip("［＃石鏃二つの図（fig42154_01.png、横321×縦123）入る］");
+        assert_roundtrip("※［＃「丸印」、U+25CB］と不明［＃改ページ］");
+        assert_roundtrip("〔Ce'sar〕は〔cafe'〕と〔na:ive〕");
+    }
+
+    #[test]
+    fn test_roundtrip_blocks() {
+        let source = "［＃ここから２字下げ］\n本文\n［＃ここで字下げ終わり］\n\
+                      ［＃ここから改行天付き、折り返して１字下げ］\n項目\n［＃ここで字下げ終わり］\n\
+                      ［＃ここから罫囲み］\n囲み\n［＃ここで罫囲み終わり］\n\
+                      ［＃ここから地から３字上げ］\n署名\n［＃ここで字上げ終わり］\n\
+                      ［＃ここから大見出し］\n章\n［＃ここで大見出し終わり］";
+        let lines: Vec<Vec<Node>> = source.lines().map(|l| parse(&tokenize(l))).collect();
+        assert_eq!(serialize_lines(&lines), source);
+    }
+
+    #[test]
+    fn test_omit_ruby_prefix() {
+        let nodes = vec![
+            Node::text("私の"),
+            Node::Ruby {
+                children: vec![Node::text("東京")],
+                ruby: vec![Node::text("とうきょう")],
+                direction: RubyDirection::Right,
+            },
+        ];
+        assert_eq!(serialize(&nodes), "私の東京《とうきょう》");
+
+        let nodes = vec![
+            Node::text("日本"),
+            Node::Ruby {
+                children: vec![Node::text("東京")],
+                ruby: vec![Node::text("とうきょう")],
+                direction: RubyDirection::Right,
+            },
+        ];
+        assert_eq!(serialize(&nodes), "日本｜東京《とうきょう》");
+    }
+
+    #[test]
+    fn test_escape_delimiters() {
+        let nodes = vec![Node::text("《》と［＃と※［＃")];
+        let output = serialize(&nodes);
+        assert_eq!(
+            output,
+            "※［＃始め二重山括弧、1-1-52］》と※［＃始め角括弧、1-1-46］＃と\
+             ※［＃米印、1-2-8］※［＃始め角括弧、1-1-46］＃"
+        );
+        let text: String = parse(&tokenize(&output))
+            .iter()
+            .map(Node::to_text)
+            .collect();
+        assert_eq!(text, "《》と［＃と※［＃");
+
+        // ｜ は後ろにルビがある場合のみ
+        assert_eq!(serialize(&[Node::text("｜：記号")]), "｜：記号");
+        let nodes = vec![
+            Node::text("a｜b"),
+            Node::Ruby {
+                children: vec![Node::text("東京")],
+                ruby: vec![Node::text("とうきょう")],
+                direction: RubyDirection::Right,
+            },
+        ];
+        assert_eq!(serialize(&nodes), "a※［＃縦線、1-1-35］b東京《とうきょう》");
+
+        let nodes = vec![Node::Ruby {
+            children: vec![Node::text("記号")],
+            ruby: vec![Node::text("》")],
+            direction: RubyDirection::Right,
+        }];
+        assert_eq!(serialize(&nodes), "記号《※［＃終わり二重山括弧、1-1-53］》");
+    }
+
+    #[test]
+    fn test_zenkaku_number() {
+        assert_eq!(zenkaku_number(3), "３");
+        assert_eq!(zenkaku_number(10), "１０");
+    }
+}
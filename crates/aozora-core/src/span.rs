@@ -0,0 +1,188 @@
+//! ソース位置情報
+//!
+//! トークンやノードが入力文字列のどの範囲から生成されたかを表す型を定義します。
+
+/// 入力文字列上のバイト範囲（半開区間 `start..end`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    /// 開始バイト位置
+    pub start: usize,
+    /// 終了バイト位置（この位置は含まない）
+    pub end: usize,
+}
+
+impl Span {
+    /// 新しい範囲を作成
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// 範囲のバイト長
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// 範囲が空かどうか
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// 2つの範囲を覆う範囲を返す
+    pub fn merge(self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+
+    /// 範囲を `offset` バイトずらす（行内の位置を文書全体の位置に変換する場合など）
+    pub fn shift(self, offset: usize) -> Span {
+        Span::new(self.start + offset, self.end + offset)
+    }
+
+    /// テキストの部分範囲に対応する範囲を返す
+    ///
+    /// `text` がこの範囲のソースと1対1に対応する場合（バイト長が一致する場合）のみ
+    /// 部分範囲を計算し、そうでなければ（外字変換後のテキストなど）範囲全体を返します。
+    pub fn sub_span(self, text: &str, range: std::ops::Range<usize>) -> Span {
+        if self.len() == text.len() {
+            Span::new(self.start + range.start, self.start + range.end)
+        } else {
+            self
+        }
+    }
+}
+
+/// 位置情報付きの値
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Spanned<T> {
+    /// 値
+    pub value: T,
+    /// ソース上の範囲
+    pub span: Span,
+}
+
+impl<T> Spanned<T> {
+    /// 位置情報付きの値を作成
+    pub fn new(value: T, span: Span) -> Self {
+        Self { value, span }
+    }
+
+    /// 位置情報を保ったまま値を変換
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Spanned<U> {
+        Spanned {
+            value: f(self.value),
+            span: self.span,
+        }
+    }
+}
+
+/// 行・桁位置（どちらも0始まり、桁は文字単位）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Position {
+    /// 行番号
+    pub line: usize,
+    /// 桁番号（行頭からの文字数）
+    pub column: usize,
+}
+
+/// バイト位置から行・桁位置への変換表
+///
+/// # Examples
+///
+/// ```
+/// use aozora_core::span::{LineIndex, Position, Span};
+///
+/// let text = "一行目\n二行目";
+/// let index = LineIndex::new(text);
+/// let span = Span::new(text.find('二').unwrap(), text.len());
+/// assert_eq!(index.position(span.start), Position { line: 1, column: 0 });
+/// assert_eq!(index.position(span.end), Position { line: 1, column: 3 });
+/// ```
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+    text: &'a str,
+    /// 各行の開始バイト位置
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    /// テキストから変換表を作成
+    pub fn new(text: &'a str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(text.match_indices('\n').map(|(i, _)| i + 1));
+        Self { text, line_starts }
+    }
+
+    /// 行数
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// 指定行の開始バイト位置
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        self.line_starts.get(line).copied()
+    }
+
+    /// バイト位置を行・桁位置に変換
+    ///
+    /// 文字境界でない位置は直前の文字境界として扱います。
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let line_start = self.line_starts[line];
+        let column = self.text[line_start..]
+            .char_indices()
+            .take_while(|(i, _)| line_start + i < offset)
+            .count();
+        Position { line, column }
+    }
+
+    /// 範囲の開始・終了位置を返す
+    pub fn range(&self, span: Span) -> (Position, Position) {
+        (self.position(span.start), self.position(span.end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_span_merge() {
+        let a = Span::new(3, 6);
+        let b = Span::new(9, 12);
+        assert_eq!(a.merge(b), Span::new(3, 12));
+        assert_eq!(b.merge(a), Span::new(3, 12));
+    }
+
+    #[test]
+    fn test_span_sub_span() {
+        let span = Span::new(10, 22);
+        assert_eq!(span.sub_span("私の東京", 6..12), Span::new(16, 22));
+        // テキストとソースの長さが異なる場合は全体を返す
+        assert_eq!(span.sub_span("○", 0..3), span);
+    }
+
+    #[test]
+    fn test_line_index_position() {
+        let text = "abc\nあいう\r\nxyz";
+        let index = LineIndex::new(text);
+        assert_eq!(index.line_count(), 3);
+        assert_eq!(index.position(0), Position { line: 0, column: 0 });
+        assert_eq!(index.position(4), Position { line: 1, column: 0 });
+        assert_eq!(index.position(7), Position { line: 1, column: 1 });
+        let x = text.find('x').unwrap();
+        assert_eq!(index.position(x), Position { line: 2, column: 0 });
+        assert_eq!(index.position(text.len()), Position { line: 2, column: 3 });
+    }
+
+    #[test]
+    fn test_line_index_line_start() {
+        let index = LineIndex::new("a\nb\n");
+        assert_eq!(index.line_start(0), Some(0));
+        assert_eq!(index.line_start(1), Some(2));
+        assert_eq!(index.line_start(2), Some(4));
+        assert_eq!(index.line_start(3), None);
+    }
+}
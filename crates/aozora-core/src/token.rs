@@ -2,6 +2,7 @@
 
 /// 青空文庫形式のトークン
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Token {
     /// 通常テキスト
     Text(String),
@@ -48,6 +49,87 @@ impl Token {
     }
 }
 
+/// 入力文字列を借用する青空文庫形式のトークン
+///
+/// [`Token`] と同じ構造で、テキスト・コマンド内容・外字説明を
+/// 入力文字列のスライスとして保持します（文字列の複製を行いません）。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenRef<'a> {
+    /// 通常テキスト
+    Text(&'a str),
+
+    /// 暗黙ルビ《...》のルビ部分
+    Ruby {
+        /// ルビ内のトークン列
+        children: Vec<TokenRef<'a>>,
+    },
+
+    /// 明示ルビ ｜親文字《ルビ》
+    PrefixedRuby {
+        /// 親文字部分のトークン列
+        base_children: Vec<TokenRef<'a>>,
+        /// ルビ部分のトークン列
+        ruby_children: Vec<TokenRef<'a>>,
+    },
+
+    /// コマンド ［＃...］
+    Command {
+        /// コマンド内容（デリミタ除く）
+        content: &'a str,
+    },
+
+    /// 外字 ※［＃...］
+    Gaiji {
+        /// 外字説明（デリミタ除く）
+        description: &'a str,
+    },
+
+    /// アクセント分解 〔...〕
+    Accent {
+        /// アクセント内のトークン列
+        children: Vec<TokenRef<'a>>,
+    },
+}
+
+impl TokenRef<'_> {
+    /// 所有権を持つ [`Token`] に変換
+    pub fn to_token(&self) -> Token {
+        match self {
+            TokenRef::Text(s) => Token::Text(s.to_string()),
+            TokenRef::Ruby { children } => Token::Ruby {
+                children: to_tokens(children),
+            },
+            TokenRef::PrefixedRuby {
+                base_children,
+                ruby_children,
+            } => Token::PrefixedRuby {
+                base_children: to_tokens(base_children),
+                ruby_children: to_tokens(ruby_children),
+            },
+            TokenRef::Command { content } => Token::Command {
+                content: content.to_string(),
+            },
+            TokenRef::Gaiji { description } => Token::Gaiji {
+                description: description.to_string(),
+            },
+            TokenRef::Accent { children } => Token::Accent {
+                children: to_tokens(children),
+            },
+        }
+    }
+}
+
+impl From<TokenRef<'_>> for Token {
+    fn from(token: TokenRef<'_>) -> Self {
+        token.to_token()
+    }
+}
+
+/// 借用トークン列を所有権を持つトークン列に変換
+fn to_tokens(tokens: &[TokenRef<'_>]) -> Vec<Token> {
+    tokens.iter().map(TokenRef::to_token).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -98,4 +180,19 @@ mod tests {
         };
         assert!(matches!(token, Token::Accent { .. }));
     }
+
+    #[test]
+    fn test_token_ref_to_token() {
+        let token = TokenRef::PrefixedRuby {
+            base_children: vec![TokenRef::Text("東京")],
+            ruby_children: vec![TokenRef::Text("とうきょう")],
+        };
+        assert_eq!(
+            Token::from(token),
+            Token::PrefixedRuby {
+                base_children: vec![Token::text("東京")],
+                ruby_children: vec![Token::text("とうきょう")],
+            }
+        );
+    }
 }
@@ -1,113 +1,142 @@
 //! 青空文庫形式の字句解析（トークナイザ）
+//!
+//! 入力文字列をバイト位置で走査し、入力を借用するトークン [`TokenRef`] を生成します。
+//! 所有権を持つ [`Token`] が必要な場合は [`Tokenizer::tokenize`] を使います。
 
 use crate::delimiters::*;
-use crate::token::Token;
+use crate::span::{Span, Spanned};
+use crate::token::{Token, TokenRef};
 
 /// 1行をトークン列に変換するトークナイザ
-pub struct Tokenizer {
-    /// 入力をcharとして保持
-    chars: Vec<char>,
-    /// 現在のchar位置
+pub struct Tokenizer<'a> {
+    /// 入力文字列
+    input: &'a str,
+    /// 現在のバイト位置
     pos: usize,
 }
 
-impl Tokenizer {
+impl<'a> Tokenizer<'a> {
     /// 新しいトークナイザを作成
-    pub fn new(input: &str) -> Self {
-        Self {
-            chars: input.chars().collect(),
-            pos: 0,
-        }
+    pub fn new(input: &'a str) -> Self {
+        Self { input, pos: 0 }
     }
 
     /// 入力をトークン列に変換
     pub fn tokenize(&mut self) -> Vec<Token> {
+        self.tokenize_ref()
+            .into_iter()
+            .map(|token| token.into())
+            .collect()
+    }
+
+    /// 入力を位置情報付きのトークン列に変換
+    ///
+    /// 範囲は入力文字列上のバイト位置です。
+    /// ルビや明示ルビの内側のトークンには位置情報は付きません。
+    pub fn tokenize_with_spans(&mut self) -> Vec<Spanned<Token>> {
+        self.tokenize_ref_with_spans()
+            .into_iter()
+            .map(|token| Spanned::new(token.value.into(), token.span))
+            .collect()
+    }
+
+    /// 入力を借用トークン列に変換
+    pub fn tokenize_ref(&mut self) -> Vec<TokenRef<'a>> {
+        let mut tokens = Vec::new();
+        while !self.is_eof() {
+            tokens.push(self.read_token());
+        }
+        tokens
+    }
+
+    /// 入力を位置情報付きの借用トークン列に変換
+    pub fn tokenize_ref_with_spans(&mut self) -> Vec<Spanned<TokenRef<'a>>> {
         let mut tokens = Vec::new();
 
         while !self.is_eof() {
-            let ch = self.current_char().unwrap();
-
-            match ch {
-                // コマンド ［＃...］ または外字 ※［＃...］の一部
-                COMMAND_BEGIN => {
-                    if self.peek_nth(1) == Some(IGETA) {
-                        tokens.push(self.read_command());
-                    } else {
-                        // ［ だけならテキスト
-                        tokens.push(Token::Text(ch.to_string()));
-                        self.skip(1);
-                    }
-                }
+            let start = self.pos;
+            let token = self.read_token();
+            tokens.push(Spanned::new(token, Span::new(start, self.pos)));
+        }
 
-                // ルビ 《...》
-                RUBY_BEGIN => {
-                    tokens.push(self.read_ruby());
-                }
+        tokens
+    }
 
-                // 明示ルビ ｜...《...》
-                RUBY_PREFIX => {
-                    tokens.push(self.read_prefixed_ruby());
-                }
+    // --- トークン読み取り ---
 
-                // 外字 ※［＃...］
-                GAIJI_MARK => {
-                    if self.peek_nth(1) == Some(COMMAND_BEGIN) && self.peek_nth(2) == Some(IGETA) {
-                        tokens.push(self.read_gaiji());
-                    } else {
-                        // ※ だけならテキスト
-                        tokens.push(Token::Text(ch.to_string()));
-                        self.skip(1);
-                    }
+    /// 現在位置から1トークンを読む
+    fn read_token(&mut self) -> TokenRef<'a> {
+        let ch = self.current_char().unwrap();
+
+        match ch {
+            // コマンド ［＃...］ または外字 ※［＃...］の一部
+            COMMAND_BEGIN => {
+                if self.peek_nth(1) == Some(IGETA) {
+                    self.read_command()
+                } else {
+                    // ［ だけならテキスト
+                    self.read_char()
                 }
+            }
+
+            // ルビ 《...》
+            RUBY_BEGIN => self.read_ruby(),
 
-                // アクセント 〔...〕
-                ACCENT_BEGIN => {
-                    if let Some(token) = self.try_read_accent() {
-                        tokens.push(token);
-                    } else {
-                        // アクセント記号がなければテキスト
-                        tokens.push(Token::Text(ch.to_string()));
-                        self.skip(1);
-                    }
+            // 明示ルビ ｜...《...》
+            RUBY_PREFIX => self.read_prefixed_ruby(),
+
+            // 外字 ※［＃...］
+            GAIJI_MARK => {
+                if self.peek_nth(1) == Some(COMMAND_BEGIN) && self.peek_nth(2) == Some(IGETA) {
+                    self.read_gaiji()
+                } else {
+                    // ※ だけならテキスト
+                    self.read_char()
                 }
+            }
 
-                // その他はテキスト
-                _ => {
-                    tokens.push(self.read_text());
+            // アクセント 〔...〕
+            ACCENT_BEGIN => {
+                if let Some(token) = self.try_read_accent() {
+                    token
+                } else {
+                    // アクセント記号がなければテキスト
+                    self.read_char()
                 }
             }
-        }
 
-        tokens
+            // その他はテキスト
+            _ => self.read_text(),
+        }
     }
 
-    // --- トークン読み取り ---
+    /// 現在の1文字をテキストトークンとして読む
+    fn read_char(&mut self) -> TokenRef<'a> {
+        let start = self.pos;
+        self.skip(1);
+        TokenRef::Text(self.slice_from(start))
+    }
 
     /// テキストトークンを読む（デリミタまで）
-    fn read_text(&mut self) -> Token {
+    fn read_text(&mut self) -> TokenRef<'a> {
         let start = self.pos;
 
-        while self.pos < self.chars.len() {
-            let ch = self.chars[self.pos];
-
-            // デリミタに遭遇したら終了
-            if matches!(
-                ch,
-                COMMAND_BEGIN | RUBY_BEGIN | RUBY_PREFIX | GAIJI_MARK | ACCENT_BEGIN
-            ) {
-                break;
-            }
-
-            self.pos += 1;
-        }
-
-        let text: String = self.chars[start..self.pos].iter().collect();
-        Token::Text(text)
+        // デリミタに遭遇したら終了
+        self.pos = self.input[start..]
+            .find(|ch| {
+                matches!(
+                    ch,
+                    COMMAND_BEGIN | RUBY_BEGIN | RUBY_PREFIX | GAIJI_MARK | ACCENT_BEGIN
+                )
+            })
+            .map_or(self.input.len(), |i| start + i);
+
+        TokenRef::Text(self.slice_from(start))
     }
 
     /// コマンドトークンを読む ［＃...］
     /// ネストに対応（括弧の深さを追跡）
-    fn read_command(&mut self) -> Token {
+    fn read_command(&mut self) -> TokenRef<'a> {
         self.skip(2); // ［＃
         let start = self.pos;
 
@@ -115,11 +144,11 @@ impl Tokenizer {
         let content = self.slice_from(start);
         self.skip_if(COMMAND_END);
 
-        Token::Command { content }
+        TokenRef::Command { content }
     }
 
     /// ルビトークンを読む 《...》
-    fn read_ruby(&mut self) -> Token {
+    fn read_ruby(&mut self) -> TokenRef<'a> {
         self.skip(1); // 《
         let start = self.pos;
 
@@ -128,20 +157,21 @@ impl Tokenizer {
         self.skip_if(RUBY_END);
 
         // ルビ内を再帰的にトークナイズ
-        let children = Tokenizer::new(&content).tokenize();
+        let children = Tokenizer::new(content).tokenize_ref();
 
-        Token::Ruby { children }
+        TokenRef::Ruby { children }
     }
 
     /// 明示ルビトークンを読む ｜...《...》
-    fn read_prefixed_ruby(&mut self) -> Token {
+    fn read_prefixed_ruby(&mut self) -> TokenRef<'a> {
+        let prefix_start = self.pos;
         self.skip(1); // ｜
         let base_start = self.pos;
 
         // 《 が見つからなければ ｜ をテキストとして返す
         if !self.skip_until(RUBY_BEGIN) {
             self.pos = base_start;
-            return Token::Text(RUBY_PREFIX.to_string());
+            return TokenRef::Text(self.slice_from(prefix_start));
         }
 
         let base_content = self.slice_from(base_start);
@@ -153,17 +183,17 @@ impl Tokenizer {
         self.skip_if(RUBY_END);
 
         // 親文字とルビを再帰的にトークナイズ
-        let base_children = Tokenizer::new(&base_content).tokenize();
-        let ruby_children = Tokenizer::new(&ruby_content).tokenize();
+        let base_children = Tokenizer::new(base_content).tokenize_ref();
+        let ruby_children = Tokenizer::new(ruby_content).tokenize_ref();
 
-        Token::PrefixedRuby {
+        TokenRef::PrefixedRuby {
             base_children,
             ruby_children,
         }
     }
 
     /// 外字トークンを読む ※［＃...］
-    fn read_gaiji(&mut self) -> Token {
+    fn read_gaiji(&mut self) -> TokenRef<'a> {
         self.skip(3); // ※［＃
         let start = self.pos;
 
@@ -171,12 +201,12 @@ impl Tokenizer {
         let description = self.slice_from(start);
         self.skip_if(COMMAND_END);
 
-        Token::Gaiji { description }
+        TokenRef::Gaiji { description }
     }
 
     /// アクセントトークンを試行的に読む 〔...〕
     /// アクセント記号がなければNone（テキストとして扱う）
-    fn try_read_accent(&mut self) -> Option<Token> {
+    fn try_read_accent(&mut self) -> Option<TokenRef<'a>> {
         let start = self.pos;
         self.skip(1); // 〔
         let content_start = self.pos;
@@ -189,81 +219,95 @@ impl Tokenizer {
 
         let content = self.slice_from(content_start);
 
-        if !Self::contains_accent_marks(&content) {
+        if !Self::contains_accent_marks(content) {
             self.pos = start;
             return None;
         }
 
         self.skip(1); // 〕
 
-        let children = Tokenizer::new(&content).tokenize();
-        Some(Token::Accent { children })
+        let children = Tokenizer::new(content).tokenize_ref();
+        Some(TokenRef::Accent { children })
     }
 
     /// 文字列がアクセント記号を含むか判定
     fn contains_accent_marks(s: &str) -> bool {
-        s.chars().any(|c| ACCENT_MARKS.contains(&c))
+        s.contains(ACCENT_MARKS)
     }
 
     // --- カーソル操作ヘルパー ---
 
+    /// 現在位置以降の入力
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
     /// 入力の終端に達したか
     fn is_eof(&self) -> bool {
-        self.pos >= self.chars.len()
+        self.pos >= self.input.len()
     }
 
     /// 現在位置から n 文字先を覗く
     fn peek_nth(&self, n: usize) -> Option<char> {
-        self.chars.get(self.pos + n).copied()
+        self.rest().chars().nth(n)
     }
 
     /// 現在の文字を取得
     fn current_char(&self) -> Option<char> {
-        self.chars.get(self.pos).copied()
+        self.rest().chars().next()
     }
 
     /// n 文字スキップ
     fn skip(&mut self, n: usize) {
-        self.pos += n;
+        self.pos += self
+            .rest()
+            .chars()
+            .take(n)
+            .map(char::len_utf8)
+            .sum::<usize>();
     }
 
     /// 特定の文字までスキップ（見つかったらtrue）
     fn skip_until(&mut self, target: char) -> bool {
-        while self.pos < self.chars.len() {
-            if self.chars[self.pos] == target {
-                return true;
+        match self.rest().find(target) {
+            Some(i) => {
+                self.pos += i;
+                true
+            }
+            None => {
+                self.pos = self.input.len();
+                false
             }
-            self.pos += 1;
         }
-        false
     }
 
     /// ネストを考慮して閉じ括弧までスキップ（閉じ括弧の手前で停止）
     fn skip_until_balanced(&mut self, open: char, close: char) {
         let mut depth = 1;
-        while self.pos < self.chars.len() && depth > 0 {
-            let ch = self.chars[self.pos];
+        for (i, ch) in self.rest().char_indices() {
             if ch == open {
                 depth += 1;
             } else if ch == close {
                 depth -= 1;
-            }
-            if depth > 0 {
-                self.pos += 1;
+                if depth == 0 {
+                    self.pos += i;
+                    return;
+                }
             }
         }
+        self.pos = self.input.len();
     }
 
     /// 現在の文字が target なら1文字スキップ
     fn skip_if(&mut self, target: char) {
         if self.current_char() == Some(target) {
-            self.pos += 1;
+            self.pos += target.len_utf8();
         }
     }
 
-    /// start から現在位置までを文字列として取得
-    fn slice_from(&self, start: usize) -> String {
-        self.chars[start..self.pos].iter().collect()
+    /// start から現在位置までの入力を取得
+    fn slice_from(&self, start: usize) -> &'a str {
+        &self.input[start..self.pos]
     }
 }
 
@@ -272,6 +316,39 @@ pub fn tokenize(input: &str) -> Vec<Token> {
     Tokenizer::new(input).tokenize()
 }
 
+/// 文字列を位置情報付きのトークン列に変換するユーティリティ関数
+///
+/// # Examples
+///
+/// ```
+/// use aozora_core::span::Span;
+/// use aozora_core::tokenizer::tokenize_with_spans;
+///
+/// let tokens = tokenize_with_spans("漢字《かんじ》");
+/// assert_eq!(tokens[0].span, Span::new(0, 6));
+/// assert_eq!(tokens[1].span, Span::new(6, 21));
+/// ```
+pub fn tokenize_with_spans(input: &str) -> Vec<Spanned<Token>> {
+    Tokenizer::new(input).tokenize_with_spans()
+}
+
+/// 文字列を入力を借用するトークン列に変換するユーティリティ関数
+///
+/// # Examples
+///
+/// ```
+/// use aozora_core::token::TokenRef;
+/// use aozora_core::tokenizer::tokenize_ref;
+///
+/// let input = String::from("猫である［＃「である」に傍点］");
+/// let tokens = tokenize_ref(&input);
+/// assert_eq!(tokens[0], TokenRef::Text("猫である"));
+/// assert_eq!(tokens[1], TokenRef::Command { content: "「である」に傍点" });
+/// ```
+pub fn tokenize_ref(input: &str) -> Vec<TokenRef<'_>> {
+    Tokenizer::new(input).tokenize_ref()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -409,6 +486,34 @@ mod tests {
         assert_eq!(tokens, vec![]);
     }
 
+    #[test]
+    fn test_spans() {
+        let input = "吾輩《わがはい》は※［＃「米印」、U+203B］｜猫《ねこ》";
+        let tokens = tokenize_with_spans(input);
+        let sources: Vec<&str> = tokens
+            .iter()
+            .map(|t| &input[t.span.start..t.span.end])
+            .collect();
+        assert_eq!(
+            sources,
+            vec![
+                "吾輩",
+                "《わがはい》",
+                "は",
+                "※［＃「米印」、U+203B］",
+                "｜猫《ねこ》"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_spans_unterminated() {
+        let input = "本文［＃閉じない";
+        let tokens = tokenize_with_spans(input);
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[1].span, Span::new(6, input.len()));
+    }
+
     #[test]
     fn test_multiple_tokens() {
         let tokens =
@@ -431,4 +536,32 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_tokenize_ref_borrows_input() {
+        let input = "｜東京《とうきょう》※［＃「丸印」、U+25CB］";
+        let tokens = tokenize_ref(input);
+        let TokenRef::PrefixedRuby { base_children, .. } = &tokens[0] else {
+            panic!("expected prefixed ruby: {tokens:?}");
+        };
+        let TokenRef::Text(base) = base_children[0] else {
+            panic!("expected text: {base_children:?}");
+        };
+        // 入力文字列のスライスを指している
+        assert_eq!(base, "東京");
+        assert!(std::ptr::eq(base.as_ptr(), input[3..].as_ptr()));
+        assert_eq!(
+            tokens[1],
+            TokenRef::Gaiji {
+                description: "「丸印」、U+25CB"
+            }
+        );
+    }
+
+    #[test]
+    fn test_tokenize_ref_matches_owned() {
+        let input = "吾輩《わがはい》は〔cafe'〕［＃「は」に傍点］｜だけ※［＃閉じない";
+        let owned: Vec<Token> = tokenize_ref(input).into_iter().map(Token::from).collect();
+        assert_eq!(owned, tokenize(input));
+    }
 }
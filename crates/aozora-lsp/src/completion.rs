@@ -0,0 +1,227 @@
+//! 注記の補完
+//!
+//! `［＃` の直後ではブロックや範囲の注記を、`［＃「対象」` の直後では
+//! 装飾・見出しなどの指定を候補にします。
+
+use aozora_core::node::{MidashiLevel, MidashiStyle, StyleType};
+use aozora_core::span::Span;
+use lsp_types::{CompletionItem, CompletionItemKind, CompletionTextEdit, Position, TextEdit};
+
+use crate::position::PositionMap;
+
+/// 注記の開始
+const COMMAND_START: &str = "［＃";
+
+/// 単独で使う注記と説明
+///
+/// 字数などの数字は例として入れてあり、補完後に書き換えて使います。
+const COMMANDS: &[(&str, &str)] = &[
+    ("ここから２字下げ", "字下げ（ブロック）"),
+    ("ここで字下げ終わり", "字下げの終わり"),
+    ("２字下げ", "字下げ（1行）"),
+    ("ここから２字下げ、折り返して３字下げ", "ぶら下げ"),
+    (
+        "ここから改行天付き、折り返して１字下げ",
+        "ぶら下げ（天付き）",
+    ),
+    ("地付き", "地付き（1行）"),
+    ("ここから地付き", "地付き（ブロック）"),
+    ("ここで地付き終わり", "地付きの終わり"),
+    ("地から２字上げ", "字上げ（1行）"),
+    ("ここから地から２字上げ", "字上げ（ブロック）"),
+    ("ここで字上げ終わり", "字上げの終わり"),
+    ("ここから２０字詰め", "字詰め"),
+    ("ここで字詰め終わり", "字詰めの終わり"),
+    ("ここから罫囲み", "罫囲み"),
+    ("ここで罫囲み終わり", "罫囲みの終わり"),
+    ("大見出し", "大見出し"),
+    ("大見出し終わり", "大見出しの終わり"),
+    ("中見出し", "中見出し"),
+    ("中見出し終わり", "中見出しの終わり"),
+    ("小見出し", "小見出し"),
+    ("小見出し終わり", "小見出しの終わり"),
+    ("ここから大見出し", "大見出し（ブロック）"),
+    ("ここで大見出し終わり", "大見出しの終わり"),
+    ("ここから中見出し", "中見出し（ブロック）"),
+    ("ここで中見出し終わり", "中見出しの終わり"),
+    ("ここから小見出し", "小見出し（ブロック）"),
+    ("ここで小見出し終わり", "小見出しの終わり"),
+    ("ここから横組み", "横組み（ブロック）"),
+    ("ここで横組み終わり", "横組みの終わり"),
+    ("横組み", "横組み"),
+    ("横組み終わり", "横組みの終わり"),
+    ("ここから太字", "太字（ブロック）"),
+    ("ここで太字終わり", "太字の終わり"),
+    ("ここから斜体", "斜体（ブロック）"),
+    ("ここで斜体終わり", "斜体の終わり"),
+    ("ここから１段階大きな文字", "大きな文字（ブロック）"),
+    ("ここで大きな文字終わり", "大きな文字の終わり"),
+    ("１段階大きな文字", "大きな文字"),
+    ("大きな文字終わり", "大きな文字の終わり"),
+    ("ここから１段階小さな文字", "小さな文字（ブロック）"),
+    ("ここで小さな文字終わり", "小さな文字の終わり"),
+    ("１段階小さな文字", "小さな文字"),
+    ("小さな文字終わり", "小さな文字の終わり"),
+    ("縦中横", "縦中横"),
+    ("縦中横終わり", "縦中横の終わり"),
+    ("キャプション", "キャプション"),
+    ("キャプション終わり", "キャプションの終わり"),
+    ("割り注", "割り注"),
+    ("割り注終わり", "割り注の終わり"),
+    ("本文終わり", "本文の終わり"),
+];
+
+/// 位置での補完候補を返す
+///
+/// 閉じていない `［＃` の内側でなければ `None` を返します。
+pub fn completion(text: &str, position: Position) -> Option<Vec<CompletionItem>> {
+    let map = PositionMap::new(text);
+    let line_start = map.line_start(position.line as usize);
+    let cursor = map.offset(position);
+    let before = &text[line_start..cursor];
+
+    let content_start = before.rfind(COMMAND_START)? + COMMAND_START.len();
+    let content = &before[content_start..];
+    if content.contains('］') {
+        return None;
+    }
+
+    let (replace_start, candidates) = match (content.rfind('「'), content.rfind('」')) {
+        // 「対象」の入力中
+        (Some(open), close) if close.is_none_or(|close| close < open) => return None,
+        // 「対象」に続く指定
+        (_, Some(close)) => (content_start + close + '」'.len_utf8(), reference_specs()),
+        _ => (content_start, commands()),
+    };
+
+    let range = map.range(Span::new(line_start + replace_start, cursor));
+    let items = candidates
+        .into_iter()
+        .map(|(label, detail)| CompletionItem {
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit::new(
+                range,
+                label.clone(),
+            ))),
+            label,
+            kind: Some(CompletionItemKind::KEYWORD),
+            detail: Some(detail),
+            ..Default::default()
+        })
+        .collect();
+    Some(items)
+}
+
+/// `［＃` の直後に書く注記の候補
+fn commands() -> Vec<(String, String)> {
+    let mut commands: Vec<(String, String)> = COMMANDS
+        .iter()
+        .map(|&(command, detail)| (command.to_string(), detail.to_string()))
+        .collect();
+
+    // 装飾の範囲指定（［＃傍点］…［＃傍点終わり］）
+    for style_type in StyleType::ALL {
+        let name = style_type.command_name();
+        commands.push((name.to_string(), format!("{name}（範囲）")));
+        commands.push((format!("{name}終わり"), format!("{name}の終わり")));
+    }
+
+    commands
+}
+
+/// `［＃「対象」` に続けて書く指定の候補
+fn reference_specs() -> Vec<(String, String)> {
+    let mut specs = Vec::new();
+
+    for style_type in StyleType::ALL {
+        let name = style_type.command_name();
+        let spec = match style_type {
+            StyleType::Bold | StyleType::Italic | StyleType::Subscript | StyleType::Superscript => {
+                format!("は{name}")
+            }
+            // 左側の装飾は「の左に」
+            _ if name.starts_with("左に") => format!("の{name}"),
+            _ => format!("に{name}"),
+        };
+        specs.push((spec, name.to_string()));
+    }
+
+    for style in [
+        MidashiStyle::Normal,
+        MidashiStyle::Dogyo,
+        MidashiStyle::Mado,
+    ] {
+        for level in [MidashiLevel::O, MidashiLevel::Naka, MidashiLevel::Ko] {
+            let name = format!("{}{}", style.command_prefix(), level.command_name());
+            specs.push((format!("は{name}"), name));
+        }
+    }
+
+    for name in ["大きな文字", "小さな文字"] {
+        specs.push((format!("は１段階{name}"), name.to_string()));
+    }
+
+    specs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aozora_core::parser::{parse_command, CommandResult};
+    use lsp_types::Range;
+
+    fn labels(text: &str, position: Position) -> Option<Vec<String>> {
+        completion(text, position).map(|items| items.into_iter().map(|i| i.label).collect())
+    }
+
+    #[test]
+    fn test_completion_commands() {
+        let text = "本文\n［＃ここ";
+        let items = completion(text, Position::new(1, 4)).unwrap();
+        let item = items
+            .iter()
+            .find(|i| i.label == "ここから２字下げ")
+            .unwrap();
+        let Some(CompletionTextEdit::Edit(edit)) = &item.text_edit else {
+            panic!("expected text edit");
+        };
+        assert_eq!(
+            edit.range,
+            Range::new(Position::new(1, 2), Position::new(1, 4))
+        );
+        assert!(items.iter().any(|i| i.label == "傍点終わり"));
+    }
+
+    #[test]
+    fn test_completion_reference_specs() {
+        let labels = labels("猫である［＃「である」に", Position::new(0, 12)).unwrap();
+        assert!(labels.contains(&"に傍点".to_string()));
+        assert!(labels.contains(&"の左に傍線".to_string()));
+        assert!(labels.contains(&"は太字".to_string()));
+        assert!(labels.contains(&"は窓小見出し".to_string()));
+    }
+
+    #[test]
+    fn test_no_completion_outside_command() {
+        assert_eq!(labels("本文", Position::new(0, 2)), None);
+        assert_eq!(labels("［＃傍点］本文", Position::new(0, 7)), None);
+        assert_eq!(labels("［＃「であ", Position::new(0, 5)), None);
+    }
+
+    #[test]
+    fn test_candidates_are_known_commands() {
+        // 本文終わりはセクションの区切りとして文書側で扱う
+        for (command, _) in commands().into_iter().filter(|(c, _)| c != "本文終わり") {
+            assert!(
+                !matches!(parse_command(&command), CommandResult::Unknown(_)),
+                "{command}"
+            );
+        }
+        for (spec, _) in reference_specs() {
+            let command = format!("「対象」{spec}");
+            assert!(
+                !matches!(parse_command(&command), CommandResult::Unknown(_)),
+                "{command}"
+            );
+        }
+    }
+}
@@ -0,0 +1,57 @@
+//! 注記の診断
+
+use aozora_core::diagnostic::Severity;
+use aozora_core::incremental::IncrementalDocument;
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+
+use crate::position::PositionMap;
+
+/// 診断の発生元
+const SOURCE: &str = "aozora";
+
+/// 文書を検査してLSPの診断に変換
+///
+/// 本文と本文終わり後のテキストが対象です（`aozora2 check` と同じ）。
+/// 行内の診断は文書が行ごとに保持しているものを使います。
+pub fn diagnostics(document: &IncrementalDocument) -> Vec<Diagnostic> {
+    let map = PositionMap::new(document.text());
+    document
+        .diagnostics()
+        .into_iter()
+        .map(|diagnostic| Diagnostic {
+            range: map.range(diagnostic.span),
+            severity: Some(match diagnostic.severity {
+                Severity::Error => DiagnosticSeverity::ERROR,
+                Severity::Warning => DiagnosticSeverity::WARNING,
+            }),
+            code: Some(NumberOrString::String(diagnostic.code.as_str().to_string())),
+            source: Some(SOURCE.to_string()),
+            message: diagnostic.message,
+            ..Default::default()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::{Position, Range};
+
+    #[test]
+    fn test_diagnostics() {
+        let text = "題名\n\n吾輩は猫である［＃「犬」に傍点］\n本文［＃閉じない\n";
+        let diagnostics = diagnostics(&IncrementalDocument::new(text));
+        assert_eq!(diagnostics.len(), 2);
+
+        assert_eq!(
+            diagnostics[0].range,
+            Range::new(Position::new(2, 7), Position::new(2, 16))
+        );
+        assert_eq!(
+            diagnostics[0].code,
+            Some(NumberOrString::String("unresolved-reference".to_string()))
+        );
+        assert_eq!(diagnostics[1].range.start, Position::new(3, 2));
+        assert_eq!(diagnostics[1].severity, Some(DiagnosticSeverity::ERROR));
+    }
+}
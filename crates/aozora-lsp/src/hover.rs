@@ -0,0 +1,109 @@
+//! 外字注記のホバー表示
+
+use aozora_core::gaiji::{parse_gaiji, GaijiResult};
+use aozora_core::span::Span;
+use aozora_core::token::TokenRef;
+use aozora_core::tokenizer::Tokenizer;
+use lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position};
+
+use crate::position::PositionMap;
+
+/// 外字注記の開始
+const GAIJI_START: &str = "※［＃";
+
+/// 位置にある外字注記の変換結果を返す
+pub fn hover(text: &str, position: Position) -> Option<Hover> {
+    let map = PositionMap::new(text);
+    let line = position.line as usize;
+    let line_start = map.line_start(line);
+    let line_text = map.line_text(line);
+    let cursor = map.offset(position) - line_start;
+
+    // ルビの親文字などの内側の外字注記も対象にするため、開始記号から読む
+    let (description, span) = line_text
+        .match_indices(GAIJI_START)
+        .find_map(|(start, _)| {
+            let token = Tokenizer::new(&line_text[start..])
+                .tokenize_ref_with_spans()
+                .into_iter()
+                .next()?;
+            let TokenRef::Gaiji { description } = token.value else {
+                return None;
+            };
+            let span = token.span.shift(start);
+            (span.start <= cursor && cursor < span.end).then_some((description, span))
+        })?;
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: gaiji_markdown(description),
+        }),
+        range: Some(map.range(Span::new(line_start + span.start, line_start + span.end))),
+    })
+}
+
+/// 外字の変換結果の表示内容
+fn gaiji_markdown(description: &str) -> String {
+    let mut lines = vec![format!("外字：{description}")];
+    match parse_gaiji(description) {
+        GaijiResult::Unicode(unicode) => {
+            lines.push(format!("**{unicode}**（{}）", code_points(&unicode)));
+        }
+        GaijiResult::JisConverted { jis_code, unicode } => {
+            lines.push(format!("**{unicode}**（{}）", code_points(&unicode)));
+            lines.push(format!("JIS X 0213：{jis_code}"));
+        }
+        GaijiResult::JisImage { jis_code } => {
+            lines.push(format!("JIS X 0213：{jis_code}"));
+            lines.push("対応するUnicode文字がありません".to_string());
+        }
+        GaijiResult::Unconvertible => {
+            lines.push("Unicode文字に変換できません".to_string());
+        }
+    }
+    lines.join("\n\n")
+}
+
+/// 文字列のコードポイント表記（U+XXXX）
+fn code_points(s: &str) -> String {
+    s.chars()
+        .map(|c| format!("U+{:04X}", c as u32))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::Range;
+
+    fn hover_text(text: &str, position: Position) -> Option<String> {
+        hover(text, position).map(|hover| match hover.contents {
+            HoverContents::Markup(markup) => markup.value,
+            _ => unreachable!(),
+        })
+    }
+
+    #[test]
+    fn test_hover_unicode_gaiji() {
+        let text = "題名\n本文※［＃「丸印」、U+25CB］です";
+        let hover = hover(text, Position::new(1, 5)).unwrap();
+        assert_eq!(
+            hover.range,
+            Some(Range::new(Position::new(1, 2), Position::new(1, 17)))
+        );
+        let value = hover_text(text, Position::new(1, 2)).unwrap();
+        assert!(value.contains("**○**（U+25CB）"));
+        assert_eq!(hover_text(text, Position::new(1, 17)), None);
+        assert_eq!(hover_text(text, Position::new(1, 0)), None);
+    }
+
+    #[test]
+    fn test_hover_jis_gaiji_in_ruby() {
+        let text = "｜※［＃「てへん＋劣」、第3水準1-84-77］《むし》る";
+        let value = hover_text(text, Position::new(0, 3)).unwrap();
+        assert!(value.contains("JIS X 0213：1-84-77"));
+        assert!(value.contains("**挘**"));
+    }
+}
@@ -0,0 +1,30 @@
+//! aozora-lsp
+//!
+//! 青空文庫形式の言語サーバー（Language Server Protocol）
+//!
+//! 標準入出力で通信し、次の機能を提供します。
+//!
+//! - 注記の診断（`aozora2 check` と同じ検査）
+//! - 外字注記 `※［＃…］` のホバーでの変換結果の表示
+//! - `［＃` 内での注記の補完
+//! - 見出しによるアウトライン
+
+mod completion;
+mod diagnostics;
+mod hover;
+mod position;
+mod server;
+mod symbols;
+
+use lsp_server::Connection;
+
+fn main() -> server::Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+
+    let capabilities = serde_json::to_value(server::capabilities())?;
+    connection.initialize(capabilities)?;
+    server::Server::new(connection).run()?;
+
+    io_threads.join()?;
+    Ok(())
+}
@@ -0,0 +1,101 @@
+//! バイト位置とLSPの位置（行・UTF-16の桁）の変換
+
+use aozora_core::span::{LineIndex, Span};
+use lsp_types::{Position, Range};
+
+/// 文書内の位置の変換表
+pub struct PositionMap<'a> {
+    text: &'a str,
+    index: LineIndex<'a>,
+}
+
+impl<'a> PositionMap<'a> {
+    /// テキストから変換表を作成
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            index: LineIndex::new(text),
+        }
+    }
+
+    /// バイト位置をLSPの位置に変換
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.text.len());
+        let line = self.index.position(offset).line;
+        let start = self.line_start(line);
+        let column = self.text[start..offset].encode_utf16().count();
+        Position::new(line as u32, column as u32)
+    }
+
+    /// バイト範囲をLSPの範囲に変換
+    pub fn range(&self, span: Span) -> Range {
+        Range::new(self.position(span.start), self.position(span.end))
+    }
+
+    /// LSPの位置をバイト位置に変換
+    ///
+    /// 行末より後ろの位置は行末（改行の手前）として扱います。
+    pub fn offset(&self, position: Position) -> usize {
+        let line = position.line as usize;
+        if line >= self.index.line_count() {
+            return self.text.len();
+        }
+        let start = self.line_start(line);
+        let text = self.line_text(line);
+
+        let mut column = 0;
+        for (i, ch) in text.char_indices() {
+            if column >= position.character as usize {
+                return start + i;
+            }
+            column += ch.len_utf16();
+        }
+        start + text.len()
+    }
+
+    /// LSPの範囲をバイト範囲に変換
+    pub fn span(&self, range: Range) -> Span {
+        Span::new(self.offset(range.start), self.offset(range.end))
+    }
+
+    /// 指定行のテキスト（改行を除く）
+    pub fn line_text(&self, line: usize) -> &'a str {
+        let start = self.line_start(line);
+        let end = self
+            .index
+            .line_start(line + 1)
+            .unwrap_or(self.text.len() + 1)
+            - 1;
+        self.text[start..end.max(start)].trim_end_matches('\r')
+    }
+
+    /// 指定行の開始バイト位置
+    pub fn line_start(&self, line: usize) -> usize {
+        self.index.line_start(line).unwrap_or(self.text.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_utf16() {
+        // 𠮷 はUTF-16でサロゲートペア（2単位）
+        let text = "一行目\r\n𠮷野家";
+        let map = PositionMap::new(text);
+        let offset = text.find('野').unwrap();
+        assert_eq!(map.position(offset), Position::new(1, 2));
+        assert_eq!(map.offset(Position::new(1, 2)), offset);
+        assert_eq!(map.line_text(0), "一行目");
+        assert_eq!(map.line_text(1), "𠮷野家");
+    }
+
+    #[test]
+    fn test_offset_past_line_end() {
+        let text = "あい\nう";
+        let map = PositionMap::new(text);
+        assert_eq!(map.offset(Position::new(0, 10)), "あい".len());
+        assert_eq!(map.offset(Position::new(5, 0)), text.len());
+    }
+}
@@ -0,0 +1,379 @@
+//! 言語サーバーの本体
+//!
+//! 開いている文書の内容を保持し、リクエストと通知を処理します。
+//! 文書の同期は差分の送信で行います。変更された行だけを解析し直し、
+//! 診断はブロックの対応だけを文書全体で検査し直します。
+
+use std::collections::HashMap;
+use std::error::Error;
+
+use aozora_core::incremental::IncrementalDocument;
+
+use lsp_server::{Connection, Message, Notification, Request, RequestId, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+    Notification as NotificationTrait, PublishDiagnostics,
+};
+use lsp_types::request::{
+    Completion, DocumentSymbolRequest, HoverRequest, Request as RequestTrait,
+};
+use lsp_types::{
+    CompletionOptions, CompletionResponse, DocumentSymbolResponse, HoverProviderCapability, OneOf,
+    PublishDiagnosticsParams, ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind,
+    Uri,
+};
+
+use crate::position::PositionMap;
+use crate::{completion, diagnostics, hover, symbols};
+
+/// サーバーの処理結果
+pub type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
+
+/// サーバーが提供する機能
+pub fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(
+            TextDocumentSyncKind::INCREMENTAL,
+        )),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec!["＃".to_string(), "」".to_string()]),
+            ..Default::default()
+        }),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    }
+}
+
+/// 言語サーバー
+pub struct Server {
+    connection: Connection,
+    /// 開いている文書の内容
+    documents: HashMap<Uri, IncrementalDocument>,
+}
+
+impl Server {
+    /// 初期化済みの接続からサーバーを作成
+    pub fn new(connection: Connection) -> Self {
+        Self {
+            connection,
+            documents: HashMap::new(),
+        }
+    }
+
+    /// 終了要求を受けるまでメッセージを処理
+    pub fn run(mut self) -> Result<()> {
+        let receiver = self.connection.receiver.clone();
+        for message in receiver {
+            match message {
+                Message::Request(request) => {
+                    if self.connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    self.handle_request(request)?;
+                }
+                Message::Notification(notification) => self.handle_notification(notification)?,
+                Message::Response(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// リクエストを処理
+    fn handle_request(&mut self, request: Request) -> Result<()> {
+        match request.method.as_str() {
+            HoverRequest::METHOD => {
+                let Some((id, params)) = self.request_params::<HoverRequest>(request)? else {
+                    return Ok(());
+                };
+                let position = params.text_document_position_params;
+                let result = self
+                    .document(&position.text_document.uri)
+                    .and_then(|text| hover::hover(text, position.position));
+                self.respond(id, result)
+            }
+            Completion::METHOD => {
+                let Some((id, params)) = self.request_params::<Completion>(request)? else {
+                    return Ok(());
+                };
+                let position = params.text_document_position;
+                let result = self
+                    .document(&position.text_document.uri)
+                    .and_then(|text| completion::completion(text, position.position))
+                    .map(CompletionResponse::Array);
+                self.respond(id, result)
+            }
+            DocumentSymbolRequest::METHOD => {
+                let Some((id, params)) = self.request_params::<DocumentSymbolRequest>(request)?
+                else {
+                    return Ok(());
+                };
+                let result = self
+                    .documents
+                    .get(&params.text_document.uri)
+                    .map(|document| {
+                        DocumentSymbolResponse::Nested(symbols::document_symbols(document))
+                    });
+                self.respond(id, result)
+            }
+            _ => {
+                let response = Response::new_err(
+                    request.id,
+                    lsp_server::ErrorCode::MethodNotFound as i32,
+                    format!("unsupported request: {}", request.method),
+                );
+                self.connection.sender.send(response.into())?;
+                Ok(())
+            }
+        }
+    }
+
+    /// 通知を処理
+    fn handle_notification(&mut self, notification: Notification) -> Result<()> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let Some(params) = notification_params::<DidOpenTextDocument>(notification) else {
+                    return Ok(());
+                };
+                let document = params.text_document;
+                self.documents.insert(
+                    document.uri.clone(),
+                    IncrementalDocument::new(&document.text),
+                );
+                self.publish(document.uri)
+            }
+            DidChangeTextDocument::METHOD => {
+                let Some(params) = notification_params::<DidChangeTextDocument>(notification)
+                else {
+                    return Ok(());
+                };
+                let uri = params.text_document.uri;
+                let Some(document) = self.documents.get_mut(&uri) else {
+                    return Ok(());
+                };
+                for change in params.content_changes {
+                    match change.range {
+                        Some(range) => {
+                            let span = PositionMap::new(document.text()).span(range);
+                            document.edit(span, &change.text);
+                        }
+                        None => *document = IncrementalDocument::new(&change.text),
+                    }
+                }
+                self.publish(uri)
+            }
+            DidCloseTextDocument::METHOD => {
+                let Some(params) = notification_params::<DidCloseTextDocument>(notification) else {
+                    return Ok(());
+                };
+                let uri = params.text_document.uri;
+                self.documents.remove(&uri);
+                self.publish_diagnostics(uri, Vec::new())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// リクエストのパラメータを取り出す
+    ///
+    /// パラメータが不正な場合は `InvalidParams` のエラーを応答し、`None` を返します。
+    fn request_params<R: RequestTrait>(
+        &self,
+        request: Request,
+    ) -> Result<Option<(RequestId, R::Params)>> {
+        let id = request.id.clone();
+        match request.extract::<R::Params>(R::METHOD) {
+            Ok(extracted) => Ok(Some(extracted)),
+            Err(error) => {
+                let response = Response::new_err(
+                    id,
+                    lsp_server::ErrorCode::InvalidParams as i32,
+                    error.to_string(),
+                );
+                self.connection.sender.send(response.into())?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// 文書の診断を送る
+    fn publish(&self, uri: Uri) -> Result<()> {
+        let diagnostics = self
+            .documents
+            .get(&uri)
+            .map(diagnostics::diagnostics)
+            .unwrap_or_default();
+        self.publish_diagnostics(uri, diagnostics)
+    }
+
+    /// 診断を送る
+    fn publish_diagnostics(&self, uri: Uri, diagnostics: Vec<lsp_types::Diagnostic>) -> Result<()> {
+        let params = PublishDiagnosticsParams::new(uri, diagnostics, None);
+        let notification = Notification::new(PublishDiagnostics::METHOD.to_string(), params);
+        self.connection.sender.send(notification.into())?;
+        Ok(())
+    }
+
+    /// 応答を送る
+    fn respond<T: serde::Serialize>(&self, id: RequestId, result: T) -> Result<()> {
+        let response = Response::new_ok(id, result);
+        self.connection.sender.send(response.into())?;
+        Ok(())
+    }
+
+    /// 開いている文書の内容
+    fn document(&self, uri: &Uri) -> Option<&str> {
+        self.documents.get(uri).map(IncrementalDocument::text)
+    }
+}
+
+/// 通知のパラメータを取り出す
+///
+/// パラメータが不正な場合は標準エラー出力に記録し、通知を無視します。
+fn notification_params<N: NotificationTrait>(notification: Notification) -> Option<N::Params> {
+    match notification.extract::<N::Params>(N::METHOD) {
+        Ok(params) => Some(params),
+        Err(error) => {
+            eprintln!("{error}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::{
+        DidChangeTextDocumentParams, DidOpenTextDocumentParams, Hover, HoverParams, Position,
+        Range, TextDocumentContentChangeEvent, TextDocumentIdentifier, TextDocumentItem,
+        TextDocumentPositionParams, VersionedTextDocumentIdentifier,
+    };
+    use std::str::FromStr;
+
+    #[test]
+    fn test_server_session() {
+        let (server, client) = Connection::memory();
+        let handle = std::thread::spawn(move || Server::new(server).run().unwrap());
+
+        let uri = Uri::from_str("file:///tmp/sample.txt").unwrap();
+        let open = DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(
+                uri.clone(),
+                "aozora".to_string(),
+                1,
+                "題名\n\n本文［＃「犬」に傍点］※［＃「丸印」、U+25CB］\n".to_string(),
+            ),
+        };
+        client
+            .sender
+            .send(Notification::new(DidOpenTextDocument::METHOD.to_string(), open).into())
+            .unwrap();
+
+        // 開いた文書の診断
+        let Message::Notification(notification) = client.receiver.recv().unwrap() else {
+            panic!("expected notification");
+        };
+        let params: PublishDiagnosticsParams = serde_json::from_value(notification.params).unwrap();
+        assert_eq!(params.uri, uri);
+        assert_eq!(params.diagnostics.len(), 1);
+
+        // 差分の変更（「犬」を「本」に）
+        let change = DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier::new(uri.clone(), 2),
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: Some(Range::new(Position::new(2, 5), Position::new(2, 6))),
+                range_length: None,
+                text: "本".to_string(),
+            }],
+        };
+        client
+            .sender
+            .send(Notification::new(DidChangeTextDocument::METHOD.to_string(), change).into())
+            .unwrap();
+        let Message::Notification(notification) = client.receiver.recv().unwrap() else {
+            panic!("expected notification");
+        };
+        let params: PublishDiagnosticsParams = serde_json::from_value(notification.params).unwrap();
+        assert!(params.diagnostics.is_empty());
+
+        // 外字のホバー
+        let hover = HoverParams {
+            text_document_position_params: TextDocumentPositionParams::new(
+                TextDocumentIdentifier::new(uri),
+                Position::new(2, 12),
+            ),
+            work_done_progress_params: Default::default(),
+        };
+        let request = Request::new(RequestId::from(1), HoverRequest::METHOD.to_string(), hover);
+        client.sender.send(request.into()).unwrap();
+        let Message::Response(response) = client.receiver.recv().unwrap() else {
+            panic!("expected response");
+        };
+        let hover: Hover = serde_json::from_value(response.result.unwrap()).unwrap();
+        assert!(hover.range.is_some());
+
+        // 終了
+        let shutdown = Request::new(RequestId::from(2), "shutdown".to_string(), ());
+        client.sender.send(shutdown.into()).unwrap();
+        client.receiver.recv().unwrap();
+        client
+            .sender
+            .send(Notification::new("exit".to_string(), ()).into())
+            .unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_invalid_params() {
+        let (server, client) = Connection::memory();
+        let handle = std::thread::spawn(move || Server::new(server).run().unwrap());
+
+        // 不正な通知は無視される
+        let open = Notification::new(
+            DidOpenTextDocument::METHOD.to_string(),
+            serde_json::json!({ "textDocument": 1 }),
+        );
+        client.sender.send(open.into()).unwrap();
+
+        // 不正なリクエストには InvalidParams のエラーを応答する
+        let request = Request::new(
+            RequestId::from(1),
+            HoverRequest::METHOD.to_string(),
+            serde_json::json!({ "position": "x" }),
+        );
+        client.sender.send(request.into()).unwrap();
+        let Message::Response(response) = client.receiver.recv().unwrap() else {
+            panic!("expected response");
+        };
+        assert_eq!(response.id, RequestId::from(1));
+        assert_eq!(
+            response.error.unwrap().code,
+            lsp_server::ErrorCode::InvalidParams as i32
+        );
+
+        // その後のリクエストにも応答する
+        let hover = HoverParams {
+            text_document_position_params: TextDocumentPositionParams::new(
+                TextDocumentIdentifier::new(Uri::from_str("file:///tmp/none.txt").unwrap()),
+                Position::new(0, 0),
+            ),
+            work_done_progress_params: Default::default(),
+        };
+        let request = Request::new(RequestId::from(2), HoverRequest::METHOD.to_string(), hover);
+        client.sender.send(request.into()).unwrap();
+        let Message::Response(response) = client.receiver.recv().unwrap() else {
+            panic!("expected response");
+        };
+        assert_eq!(response.id, RequestId::from(2));
+        assert!(response.error.is_none());
+
+        let shutdown = Request::new(RequestId::from(3), "shutdown".to_string(), ());
+        client.sender.send(shutdown.into()).unwrap();
+        client.receiver.recv().unwrap();
+        client
+            .sender
+            .send(Notification::new("exit".to_string(), ()).into())
+            .unwrap();
+        handle.join().unwrap();
+    }
+}
@@ -0,0 +1,88 @@
+//! 見出しによる文書のアウトライン
+
+use aozora_core::incremental::IncrementalDocument;
+use aozora_core::outline::OutlineEntry;
+use aozora_core::span::Span;
+use lsp_types::{DocumentSymbol, SymbolKind};
+
+use crate::position::PositionMap;
+
+/// 本文の見出しを大・中・小の階層にまとめたアウトラインを返す
+///
+/// 階層は [`AozoraDocument::outline`] と同じです。
+/// 各見出しの範囲は、次の同じか上の階層の見出しの手前までです。
+///
+/// [`AozoraDocument::outline`]: aozora_core::document::AozoraDocument::outline
+pub fn document_symbols(document: &IncrementalDocument) -> Vec<DocumentSymbol> {
+    let text = document.text();
+    let map = PositionMap::new(text);
+    symbols(&document.document().outline(), text.len(), &map)
+}
+
+/// 見出しの階層をシンボルに変換
+fn symbols(entries: &[OutlineEntry], end: usize, map: &PositionMap) -> Vec<DocumentSymbol> {
+    entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let start = map.line_start(entry.line);
+            let line_end = start + map.line_text(entry.line).len();
+            let section_end = entries
+                .get(i + 1)
+                .map_or(end, |next| map.line_start(next.line));
+
+            #[allow(deprecated)]
+            DocumentSymbol {
+                name: heading_name(&entry.text),
+                detail: Some(entry.level.command_name().to_string()),
+                kind: SymbolKind::NAMESPACE,
+                tags: None,
+                deprecated: None,
+                range: map.range(Span::new(start, section_end)),
+                selection_range: map.range(Span::new(start, line_end)),
+                children: Some(symbols(&entry.children, section_end, map)),
+            }
+        })
+        .collect()
+}
+
+/// 見出しの表示名（空の場合は代わりの表記）
+fn heading_name(text: &str) -> String {
+    if text.is_empty() {
+        "（空の見出し）".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::Position;
+
+    #[test]
+    fn test_document_symbols() {
+        let text = "題名\n\n\
+                    第一章［＃「第一章」は大見出し］\n\
+                    ［＃中見出し］一［＃中見出し終わり］\n\
+                    本文\n\
+                    ［＃中見出し］二［＃中見出し終わり］\n\
+                    第二章［＃「第二章」は大見出し］\n";
+        let symbols = document_symbols(&IncrementalDocument::new(text));
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols[0].name, "第一章");
+        assert_eq!(symbols[0].detail.as_deref(), Some("大見出し"));
+        assert_eq!(symbols[0].kind, SymbolKind::NAMESPACE);
+        assert_eq!(symbols[0].range.start, Position::new(2, 0));
+        assert_eq!(symbols[0].range.end, Position::new(6, 0));
+
+        let children = symbols[0].children.as_ref().unwrap();
+        let names: Vec<&str> = children.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["一", "二"]);
+        assert_eq!(children[0].selection_range.start, Position::new(3, 0));
+        assert_eq!(children[0].range.end, Position::new(5, 0));
+
+        assert_eq!(symbols[1].name, "第二章");
+        assert!(symbols[1].children.as_ref().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,145 @@
+//! CLIの共通部品
+//!
+//! `aozora2` と `aozora2text` で共有する入出力と引数です。
+
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Cursor, Read, Write};
+use std::path::Path;
+
+use aozora_core::zip::{is_zip_file, read_first_txt_from_zip};
+use clap::Args;
+
+use crate::strip::{Newline, OutputEncoding, StripOptions, Unmappable};
+
+/// プレーンテキストの出力形式の引数
+#[derive(Args, Debug, Clone)]
+pub struct StripOutputArgs {
+    /// 出力エンコーディング
+    #[arg(long, value_enum, ignore_case = true, default_value_t = OutputEncoding::Utf8)]
+    pub encoding: OutputEncoding,
+
+    /// 改行コード
+    #[arg(long, value_enum, ignore_case = true, default_value_t = Newline::Lf)]
+    pub newline: Newline,
+
+    /// Shift_JISで表せない文字の扱い
+    #[arg(long, value_enum, ignore_case = true, value_name = "POLICY", default_value_t = Unmappable::Geta)]
+    pub unmappable: Unmappable,
+}
+
+impl StripOutputArgs {
+    /// 出力形式を設定したオプション
+    pub fn options(&self) -> StripOptions {
+        StripOptions::new()
+            .with_encoding(self.encoding)
+            .with_newline(self.newline)
+            .with_unmappable(self.unmappable)
+    }
+}
+
+/// 入力を開く
+///
+/// 入力ファイルを省略した場合は標準入力を使います。
+/// ZIPモードではアーカイブ内の最初のテキストファイルを読み込みます。
+pub fn open_input(input: Option<&Path>, zip: bool) -> io::Result<Box<dyn BufRead>> {
+    if zip {
+        // ZIPモード
+        let path = input.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "ZIP mode requires an input file",
+            )
+        })?;
+        return Ok(Box::new(Cursor::new(read_first_txt_from_zip(path)?)));
+    }
+
+    // 通常モード
+    match input {
+        Some(path) => {
+            let mut reader = BufReader::new(File::open(path)?);
+            // ZIPファイルの誤用を検出
+            if is_zip_file(reader.fill_buf()?) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "input appears to be a ZIP file; use --zip option",
+                ));
+            }
+            Ok(Box::new(reader))
+        }
+        None => Ok(Box::new(io::stdin().lock())),
+    }
+}
+
+/// 入力全体をバイト列として読み込む
+///
+/// 入力の開き方は [`open_input`] と同じです。
+pub fn read_input(input: Option<&Path>, zip: bool) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    open_input(input, zip)?.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// 出力に書き出す（省略時は標準出力）
+///
+/// ファイルへは同じディレクトリの一時ファイルに書き、`write` が成功してから
+/// 置き換えます。エラーの場合は一時ファイルを削除し、元のファイルは変更しません。
+pub fn write_output<F>(output: Option<&Path>, write: F) -> io::Result<()>
+where
+    F: FnOnce(&mut dyn Write) -> io::Result<()>,
+{
+    let Some(path) = output else {
+        let mut writer = BufWriter::new(io::stdout().lock());
+        write(&mut writer)?;
+        return writer.flush();
+    };
+
+    let file_name = path.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid output path: {}", path.display()),
+        )
+    })?;
+    let mut temp_name = OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp_path = path.with_file_name(temp_name);
+
+    let result = File::create(&temp_path).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        write(&mut writer)?;
+        writer.flush()?;
+        fs::rename(&temp_path, path)
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_output_keeps_file_on_error() {
+        let dir = std::env::temp_dir().join(format!("aozora2-cli-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("out.html");
+        fs::write(&path, "old").unwrap();
+
+        let error = write_output(Some(&path), |w| {
+            w.write_all(b"partial")?;
+            Err(io::Error::new(io::ErrorKind::InvalidData, "failed"))
+        })
+        .unwrap_err();
+        assert_eq!(error.to_string(), "failed");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old");
+
+        write_output(Some(&path), |w| w.write_all(b"new")).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        // 一時ファイルは残らない
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,56 @@
+//! ast サブコマンド
+//!
+//! 青空文庫形式を解析し、文書構造をJSONで出力
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use aozora_core::document::AozoraDocument;
+use clap::Args as ClapArgs;
+
+use aozora2::cli::read_input;
+
+/// ast サブコマンドの引数
+#[derive(ClapArgs, Debug)]
+pub struct Args {
+    /// 入力ファイル（省略時は標準入力）
+    pub input: Option<PathBuf>,
+
+    /// 出力ファイル（省略時は標準出力）
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// 入力をZIPファイルとして扱う
+    #[arg(short, long)]
+    pub zip: bool,
+
+    /// 整形して出力
+    #[arg(long)]
+    pub pretty: bool,
+}
+
+/// ast サブコマンドを実行
+pub fn run(args: Args) -> io::Result<()> {
+    let bytes = read_input(args.input.as_deref(), args.zip)?;
+
+    let input = aozora_core::encoding::decode_to_utf8(&bytes);
+    let document = AozoraDocument::parse(&input);
+
+    // JSON化
+    let mut output = if args.pretty {
+        serde_json::to_string_pretty(&document)
+    } else {
+        serde_json::to_string(&document)
+    }
+    .map_err(io::Error::other)?;
+    output.push('\n');
+
+    // 出力
+    match &args.output {
+        Some(path) => fs::write(path, &output)?,
+        None => io::stdout().write_all(output.as_bytes())?,
+    }
+
+    Ok(())
+}
@@ -0,0 +1,169 @@
+//! check サブコマンド
+//!
+//! 青空文庫形式の注記を検査し、問題のある箇所を報告
+
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use aozora_core::diagnostic::{check_document, CheckResult};
+use aozora_core::encoding::{decode, Decoded};
+use clap::Args as ClapArgs;
+use serde_json::json;
+
+use aozora2::cli::read_input;
+
+/// これより低い確かさで判定したエンコーディングは警告する
+const LOW_CONFIDENCE: f32 = 0.5;
+
+/// check サブコマンドの引数
+#[derive(ClapArgs, Debug)]
+pub struct Args {
+    /// 入力ファイル（省略時は標準入力）
+    pub input: Option<PathBuf>,
+
+    /// 入力をZIPファイルとして扱う
+    #[arg(short, long)]
+    pub zip: bool,
+
+    /// 出力形式（text または json）
+    #[arg(long, default_value = "text")]
+    pub format: String,
+}
+
+/// check サブコマンドを実行
+///
+/// エラーが見つかった場合は `false` を返します。
+pub fn run(args: Args) -> io::Result<bool> {
+    let format = args.format.to_lowercase();
+    if format != "text" && format != "json" {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("unknown format: {} (expected text or json)", args.format),
+        ));
+    }
+
+    let bytes = read_input(args.input.as_deref(), args.zip)?;
+
+    let decoded = decode(&bytes);
+    let result = check_document(&decoded.text);
+
+    let name = args
+        .input
+        .as_ref()
+        .map_or_else(|| "<stdin>".to_string(), |p| p.display().to_string());
+    let report = if format == "json" {
+        format_json(&name, &decoded, &result)
+    } else {
+        format_text(&name, &decoded, &result)
+    };
+
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(report.as_bytes())?;
+    stdout.flush()?;
+
+    Ok(!result.has_errors())
+}
+
+/// 人間向けの形式（`ファイル:行:桁: 重大度[コード]: メッセージ`）
+fn format_text(name: &str, decoded: &Decoded, result: &CheckResult) -> String {
+    let mut output = String::new();
+    let encoding = decoded.encoding.name();
+    if decoded.lossy {
+        output.push_str(&format!(
+            "{name}: warning[encoding]: invalid {encoding} bytes were replaced with U+FFFD\n"
+        ));
+    } else if decoded.confidence < LOW_CONFIDENCE {
+        output.push_str(&format!(
+            "{name}: warning[encoding]: guessed {encoding} with low confidence ({:.2})\n",
+            decoded.confidence
+        ));
+    }
+    for diagnostic in &result.diagnostics {
+        output.push_str(&format!("{name}:{diagnostic}\n"));
+    }
+    output.push_str(&format!(
+        "{name}: {} error(s), {} warning(s)\n",
+        result.error_count(),
+        result.warning_count()
+    ));
+    output
+}
+
+/// JSON形式（行・桁は1始まり、offsetは入力のUTF-8バイト位置）
+fn format_json(name: &str, decoded: &Decoded, result: &CheckResult) -> String {
+    let diagnostics: Vec<_> = result
+        .diagnostics
+        .iter()
+        .map(|d| {
+            json!({
+                "severity": d.severity.as_str(),
+                "code": d.code.as_str(),
+                "message": d.message,
+                "line": d.start.line + 1,
+                "column": d.start.column + 1,
+                "end_line": d.end.line + 1,
+                "end_column": d.end.column + 1,
+                "offset": { "start": d.span.start, "end": d.span.end },
+            })
+        })
+        .collect();
+    let report = json!({
+        "file": name,
+        "encoding": {
+            "name": decoded.encoding.name(),
+            "bom": decoded.bom,
+            "confidence": decoded.confidence,
+            "lossy": decoded.lossy,
+        },
+        "errors": result.error_count(),
+        "warnings": result.warning_count(),
+        "diagnostics": diagnostics,
+    });
+    format!("{report:#}\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_text() {
+        let decoded = decode("本文\n漢字《かんじ".as_bytes());
+        let result = aozora_core::diagnostic::check_text(&decoded.text);
+        assert_eq!(
+            format_text("a.txt", &decoded, &result),
+            "a.txt:2:3: error[unterminated-ruby]: ruby is not closed with 》\n\
+             a.txt: 1 error(s), 0 warning(s)\n"
+        );
+    }
+
+    #[test]
+    fn test_format_json() {
+        let (bytes, _, _) = encoding_rs::EUC_JP.encode("本文［＃不明］");
+        let decoded = decode(&bytes);
+        let result = aozora_core::diagnostic::check_text(&decoded.text);
+        let value: serde_json::Value =
+            serde_json::from_str(&format_json("a.txt", &decoded, &result)).unwrap();
+        assert_eq!(value["file"], "a.txt");
+        assert_eq!(value["encoding"]["name"], "EUC-JP");
+        assert_eq!(value["encoding"]["lossy"], false);
+        assert_eq!(value["errors"], 0);
+        assert_eq!(value["warnings"], 1);
+        let diagnostic = &value["diagnostics"][0];
+        assert_eq!(diagnostic["code"], "unknown-command");
+        assert_eq!(diagnostic["severity"], "warning");
+        assert_eq!(diagnostic["line"], 1);
+        assert_eq!(diagnostic["column"], 3);
+        assert_eq!(diagnostic["offset"]["start"], 6);
+    }
+
+    #[test]
+    fn test_format_text_lossy_encoding() {
+        let decoded = decode(&[0x96, 0x7B, 0x95, 0xB6, 0x82]);
+        let result = aozora_core::diagnostic::check_text(&decoded.text);
+        let report = format_text("a.txt", &decoded, &result);
+        assert!(report.starts_with(
+            "a.txt: warning[encoding]: invalid Shift_JIS bytes were replaced with U+FFFD\n"
+        ));
+    }
+}
@@ -0,0 +1,112 @@
+//! fmt サブコマンド
+//!
+//! 青空文庫形式の表記を正規形に整形
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use clap::Args as ClapArgs;
+
+use aozora2::cli::read_input;
+use aozora2::format;
+
+/// fmt サブコマンドの引数
+#[derive(ClapArgs, Debug)]
+pub struct Args {
+    /// 整形するファイル（省略時は標準入力を整形して標準出力へ）
+    pub inputs: Vec<PathBuf>,
+
+    /// ファイルを書き換えず、整形が必要なファイルを報告する
+    #[arg(long)]
+    pub check: bool,
+}
+
+/// fmt サブコマンドを実行
+///
+/// `--check` で整形が必要なファイルが見つかった場合は `false` を返します。
+/// 整形できないファイルがあれば、どのファイルも書き換えずにエラーを返します。
+pub fn run(args: Args) -> io::Result<bool> {
+    if args.inputs.is_empty() {
+        let input = read_input(None, false)?;
+        let output = format::convert(&input)?;
+        if args.check {
+            return Ok(report("<stdin>", input == output));
+        }
+        io::stdout().write_all(&output)?;
+        return Ok(true);
+    }
+
+    // すべてのファイルを整形してから書き込み、途中で失敗しても一部だけ書き換えない
+    let mut files = Vec::new();
+    let mut failures = 0;
+    for path in &args.inputs {
+        match read_input(Some(path), false).and_then(|input| {
+            let output = format::convert(&input)?;
+            Ok((input, output))
+        }) {
+            Ok((input, output)) => files.push((path, input, output)),
+            Err(e) => {
+                eprintln!("{}: {e}", path.display());
+                failures += 1;
+            }
+        }
+    }
+    if failures > 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{failures} file(s) could not be formatted; no files were changed"),
+        ));
+    }
+
+    let mut formatted = true;
+    for (path, input, output) in files {
+        if args.check {
+            formatted &= report(&path.display().to_string(), input == output);
+        } else if input != output {
+            // 整形済みのファイルは書き換えない
+            fs::write(path, &output)
+                .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))?;
+        }
+    }
+
+    Ok(formatted)
+}
+
+/// `--check` の結果を報告
+fn report(name: &str, formatted: bool) -> bool {
+    if !formatted {
+        println!("{name}: needs formatting");
+    }
+    formatted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_keeps_files_on_error() {
+        let dir = std::env::temp_dir().join(format!("aozora2-fmt-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let valid = dir.join("a.txt");
+        let invalid = dir.join("b.txt");
+        fs::write(&valid, "題名\n\n［＃2字下げ］本文\n").unwrap();
+        fs::write(&invalid, b"\x82\xa0\x82").unwrap();
+
+        let args = Args {
+            inputs: vec![valid.clone(), invalid],
+            check: false,
+        };
+        let error = run(args).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "1 file(s) could not be formatted; no files were changed"
+        );
+        assert_eq!(
+            fs::read_to_string(&valid).unwrap(),
+            "題名\n\n［＃2字下げ］本文\n"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -3,14 +3,16 @@
 //! 青空文庫形式をHTMLに変換
 
 use std::fs;
-use std::io::{self, Read, Write};
+use std::io::{self, Write};
 use std::path::PathBuf;
 
-use aozora_core::zip::{is_zip_file, read_first_txt_from_zip};
+use aozora_core::encoding::{self, TextEncoding};
 use clap::Args as ClapArgs;
-use encoding_rs::SHIFT_JIS;
 
-use aozora2::html::{self, RenderOptions};
+use aozora2::cli::{open_input, write_output};
+use aozora2::html::{
+    Charset, CorrectionDisplay, HtmlProfile, HtmlRenderer, RenderOptions, Template,
+};
 
 /// html サブコマンドの引数
 #[derive(ClapArgs, Debug)]
@@ -46,47 +48,39 @@ pub struct Args {
     #[arg(long)]
     pub title: Option<String>,
 
-    /// 出力エンコーディング（utf-8 または shift_jis）
-    #[arg(long, default_value = "shift_jis")]
-    pub encoding: String,
+    /// 出力エンコーディング
+    #[arg(long, value_enum, ignore_case = true, default_value_t = Charset::ShiftJis)]
+    pub encoding: Charset,
+
+    /// HTMLの形式
+    #[arg(long, value_enum, ignore_case = true, default_value_t = HtmlProfile::Xhtml)]
+    pub profile: HtmlProfile,
+
+    /// 校正注記の表示
+    #[arg(long, value_enum, ignore_case = true, value_name = "MODE", default_value_t = CorrectionDisplay::Note)]
+    pub correction: CorrectionDisplay,
+
+    /// ページのテンプレートファイル（`{{body}}` などの置き換え）
+    #[arg(long, value_name = "FILE")]
+    pub template: Option<PathBuf>,
+
+    /// 本文（main_textの中身）のみを出力（--toc とは併用不可）
+    #[arg(long, conflicts_with = "toc")]
+    pub fragment: bool,
+
+    /// 見出しの目次を出力
+    #[arg(long)]
+    pub toc: bool,
+
+    /// 出力エンコーディングで表せない文字があればエラーにする
+    #[arg(long)]
+    pub strict: bool,
 }
 
 /// html サブコマンドを実行
+///
+/// 入力を1行ずつ変換し、変換した部分から順に出力します。
 pub fn run(args: Args) -> io::Result<()> {
-    // 入力読み込み
-    let bytes = if args.zip {
-        // ZIPモード
-        let path = args.input.as_ref().ok_or_else(|| {
-            io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "ZIP mode requires an input file",
-            )
-        })?;
-        read_first_txt_from_zip(path)?
-    } else {
-        // 通常モード
-        match &args.input {
-            Some(path) => {
-                let bytes = fs::read(path)?;
-                // ZIPファイルの誤用を検出
-                if is_zip_file(&bytes) {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidInput,
-                        "input appears to be a ZIP file; use --zip option",
-                    ));
-                }
-                bytes
-            }
-            None => {
-                let mut buf = Vec::new();
-                io::stdin().read_to_end(&mut buf)?;
-                buf
-            }
-        }
-    };
-
-    let input = aozora_core::encoding::decode_to_utf8(&bytes);
-
     // オプション設定
     let css_files: Vec<String> = args
         .css_files
@@ -94,11 +88,19 @@ pub fn run(args: Args) -> io::Result<()> {
         .map(|s| s.trim().to_string())
         .collect();
 
+    let charset = args.encoding;
+
     let options = RenderOptions::new()
+        .with_charset(charset)
+        .with_profile(args.profile)
+        .with_fragment(args.fragment)
+        .with_toc(args.toc)
+        .with_strict(args.strict)
         .with_gaiji_dir(&args.gaiji_dir)
         .with_css_files(css_files)
         .with_jisx0213(args.use_jisx0213)
-        .with_unicode(args.use_unicode);
+        .with_unicode(args.use_unicode)
+        .with_correction_display(args.correction);
 
     let options = if let Some(title) = &args.title {
         options.with_title(title)
@@ -106,26 +108,107 @@ pub fn run(args: Args) -> io::Result<()> {
         options
     };
 
-    // 変換
-    let output_html = html::convert(&input, &options);
-
-    // エンコーディング変換
-    let output_bytes = if args.encoding.to_lowercase() == "shift_jis" {
-        let (encoded, _, _) = SHIFT_JIS.encode(&output_html);
-        encoded.into_owned()
-    } else {
-        output_html.into_bytes()
+    let options = match &args.template {
+        Some(path) => options.with_template(Template::new(&fs::read_to_string(path)?)?),
+        None => options,
     };
 
-    // 出力
-    match &args.output {
-        Some(path) => {
-            fs::write(path, &output_bytes)?;
+    let input = open_input(args.input.as_deref(), args.zip)?;
+
+    // 変換（エンコーディング変換しながら出力）
+    let mut renderer = HtmlRenderer::new(options);
+    let mut written_as_reference = Vec::new();
+    write_output(args.output.as_deref(), |output| {
+        if charset == Charset::ShiftJis {
+            let mut writer = ShiftJisWriter::new(output, args.strict);
+            renderer.render_stream(input, &mut writer)?;
+            written_as_reference = writer.unmappable;
+            Ok(())
+        } else {
+            renderer.render_stream(input, output)
         }
-        None => {
-            io::stdout().write_all(&output_bytes)?;
+    })?;
+
+    // 表せなかった文字を報告
+    let name = charset.name();
+    for unmappable in renderer.unmappable_chars() {
+        let rendered_as = match &unmappable.jis_code {
+            Some(jis) => format!("gaiji image {jis}"),
+            None => "numeric character reference".to_string(),
+        };
+        eprintln!(
+            "warning: line {}: character {} (U+{:04X}) is not in {name}; rendered as {rendered_as}",
+            unmappable.line + 1,
+            unmappable.ch,
+            u32::from(unmappable.ch)
+        );
+    }
+    for c in written_as_reference {
+        eprintln!(
+            "warning: character {c} (U+{:04X}) is not in {name}; written as numeric character reference",
+            u32::from(c)
+        );
+    }
+    Ok(())
+}
+
+/// UTF-8の出力をShift_JISに変換して書き出すライター
+///
+/// Shift_JISで表せない文字は数値文字参照にします（`strict` ならエラー）。
+struct ShiftJisWriter<W: Write> {
+    inner: W,
+    /// 文字の途中で区切られたUTF-8のバイト列
+    pending: Vec<u8>,
+    /// Shift_JISで表せない文字をエラーにするか
+    strict: bool,
+    /// 数値文字参照にした文字
+    unmappable: Vec<char>,
+}
+
+impl<W: Write> ShiftJisWriter<W> {
+    fn new(inner: W, strict: bool) -> Self {
+        Self {
+            inner,
+            pending: Vec::new(),
+            strict,
+            unmappable: Vec::new(),
         }
     }
+}
 
-    Ok(())
+impl<W: Write> Write for ShiftJisWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        let valid = match std::str::from_utf8(&self.pending) {
+            Ok(s) => s.len(),
+            // 末尾の不完全な文字は次の書き込みまで保留
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        };
+        let text = std::str::from_utf8(&self.pending[..valid]).expect("validated above");
+        let (strict, unmappable) = (self.strict, &mut self.unmappable);
+        let mut encoded = Vec::with_capacity(text.len());
+        encoding::encode(text, TextEncoding::ShiftJis, &mut encoded, |c| {
+            if strict {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "character {c} (U+{:04X}) cannot be encoded in Shift_JIS",
+                        u32::from(c)
+                    ),
+                ));
+            }
+            if !unmappable.contains(&c) {
+                unmappable.push(c);
+            }
+            Ok(format!("&#{};", u32::from(c)))
+        })?;
+        self.inner.write_all(&encoded)?;
+        self.pending.drain(..valid);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
 }
@@ -1,4 +1,7 @@
 //! CLI サブコマンド
 
+pub mod ast;
+pub mod check;
+pub mod fmt;
 pub mod html;
 pub mod strip;
@@ -2,13 +2,12 @@
 //!
 //! 青空文庫形式をプレーンテキストに変換
 
-use std::fs;
-use std::io::{self, Read, Write};
+use std::io;
 use std::path::PathBuf;
 
-use aozora_core::zip::{is_zip_file, read_first_txt_from_zip};
 use clap::Args as ClapArgs;
 
+use aozora2::cli::{open_input, write_output, StripOutputArgs};
 use aozora2::strip;
 
 /// strip サブコマンドの引数
@@ -24,50 +23,28 @@ pub struct Args {
     /// 入力をZIPファイルとして扱う
     #[arg(short, long)]
     pub zip: bool,
+
+    /// 改ページ・改丁・改見開き・改段の注記の代わりに出力する文字列
+    #[arg(long, value_name = "TEXT")]
+    pub break_separator: Option<String>,
+
+    #[command(flatten)]
+    pub output_format: StripOutputArgs,
+
+    /// 本文の前に見出しの目次を出力
+    #[arg(long)]
+    pub toc: bool,
 }
 
 /// strip サブコマンドを実行
+///
+/// 入力を1行ずつ変換し、変換した部分から順に出力します。
 pub fn run(args: Args) -> io::Result<()> {
-    // 入力読み込み
-    let bytes = if args.zip {
-        // ZIPモード
-        let path = args.input.as_ref().ok_or_else(|| {
-            io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "ZIP mode requires an input file",
-            )
-        })?;
-        read_first_txt_from_zip(path)?
-    } else {
-        // 通常モード
-        match &args.input {
-            Some(path) => {
-                let bytes = fs::read(path)?;
-                // ZIPファイルの誤用を検出
-                if is_zip_file(&bytes) {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidInput,
-                        "input appears to be a ZIP file; use --zip option",
-                    ));
-                }
-                bytes
-            }
-            None => {
-                let mut buf = Vec::new();
-                io::stdin().read_to_end(&mut buf)?;
-                buf
-            }
-        }
-    };
-
-    // 変換
-    let output = strip::convert(&bytes);
-
-    // 出力
-    match &args.output {
-        Some(path) => fs::write(path, &output)?,
-        None => io::stdout().write_all(output.as_bytes())?,
-    }
+    let mut options = args.output_format.options().with_toc(args.toc);
+    options.break_separator = args.break_separator;
 
-    Ok(())
+    let input = open_input(args.input.as_deref(), args.zip)?;
+    write_output(args.output.as_deref(), |output| {
+        strip::convert_stream(input, output, &options)
+    })
 }
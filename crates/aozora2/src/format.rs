@@ -0,0 +1,290 @@
+//! 青空文庫形式の整形（fmt）
+//!
+//! 入力ごとに揺れのある表記を正規形にそろえます。
+//!
+//! - ルビの `｜` は親文字の範囲が曖昧な場合のみ付ける
+//! - 注記内の数字は全角（`［＃2字下げ］` → `［＃２字下げ］`）
+//! - 外字注記の説明は `「…」、第3水準1-85-7` 形式
+//! - 行末の空白を除去
+//!
+//! 本文以外（表題・記号の説明・底本情報）は行末の空白の除去のみ行います。
+//! 書き換えは元の行の該当部分だけに行い、解析結果が変わる書き換えはしません。
+
+use std::io;
+
+use aozora_core::document::body_line_range;
+use aozora_core::encoding;
+use aozora_core::gaiji::canonicalize_description;
+use aozora_core::parser::{parse, parse_command};
+use aozora_core::token::Token;
+use aozora_core::tokenizer::{tokenize, tokenize_with_spans};
+
+/// 青空文庫形式のバイト列を整形
+///
+/// 入力のエンコーディング（[`encoding::decode`] で判定）、BOM、改行コードを保って出力します。
+///
+/// 入力に不正なバイト列がある場合や、整形結果を元のエンコーディングで表せない場合は
+/// 内容を失わないよう [`io::ErrorKind::InvalidData`] のエラーを返します。
+///
+/// # Examples
+///
+/// ```
+/// let input = "題名\r\n著者\r\n\r\n［＃2字下げ］本文  \r\n";
+/// let output = aozora2::format::convert(input.as_bytes()).unwrap();
+/// assert_eq!(output, "題名\r\n著者\r\n\r\n［＃２字下げ］本文\r\n".as_bytes());
+/// ```
+pub fn convert(input: &[u8]) -> io::Result<Vec<u8>> {
+    let decoded = encoding::decode(input);
+    let name = decoded.encoding.name();
+    if decoded.lossy {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("input contains invalid {name} bytes"),
+        ));
+    }
+    let formatted = format_text(&decoded.text);
+
+    let mut output = Vec::with_capacity(input.len());
+    if decoded.bom {
+        output.extend_from_slice(decoded.encoding.bom());
+    }
+    encoding::encode(&formatted, decoded.encoding, &mut output, |c| {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "character {c} (U+{:04X}) cannot be encoded in {name}",
+                u32::from(c)
+            ),
+        ))
+    })?;
+    Ok(output)
+}
+
+/// 青空文庫形式の文字列を整形
+///
+/// 改行コード（`\r\n` / `\n`）と末尾の改行の有無は行ごとに入力に合わせます。
+///
+/// # Examples
+///
+/// ```
+/// let input = "題名\n著者\n\n｜東京《とうきょう》へ  \n";
+/// assert_eq!(
+///     aozora2::format::format_text(input),
+///     "題名\n著者\n\n東京《とうきょう》へ\n"
+/// );
+/// ```
+pub fn format_text(text: &str) -> String {
+    let lines: Vec<(&str, &str)> = text.split_inclusive('\n').map(split_newline).collect();
+    let contents: Vec<&str> = lines.iter().map(|(line, _)| *line).collect();
+    let body = body_line_range(&contents);
+
+    let mut output = String::with_capacity(text.len());
+    for (i, (line, newline)) in lines.iter().enumerate() {
+        if body.contains(&i) {
+            output.push_str(&format_line(line));
+        } else {
+            output.push_str(line.trim_end());
+        }
+        output.push_str(newline);
+    }
+    output
+}
+
+/// 行を内容と改行コードに分ける
+fn split_newline(line: &str) -> (&str, &str) {
+    if let Some(content) = line.strip_suffix("\r\n") {
+        (content, "\r\n")
+    } else if let Some(content) = line.strip_suffix('\n') {
+        (content, "\n")
+    } else {
+        (line, "")
+    }
+}
+
+/// 本文の1行を整形
+fn format_line(line: &str) -> String {
+    let line = line.trim_end();
+    let mut output = String::with_capacity(line.len());
+    for token in tokenize_with_spans(line) {
+        let source = &line[token.span.start..token.span.end];
+        match &token.value {
+            // 閉じていない注記は書き換えない
+            Token::Gaiji { description } if source.ends_with('］') => {
+                output.push_str(&format!("※［＃{}］", canonicalize_description(description)));
+            }
+            Token::Command { content } if source.ends_with('］') => {
+                let zenkaku = zenkaku_digits(content);
+                if parse_command(&zenkaku) == parse_command(content) {
+                    output.push_str(&format!("［＃{zenkaku}］"));
+                } else {
+                    output.push_str(source);
+                }
+            }
+            _ => output.push_str(source),
+        }
+    }
+    omit_ruby_prefixes(output)
+}
+
+/// 注記内の「」の外の半角数字を全角にする
+fn zenkaku_digits(content: &str) -> String {
+    let mut depth = 0usize;
+    content
+        .chars()
+        .map(|c| match c {
+            '「' => {
+                depth += 1;
+                c
+            }
+            '」' => {
+                depth = depth.saturating_sub(1);
+                c
+            }
+            '0'..='9' if depth == 0 => {
+                char::from_u32(c as u32 - '0' as u32 + '０' as u32).unwrap_or(c)
+            }
+            _ => c,
+        })
+        .collect()
+}
+
+/// 取り除いても親文字が変わらないルビの `｜` を取り除く
+fn omit_ruby_prefixes(mut line: String) -> String {
+    let nodes = parse(&tokenize(&line));
+    let prefixes: Vec<usize> = tokenize_with_spans(&line)
+        .iter()
+        .filter(|token| matches!(token.value, Token::PrefixedRuby { .. }))
+        .map(|token| token.span.start)
+        .filter(|&start| line[start..].starts_with('｜'))
+        .collect();
+
+    // 後ろから取り除き、前の位置をずらさない
+    for start in prefixes.into_iter().rev() {
+        let mut candidate = line.clone();
+        candidate.replace_range(start..start + '｜'.len_utf8(), "");
+        if parse(&tokenize(&candidate)) == nodes {
+            line = candidate;
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::SHIFT_JIS;
+
+    #[test]
+    fn test_format_body() {
+        let input = "題名\n著者\n\n\
+                     ［＃ここから2字下げ］  \n\
+                     ｜東京《とうきょう》と日本｜東京《とうきょう》\n\
+                     ※［＃「てへん＋劣」、第３水準１－８４－７７］《むし》る\u{3000}\n\
+                     ［＃ここで字下げ終わり］\n\
+                     \n\
+                     底本：某文庫  \n";
+        let expected = "題名\n著者\n\n\
+                        ［＃ここから２字下げ］\n\
+                        東京《とうきょう》と日本｜東京《とうきょう》\n\
+                        ※［＃「てへん＋劣」、第3水準1-84-77］《むし》る\n\
+                        ［＃ここで字下げ終わり］\n\
+                        \n\
+                        底本：某文庫\n";
+        assert_eq!(format_text(input), expected);
+        assert_eq!(format_text(expected), expected);
+    }
+
+    #[test]
+    fn test_format_keeps_header_and_legend() {
+        let input = "題名\n著者\n\n\
+                     -------------------------------------------------------\n\
+                     《》：ルビ\n\
+                     ｜：ルビの付く文字列の始まりを特定する記号\n\
+                     -------------------------------------------------------\n\
+                     本文\n";
+        assert_eq!(format_text(input), input);
+    }
+
+    #[test]
+    fn test_format_keeps_unrewritable_lines() {
+        // 閉じていない注記
+        let input = "題名\n著者\n\n本文［＃2字下げ\n";
+        assert_eq!(format_text(input), input);
+
+        // 注記付き範囲（外字注記のみ正規化）
+        let input = "題名\n著者\n\n\
+                     ［＃注記付き］※［＃「丸印」、u+25cb］［＃「（まる）」の注記付き終わり］\n";
+        let expected = "題名\n著者\n\n\
+                        ［＃注記付き］※［＃「丸印」、U+25CB］［＃「（まる）」の注記付き終わり］\n";
+        assert_eq!(format_text(input), expected);
+    }
+
+    #[test]
+    fn test_format_keeps_other_notation() {
+        // 前方参照の注記の位置、「」内の数字、書かれている水準は変えない
+        let input = "題名\n著者\n\n\
+                     猫である。［＃「猫」に傍点］12［＃「12」は縦中横］\n\
+                     ※［＃「亜」、第1水準1-16-1］\n";
+        assert_eq!(format_text(input), input);
+    }
+
+    #[test]
+    fn test_format_keeps_mixed_newlines() {
+        let input = "題名\r\n著者\n\r\n［＃2字下げ］本文 \n末尾";
+        let expected = "題名\r\n著者\n\r\n［＃２字下げ］本文\n末尾";
+        assert_eq!(format_text(input), expected);
+    }
+
+    #[test]
+    fn test_convert_keeps_encoding() {
+        use aozora_core::encoding::{decode, encode, TextEncoding};
+
+        let input =
+            "吾輩は猫である\r\n夏目漱石\r\n\r\n［＃3字下げ］吾輩《わがはい》は猫である。①\r\n";
+        let expected =
+            "吾輩は猫である\r\n夏目漱石\r\n\r\n［＃３字下げ］吾輩《わがはい》は猫である。①\r\n";
+        for (encoding, bom) in [
+            (TextEncoding::Utf8, false),
+            (TextEncoding::Utf8, true),
+            (TextEncoding::Utf16Le, false),
+            (TextEncoding::Utf16Le, true),
+            (TextEncoding::Utf16Be, true),
+            (TextEncoding::Cp932, false),
+            (TextEncoding::EucJp, false),
+            (TextEncoding::Iso2022Jp, false),
+        ] {
+            let bytes = |text: &str| {
+                let mut bytes = if bom {
+                    encoding.bom().to_vec()
+                } else {
+                    Vec::new()
+                };
+                encode(text, encoding, &mut bytes, |c| panic!("{c}")).unwrap();
+                bytes
+            };
+            let name = encoding.name();
+            let output = convert(&bytes(input)).unwrap();
+            assert_eq!(output, bytes(expected), "{name} (BOM: {bom})");
+            let decoded = decode(&output);
+            assert_eq!((decoded.encoding, decoded.bom), (encoding, bom), "{name}");
+            // 整形済みなら同じバイト列（fmt --check で差分なし）
+            assert_eq!(convert(&output).unwrap(), output, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_convert_shift_jis() {
+        let (input, _, _) = SHIFT_JIS.encode("題名\r\n\r\n［＃3字下げ］本文\r\n");
+        let (expected, _, _) = SHIFT_JIS.encode("題名\r\n\r\n［＃３字下げ］本文\r\n");
+        assert_eq!(convert(&input).unwrap(), expected.into_owned());
+    }
+
+    #[test]
+    fn test_convert_rejects_invalid_bytes() {
+        let mut input = SHIFT_JIS.encode("題名\r\n\r\n本文").0.into_owned();
+        input.push(0x82);
+        let error = convert(&input).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "input contains invalid Shift_JIS bytes");
+    }
+}
@@ -136,12 +136,7 @@ impl BlockManager {
 
     /// 見出しIDを生成
     pub fn generate_midashi_id(&mut self, level: MidashiLevel) -> u32 {
-        let increment = match level {
-            MidashiLevel::O => 100,
-            MidashiLevel::Naka => 10,
-            MidashiLevel::Ko => 1,
-        };
-        self.midashi_id_counter += increment;
+        self.midashi_id_counter += level.id_step();
         self.midashi_id_counter
     }
 
@@ -4,9 +4,10 @@
 //! ドキュメント構造を生成します。
 
 use aozora_core::document::HeaderInfo;
+use aozora_core::outline::OutlineEntry;
 
 use super::node_renderer::UnconvertedGaiji;
-use super::options::RenderOptions;
+use super::options::{HtmlProfile, RenderOptions};
 use super::presentation::html_escape;
 
 /// 青空文庫パブリッシャー名
@@ -25,18 +26,36 @@ impl<'a> DocumentRenderer<'a> {
 
     /// HTMLヘッダーを出力
     pub fn render_html_head(&self, output: &mut String, header_info: &HeaderInfo) {
-        // XML宣言とDOCTYPE
-        output.push_str("<?xml version=\"1.0\" encoding=\"Shift_JIS\"?>\r\n");
-        output.push_str("<!DOCTYPE html PUBLIC \"-//W3C//DTD XHTML 1.1//EN\"\r\n");
-        output.push_str("    \"http://www.w3.org/TR/xhtml11/DTD/xhtml11.dtd\">\r\n");
-        output.push_str("<html xmlns=\"http://www.w3.org/1999/xhtml\" xml:lang=\"ja\" >\r\n");
-        output.push_str("<head>\r\n");
-
-        // メタ情報
-        output.push_str(
-            "\t<meta http-equiv=\"Content-Type\" content=\"text/html;charset=Shift_JIS\" />\r\n",
-        );
-        output.push_str("\t<meta http-equiv=\"content-style-type\" content=\"text/css\" />\r\n");
+        let charset = self.options.charset.name();
+        let profile = self.options.profile;
+
+        match profile {
+            HtmlProfile::Xhtml => {
+                // XML宣言とDOCTYPE
+                output.push_str(&format!(
+                    "<?xml version=\"1.0\" encoding=\"{charset}\"?>\r\n"
+                ));
+                output.push_str("<!DOCTYPE html PUBLIC \"-//W3C//DTD XHTML 1.1//EN\"\r\n");
+                output.push_str("    \"http://www.w3.org/TR/xhtml11/DTD/xhtml11.dtd\">\r\n");
+                output
+                    .push_str("<html xmlns=\"http://www.w3.org/1999/xhtml\" xml:lang=\"ja\" >\r\n");
+                output.push_str("<head>\r\n");
+
+                // メタ情報
+                output.push_str(&format!(
+                    "\t<meta http-equiv=\"Content-Type\" content=\"text/html;charset={charset}\" />\r\n"
+                ));
+                output.push_str(
+                    "\t<meta http-equiv=\"content-style-type\" content=\"text/css\" />\r\n",
+                );
+            }
+            HtmlProfile::Html5 => {
+                output.push_str("<!DOCTYPE html>\r\n");
+                output.push_str("<html lang=\"ja\">\r\n");
+                output.push_str("<head>\r\n");
+                output.push_str(&format!("\t<meta charset=\"{charset}\" />\r\n"));
+            }
+        }
 
         // CSSリンク
         for css in &self.options.css_files {
@@ -49,14 +68,16 @@ impl<'a> DocumentRenderer<'a> {
         let html_title = if let Some(title) = &self.options.title {
             html_escape(title)
         } else {
-            header_info.html_title()
+            html_escape(&header_info.html_title())
         };
         output.push_str(&format!("\t<title>{}</title>\r\n", html_title));
 
         // jQuery
-        output.push_str(
-            "\t<script type=\"text/javascript\" src=\"../../jquery-1.4.2.min.js\"></script>\r\n",
-        );
+        if profile == HtmlProfile::Xhtml {
+            output.push_str(
+                "\t<script type=\"text/javascript\" src=\"../../jquery-1.4.2.min.js\"></script>\r\n",
+            );
+        }
 
         // Dublin Core メタデータ
         output
@@ -144,6 +165,18 @@ impl<'a> DocumentRenderer<'a> {
         output.push_str("<br />\r\n<br />\r\n</div>\r\n");
     }
 
+    /// 見出しの目次セクションを出力
+    ///
+    /// 見出しがなければ何も出力しません。
+    pub fn render_toc(&self, output: &mut String, outline: &[OutlineEntry]) {
+        if outline.is_empty() {
+            return;
+        }
+        output.push_str("<div class=\"toc\">\r\n");
+        render_toc_list(output, outline);
+        output.push_str("</div>\r\n");
+    }
+
     /// HTMLフッターを出力
     pub fn render_html_foot(&self, output: &mut String) {
         output.push_str("</body>\r\n");
@@ -193,10 +226,15 @@ impl<'a> DocumentRenderer<'a> {
         output.push_str("●表記について<br />\r\n");
         output.push_str("<ul>\r\n");
 
-        // XHTML1.1準拠
-        output.push_str(
-            "\t<li>このファイルは W3C 勧告 XHTML1.1 にそった形式で作成されています。</li>\r\n",
-        );
+        // 準拠する形式
+        output.push_str(match self.options.profile {
+            HtmlProfile::Xhtml => {
+                "\t<li>このファイルは W3C 勧告 XHTML1.1 にそった形式で作成されています。</li>\r\n"
+            }
+            HtmlProfile::Html5 => {
+                "\t<li>このファイルは HTML Living Standard にそった形式で作成されています。</li>\r\n"
+            }
+        });
 
         // 注記を使用した場合
         if has_notes {
@@ -252,7 +290,12 @@ impl<'a> DocumentRenderer<'a> {
     }
 
     /// 図書カードセクションを出力
+    ///
+    /// 図書カードへのリンクはjQueryを使うスクリプトで動くため、XHTMLの形式でのみ出力します。
     pub fn render_card_section(&self, output: &mut String) {
+        if self.options.profile != HtmlProfile::Xhtml {
+            return;
+        }
         output.push_str("<div id=\"card\">\r\n");
         output.push_str("<hr />\r\n");
         output.push_str("<br />\r\n");
@@ -264,10 +307,13 @@ impl<'a> DocumentRenderer<'a> {
     }
 
     /// main_text開始タグを出力
+    ///
+    /// XHTMLの形式では、スクリプト（`contents.js`）が使う `<div id="contents">` も出力します。
     pub fn render_main_text_start(&self, output: &mut String) {
-        output.push_str(
-            "<div id=\"contents\" style=\"display:none\"></div><div class=\"main_text\">",
-        );
+        if self.options.profile == HtmlProfile::Xhtml {
+            output.push_str("<div id=\"contents\" style=\"display:none\"></div>");
+        }
+        output.push_str("<div class=\"main_text\">");
     }
 
     /// main_text終了タグを出力
@@ -275,3 +321,114 @@ impl<'a> DocumentRenderer<'a> {
         output.push_str("</div>\r\n");
     }
 }
+
+/// 目次の項目を入れ子のリストで出力
+fn render_toc_list(output: &mut String, entries: &[OutlineEntry]) {
+    output.push_str("<ul>\r\n");
+    for entry in entries {
+        output.push_str(&format!(
+            "<li><a href=\"#{}\">{}</a>",
+            entry.id,
+            html_escape(&entry.text)
+        ));
+        if !entry.children.is_empty() {
+            output.push_str("\r\n");
+            render_toc_list(output, &entry.children);
+        }
+        output.push_str("</li>\r\n");
+    }
+    output.push_str("</ul>\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::html::Charset;
+
+    fn html_head(options: &RenderOptions) -> String {
+        let mut output = String::new();
+        DocumentRenderer::new(options).render_html_head(&mut output, &HeaderInfo::default());
+        output
+    }
+
+    #[test]
+    fn test_xhtml_head_charset() {
+        let head = html_head(&RenderOptions::default());
+        assert!(head.starts_with("<?xml version=\"1.0\" encoding=\"Shift_JIS\"?>\r\n"));
+        assert!(head.contains("content=\"text/html;charset=Shift_JIS\""));
+
+        let head = html_head(&RenderOptions::new().with_charset(Charset::Utf8));
+        assert!(head.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\r\n"));
+        assert!(head.contains("content=\"text/html;charset=UTF-8\""));
+        assert!(head.contains("jquery-1.4.2.min.js"));
+    }
+
+    #[test]
+    fn test_html5_head() {
+        let options = RenderOptions::new()
+            .with_profile(HtmlProfile::Html5)
+            .with_charset(Charset::Utf8);
+        let head = html_head(&options);
+        assert!(head.starts_with(
+            "<!DOCTYPE html>\r\n<html lang=\"ja\">\r\n<head>\r\n\t<meta charset=\"UTF-8\" />\r\n"
+        ));
+        assert!(!head.contains("<?xml"));
+        assert!(!head.contains("xhtml11.dtd"));
+        assert!(!head.contains("jquery"));
+    }
+
+    #[test]
+    fn test_html5_has_no_script_hooks() {
+        let options = RenderOptions::new().with_profile(HtmlProfile::Html5);
+        let html = crate::html::convert("題名\n著者\n\n本文\n\n底本：某文庫\n", &options);
+        for hook in [
+            "contents.js",
+            "golibcard.js",
+            "goLibCard",
+            "goAZLibCard",
+            "id=\"contents\"",
+            "<script",
+        ] {
+            assert!(!html.contains(hook), "{hook}");
+        }
+        assert!(html.contains("<div class=\"main_text\">"));
+
+        let html = crate::html::convert("題名\n著者\n\n本文\n", &RenderOptions::default());
+        assert!(html.contains(
+            "<div id=\"contents\" style=\"display:none\"></div><div class=\"main_text\">"
+        ));
+        assert!(html.contains("golibcard.js"));
+    }
+
+    #[test]
+    fn test_render_toc() {
+        let outline = aozora_core::document::AozoraDocument::parse(
+            "題名\n\n上［＃「上」は大見出し］\n一&二［＃「一&二」は中見出し］\n下［＃「下」は大見出し］",
+        )
+        .outline();
+        let mut output = String::new();
+        DocumentRenderer::new(&RenderOptions::default()).render_toc(&mut output, &outline);
+        assert_eq!(
+            output,
+            "<div class=\"toc\">\r\n<ul>\r\n\
+             <li><a href=\"#midashi100\">上</a>\r\n\
+             <ul>\r\n<li><a href=\"#midashi110\">一&amp;二</a></li>\r\n</ul>\r\n</li>\r\n\
+             <li><a href=\"#midashi210\">下</a></li>\r\n</ul>\r\n</div>\r\n"
+        );
+
+        let mut output = String::new();
+        DocumentRenderer::new(&RenderOptions::default()).render_toc(&mut output, &[]);
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_head_escapes_header_title() {
+        let header = HeaderInfo {
+            title: Some("A&B <1>".to_string()),
+            ..HeaderInfo::default()
+        };
+        let mut output = String::new();
+        DocumentRenderer::new(&RenderOptions::default()).render_html_head(&mut output, &header);
+        assert!(output.contains("\t<title>A&amp;B &lt;1&gt;</title>\r\n"));
+    }
+}
@@ -2,6 +2,8 @@
 //!
 //! 青空文庫形式のテキストをHTMLに変換します。
 
+use std::io::{self, BufRead, Write};
+
 mod block_manager;
 mod document_renderer;
 mod node_renderer;
@@ -9,10 +11,13 @@ mod options;
 mod presentation;
 mod renderer;
 mod tag_generator;
+mod template;
 
-pub use options::RenderOptions;
+pub use node_renderer::{UnconvertedGaiji, UnmappableChar};
+pub use options::{Charset, CorrectionDisplay, HtmlProfile, RenderOptions};
 pub use presentation::html_escape;
-pub use renderer::HtmlRenderer;
+pub use renderer::{HtmlParts, HtmlRenderer};
+pub use template::Template;
 
 /// 青空文庫形式のテキストをHTMLに変換
 ///
@@ -40,6 +45,53 @@ pub fn convert(input: &str, options: &RenderOptions) -> String {
     renderer.render(input)
 }
 
+/// 青空文庫形式の入力を読みながらHTMLを書き出す
+///
+/// [`convert`] と同じ変換を入力全体を読み込まずに行い、変換した部分から順に出力します。
+/// 入力はバイト列で、エンコーディング（UTF-8 / Shift_JIS）は自動判定します。
+///
+/// # Examples
+///
+/// ```
+/// use aozora2::html::{convert_stream, RenderOptions};
+///
+/// let input = "タイトル\n\n吾輩《わがはい》は猫である";
+/// let mut html = Vec::new();
+/// convert_stream(input.as_bytes(), &mut html, &RenderOptions::default()).unwrap();
+/// assert!(String::from_utf8(html).unwrap().contains("<ruby>"));
+/// ```
+pub fn convert_stream<R: BufRead, W: Write>(
+    reader: R,
+    writer: W,
+    options: &RenderOptions,
+) -> io::Result<()> {
+    let mut renderer = HtmlRenderer::new(options.clone());
+    renderer.render_stream(reader, writer)
+}
+
+/// 青空文庫形式のテキストを部分ごとにHTMLに変換
+///
+/// 本文、メタデータ、底本情報、表記についてを別々に返します。
+///
+/// # Examples
+///
+/// ```
+/// use aozora2::html::{convert_parts, RenderOptions};
+///
+/// let input = "タイトル\n著者\n\n吾輩《わがはい》は猫である\n\n底本：青空文庫";
+/// let parts = convert_parts(input, &RenderOptions::default());
+/// assert_eq!(
+///     parts.body,
+///     "<ruby><rb>吾輩</rb><rp>（</rp><rt>わがはい</rt><rp>）</rp></ruby>は猫である<br />\r\n<br />\r\n"
+/// );
+/// assert!(parts.metadata.contains("<h1 class=\"title\">タイトル</h1>"));
+/// assert!(parts.bibliography.contains("底本：青空文庫"));
+/// ```
+pub fn convert_parts(input: &str, options: &RenderOptions) -> HtmlParts {
+    let mut renderer = HtmlRenderer::new(options.clone());
+    renderer.render_parts(input)
+}
+
 /// 1行をHTMLに変換
 pub fn convert_line(line: &str, options: &RenderOptions) -> String {
     let mut renderer = HtmlRenderer::new(options.clone());
@@ -74,4 +126,79 @@ mod tests {
         let html = convert_line("猫《ねこ》", &RenderOptions::default());
         assert!(html.contains("<ruby>"));
     }
+
+    #[test]
+    fn test_convert_stream_matches_convert() {
+        let input = "タイトル\r\n著者\r\n\r\n\
+                     -------\r\n《》：ルビ\r\n-------\r\n\
+                     ［＃ここから２字下げ］\r\n吾輩《わがはい》は猫である\r\n\
+                     ［＃ここから２字下げ、折り返して３字下げ］\r\nぶら下げ\r\n\r\n\
+                     ［＃本文終わり］\r\nhttp://example.com/\r\n\
+                     底本：青空文庫\r\n入力：某\r\n";
+        let options = RenderOptions::default();
+        let mut html = Vec::new();
+        convert_stream(input.as_bytes(), &mut html, &options).unwrap();
+        assert_eq!(String::from_utf8(html).unwrap(), convert(input, &options));
+
+        // 本文のみ
+        let input = "タイトル\n\n本文";
+        let mut html = Vec::new();
+        convert_stream(input.as_bytes(), &mut html, &options).unwrap();
+        assert_eq!(String::from_utf8(html).unwrap(), convert(input, &options));
+    }
+
+    #[test]
+    fn test_fragment_and_parts() {
+        let input = "タイトル\n著者\n\n\
+                     ［＃ここから２字下げ］\n吾輩《わがはい》は猫である\n\n\
+                     ［＃本文終わり］\n後書き\n底本：青空文庫\n";
+        let options = RenderOptions::default();
+        let full = convert(input, &options);
+        let parts = convert_parts(input, &options);
+
+        // 各部分は全体の出力の一部
+        for part in [
+            &parts.metadata,
+            &parts.body,
+            &parts.after_text,
+            &parts.bibliography,
+            &parts.notes,
+        ] {
+            assert!(!part.is_empty());
+            assert!(full.contains(part.as_str()), "{part}");
+        }
+        assert_eq!(parts.header.title.as_deref(), Some("タイトル"));
+        assert!(parts.body.ends_with("</div>"));
+        assert!(parts.toc.is_empty());
+
+        // 断片モードは本文のみ
+        let options = options.with_fragment(true);
+        let fragment = convert(input, &options);
+        assert_eq!(fragment, parts.body);
+        let mut streamed = Vec::new();
+        convert_stream(input.as_bytes(), &mut streamed, &options).unwrap();
+        assert_eq!(String::from_utf8(streamed).unwrap(), fragment);
+    }
+
+    #[test]
+    fn test_toc() {
+        let input = "タイトル\n著者\n\n\
+                     ［＃３字下げ］上［＃「上」は大見出し］\n本文\n\
+                     ［＃ここから中見出し］\n一\n［＃ここで中見出し終わり］\n本文\n";
+        let options = RenderOptions::default().with_toc(true);
+        let html = convert(input, &options);
+        let toc = "<div class=\"toc\">\r\n<ul>\r\n\
+                   <li><a href=\"#midashi100\">上</a>\r\n\
+                   <ul>\r\n<li><a href=\"#midashi110\">一</a></li>\r\n</ul>\r\n</li>\r\n\
+                   </ul>\r\n</div>\r\n";
+        assert!(html.contains(&format!("</div>\r\n{toc}<div id=\"contents\"")));
+        assert!(html.contains("id=\"midashi100\">上</a>"));
+        assert!(html.contains("id=\"midashi110\">"));
+        assert_eq!(convert_parts(input, &options).toc, toc);
+
+        let mut streamed = Vec::new();
+        convert_stream(input.as_bytes(), &mut streamed, &options).unwrap();
+        assert_eq!(String::from_utf8(streamed).unwrap(), html);
+        assert!(!convert(input, &RenderOptions::default()).contains("class=\"toc\""));
+    }
 }
@@ -3,16 +3,20 @@
 //! ASTノードをHTMLに変換します。
 
 use aozora_core::gaiji::{parse_gaiji, GaijiResult};
+use aozora_core::jis_table::unicode_to_jis;
 use aozora_core::node::{
-    BlockType, FontSizeType, MidashiLevel, MidashiStyle, Node, RubyDirection, StyleType,
+    BlockType, CorrectionKind, FontSizeType, MidashiLevel, MidashiStyle, Node, RubyDirection,
+    StyleType,
 };
+use encoding_rs::SHIFT_JIS;
 
 use super::block_manager::BlockManager;
-use super::options::RenderOptions;
+use super::options::{Charset, CorrectionDisplay, RenderOptions};
 use super::presentation::{
     html_escape, jis_code_to_path, midashi_combined_css_class, midashi_html_tag, style_css_class,
     style_html_tag,
 };
+use super::tag_generator::generate_break_tag;
 
 /// 未変換外字情報
 #[derive(Debug, Clone)]
@@ -23,6 +27,17 @@ pub struct UnconvertedGaiji {
     pub page_line: String,
 }
 
+/// 出力の文字コードで表せなかった文字
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnmappableChar {
+    /// 文字
+    pub ch: char,
+    /// 最初に現れた行（0始まり）
+    pub line: usize,
+    /// 外字画像にした場合のJIS X 0213の面区点番号
+    pub jis_code: Option<String>,
+}
+
 /// ノードレンダラー
 pub struct NodeRenderer<'a> {
     options: &'a RenderOptions,
@@ -36,6 +51,10 @@ pub struct NodeRenderer<'a> {
     pub has_jisx0213: bool,
     /// 未変換外字のリスト
     pub unconverted_gaiji: Vec<UnconvertedGaiji>,
+    /// 出力の文字コードで表せなかった文字のリスト
+    pub unmappable_chars: Vec<UnmappableChar>,
+    /// 変換中の行（0始まり）
+    pub line: usize,
 }
 
 impl<'a> NodeRenderer<'a> {
@@ -48,6 +67,8 @@ impl<'a> NodeRenderer<'a> {
             has_accent: false,
             has_jisx0213: false,
             unconverted_gaiji: Vec::new(),
+            unmappable_chars: Vec::new(),
+            line: 0,
         }
     }
 
@@ -63,7 +84,7 @@ impl<'a> NodeRenderer<'a> {
     /// 単一ノードをHTMLに変換
     pub fn render_node(&mut self, node: &Node, block_manager: &mut BlockManager) -> String {
         match node {
-            Node::Text(text) => html_escape(text),
+            Node::Text(text) => self.render_text(text),
 
             Node::Ruby {
                 children,
@@ -157,11 +178,11 @@ impl<'a> NodeRenderer<'a> {
             } => self.render_font_size(children, *size_type, *level, block_manager),
 
             Node::Kaeriten(text) => {
-                format!("<sub class=\"kaeriten\">{}</sub>", html_escape(text))
+                format!("<sub class=\"kaeriten\">{}</sub>", self.render_text(text))
             }
 
             Node::Okurigana(text) => {
-                format!("<sup class=\"okurigana\">{}</sup>", html_escape(text))
+                format!("<sup class=\"okurigana\">{}</sup>", self.render_text(text))
             }
 
             Node::BlockStart { block_type, params } => {
@@ -199,6 +220,14 @@ impl<'a> NodeRenderer<'a> {
                 }
             }
 
+            Node::Break { kind } => generate_break_tag(*kind),
+
+            Node::Correction {
+                target,
+                original,
+                kind,
+            } => self.render_correction(target, original.as_deref(), *kind, block_manager),
+
             Node::Note(text) => {
                 self.has_notes = true;
                 format!("<span class=\"notes\">［＃{}］</span>", html_escape(text))
@@ -242,6 +271,40 @@ impl<'a> NodeRenderer<'a> {
         }
     }
 
+    /// 校正注記をHTMLに変換
+    fn render_correction(
+        &mut self,
+        target: &[Node],
+        original: Option<&str>,
+        kind: CorrectionKind,
+        block_manager: &mut BlockManager,
+    ) -> String {
+        let target_html = self.render_nodes(target, block_manager);
+        match (self.options.correction_display, original) {
+            (CorrectionDisplay::Note, _) => {
+                self.has_notes = true;
+                format!(
+                    "{target_html}<span class=\"notes\">［＃{}］</span>",
+                    html_escape(&kind.command(target, original))
+                )
+            }
+            (CorrectionDisplay::Corrected, _) => target_html,
+            (CorrectionDisplay::Original, Some(original)) => html_escape(original),
+            (CorrectionDisplay::Original, None) => target_html,
+            (CorrectionDisplay::Both, Some(original)) => format!(
+                "<del class=\"teihon\">{}</del><ins class=\"correction\">{target_html}</ins>",
+                html_escape(original)
+            ),
+            (CorrectionDisplay::Both, None) => {
+                let title = match kind {
+                    CorrectionKind::RubySic => "ルビはママ",
+                    _ => "ママ",
+                };
+                format!("<span class=\"mama\" title=\"{title}\">{target_html}</span>")
+            }
+        }
+    }
+
     /// ルビをHTMLに変換
     fn render_ruby(
         &mut self,
@@ -330,6 +393,49 @@ impl<'a> NodeRenderer<'a> {
         format!("<span class=\"{class}\" style=\"{style}\">{inner}</span>")
     }
 
+    /// テキストをHTMLに変換
+    ///
+    /// 出力の文字コードがShift_JISの場合、Shift_JISで表せない文字は
+    /// JIS X 0213にあれば外字として、なければ数値文字参照で出力します。
+    fn render_text(&mut self, text: &str) -> String {
+        if self.options.charset != Charset::ShiftJis || !SHIFT_JIS.encode(text).2 {
+            return html_escape(text);
+        }
+
+        let mut output = String::with_capacity(text.len());
+        let mut buf = [0u8; 4];
+        for c in text.chars() {
+            let s = c.encode_utf8(&mut buf);
+            if !SHIFT_JIS.encode(s).2 {
+                output.push_str(&html_escape(s));
+                continue;
+            }
+
+            let jis_code = unicode_to_jis(s);
+            match &jis_code {
+                Some(jis) => {
+                    let description = format!("U+{:04X}", u32::from(c));
+                    output.push_str(&self.render_gaiji(&description, Some(s), Some(jis)));
+                }
+                None => output.push_str(&format!("&#{};", u32::from(c))),
+            }
+            self.add_unmappable_char(c, jis_code);
+        }
+        output
+    }
+
+    /// 出力の文字コードで表せなかった文字を追加（重複を避ける）
+    fn add_unmappable_char(&mut self, ch: char, jis_code: Option<String>) {
+        if self.unmappable_chars.iter().any(|u| u.ch == ch) {
+            return;
+        }
+        self.unmappable_chars.push(UnmappableChar {
+            ch,
+            line: self.line,
+            jis_code: jis_code.filter(|_| !self.options.use_jisx0213 && !self.options.use_unicode),
+        });
+    }
+
     /// 外字をHTMLに変換
     fn render_gaiji(
         &mut self,
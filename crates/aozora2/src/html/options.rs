@@ -1,5 +1,9 @@
 //! レンダリングオプション
 
+use clap::ValueEnum;
+
+use super::template::Template;
+
 /// HTML変換オプション
 #[derive(Debug, Clone)]
 pub struct RenderOptions {
@@ -13,6 +17,81 @@ pub struct RenderOptions {
     pub use_unicode: bool,
     /// ドキュメントのタイトル
     pub title: Option<String>,
+    /// 校正注記の表示方法
+    pub correction_display: CorrectionDisplay,
+    /// 出力するHTMLの形式
+    pub profile: HtmlProfile,
+    /// ページのテンプレート
+    ///
+    /// 指定した場合、ページの枠組みは [`HtmlProfile`] の代わりにテンプレートで出力します。
+    /// `fragment` より優先します。
+    pub template: Option<Template>,
+    /// 本文（`<div class="main_text">` の中身）のみを出力
+    ///
+    /// ヘッダー、メタデータ、本文終わり後のテキスト、底本情報、表記について、
+    /// 図書カードは出力しません。
+    pub fragment: bool,
+    /// メタデータセクションの後に見出しの目次（`<div class="toc">`）を出力
+    ///
+    /// 目次は入力全体を読み込んでから作成します。
+    /// `fragment` の場合は出力しません（目次は [`HtmlRenderer::render_parts`] で取得できます）。
+    ///
+    /// [`HtmlRenderer::render_parts`]: super::HtmlRenderer::render_parts
+    pub toc: bool,
+    /// 出力の文字コード（XML宣言と `charset` にも使用）
+    ///
+    /// Shift_JISの場合、Shift_JISで表せない本文の文字は外字と同じく画像か数値文字参照にします。
+    pub charset: Charset,
+    /// 出力の文字コードで表せない文字をエラーにする（[`HtmlRenderer::render_stream`] で使用）
+    ///
+    /// [`HtmlRenderer::render_stream`]: super::HtmlRenderer::render_stream
+    pub strict: bool,
+}
+
+/// 出力するHTMLの形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum HtmlProfile {
+    /// aozora2html互換のXHTML 1.1（XML宣言、DTD、jQueryの読み込みを含む）
+    #[default]
+    Xhtml,
+    /// HTML5（`<!DOCTYPE html>` と `<meta charset>`）
+    Html5,
+}
+
+/// 出力の文字コード
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Charset {
+    /// UTF-8
+    #[value(name = "utf-8", alias = "utf8")]
+    Utf8,
+    /// Shift_JIS（aozora2html互換）
+    #[default]
+    #[value(name = "shift_jis", alias = "sjis", alias = "cp932")]
+    ShiftJis,
+}
+
+impl Charset {
+    /// 文字コード名（`charset` や `encoding` の値）
+    pub fn name(self) -> &'static str {
+        match self {
+            Charset::Utf8 => "UTF-8",
+            Charset::ShiftJis => "Shift_JIS",
+        }
+    }
+}
+
+/// 校正注記（「底本では」「ママ」）の表示方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum CorrectionDisplay {
+    /// 本文の後に注記をそのまま表示
+    #[default]
+    Note,
+    /// 校正後の本文のみ
+    Corrected,
+    /// 底本の読みのみ
+    Original,
+    /// 底本の読みを `<del>`、校正後を `<ins>` で併記し、ママは `title` で示す
+    Both,
 }
 
 impl Default for RenderOptions {
@@ -23,6 +102,13 @@ impl Default for RenderOptions {
             use_jisx0213: false,
             use_unicode: false,
             title: None,
+            correction_display: CorrectionDisplay::default(),
+            profile: HtmlProfile::default(),
+            template: None,
+            fragment: false,
+            toc: false,
+            charset: Charset::default(),
+            strict: false,
         }
     }
 }
@@ -62,6 +148,48 @@ impl RenderOptions {
         self.title = Some(title.into());
         self
     }
+
+    /// 校正注記の表示方法を設定
+    pub fn with_correction_display(mut self, display: CorrectionDisplay) -> Self {
+        self.correction_display = display;
+        self
+    }
+
+    /// 出力するHTMLの形式を設定
+    pub fn with_profile(mut self, profile: HtmlProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// ページのテンプレートを設定
+    pub fn with_template(mut self, template: Template) -> Self {
+        self.template = Some(template);
+        self
+    }
+
+    /// 本文のみを出力するかを設定
+    pub fn with_fragment(mut self, fragment: bool) -> Self {
+        self.fragment = fragment;
+        self
+    }
+
+    /// 見出しの目次を出力するかを設定
+    pub fn with_toc(mut self, toc: bool) -> Self {
+        self.toc = toc;
+        self
+    }
+
+    /// 出力の文字コードを設定
+    pub fn with_charset(mut self, charset: Charset) -> Self {
+        self.charset = charset;
+        self
+    }
+
+    /// 出力の文字コードで表せない文字をエラーにするかを設定
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -74,6 +202,11 @@ mod tests {
         assert_eq!(opts.gaiji_dir, "../../../gaiji/");
         assert!(!opts.use_jisx0213);
         assert!(!opts.use_unicode);
+        assert_eq!(opts.correction_display, CorrectionDisplay::Note);
+        assert_eq!(opts.profile, HtmlProfile::Xhtml);
+        assert_eq!(opts.charset, Charset::ShiftJis);
+        assert!(!opts.strict);
+        assert!(!opts.toc);
     }
 
     #[test]
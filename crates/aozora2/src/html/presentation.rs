@@ -2,7 +2,7 @@
 //!
 //! CSSクラス名とHTMLタグ名のマッピングを提供します。
 
-use aozora_core::node::{MidashiLevel, MidashiStyle, StyleType};
+use aozora_core::node::{BreakKind, MidashiLevel, MidashiStyle, StyleType};
 
 /// 行のHTML出力タイプ
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -110,6 +110,28 @@ pub fn midashi_html_tag(level: MidashiLevel) -> &'static str {
     }
 }
 
+/// BreakKind のCSSクラス名を取得
+pub fn break_css_class(kind: BreakKind) -> &'static str {
+    match kind {
+        BreakKind::Page => "kaipage",
+        BreakKind::Recto => "kaicho",
+        BreakKind::Spread => "kaimihiraki",
+        BreakKind::Column => "kaidan",
+    }
+}
+
+/// BreakKind の `break-before` の値を取得
+///
+/// 縦書きの見開きは右ページから始まるため、改見開きは `right` にします。
+pub fn break_before_value(kind: BreakKind) -> &'static str {
+    match kind {
+        BreakKind::Page => "page",
+        BreakKind::Recto => "recto",
+        BreakKind::Spread => "right",
+        BreakKind::Column => "column",
+    }
+}
+
 /// HTMLエスケープ
 pub fn html_escape(s: &str) -> String {
     s.replace('&', "&amp;")
@@ -177,10 +199,10 @@ pub fn is_block_only_line(html: &str) -> bool {
     }
 
     // 見出し開始タグで終わる
-    if html.ends_with("\">") {
-        if html.contains("<h3") || html.contains("<h4") || html.contains("<h5") {
-            return true;
-        }
+    if html.ends_with("\">")
+        && (html.contains("<h3") || html.contains("<h4") || html.contains("<h5"))
+    {
+        return true;
     }
 
     // 全体が単一のタグ: ^<[^>]*>$
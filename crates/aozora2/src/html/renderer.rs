@@ -2,177 +2,264 @@
 //!
 //! ASTノードをHTMLに変換します。
 
+use std::io::{self, BufRead, Write};
+
 use aozora_core::document::{
-    extract_after_text_lines, extract_bibliographical_lines, extract_body_lines,
-    extract_header_info,
+    extract_header_info, AozoraDocument, DocumentLine, HeaderInfo, Section, SectionScanner,
 };
+use aozora_core::encoding::decode_lines;
 use aozora_core::node::Node;
-use aozora_core::parser::parse;
-use aozora_core::parser::reference_resolver::resolve_inline_ruby;
-use aozora_core::tokenizer::tokenize;
+use aozora_core::outline::OutlineEntry;
 
 use super::block_manager::BlockManager;
 use super::document_renderer::DocumentRenderer;
-use super::node_renderer::NodeRenderer;
+use super::node_renderer::{NodeRenderer, UnconvertedGaiji, UnmappableChar};
 use super::options::RenderOptions;
 use super::presentation::{auto_link, classify_line, is_block_only_line, LineType};
 
+/// 部分ごとに変換したHTML
+///
+/// [`HtmlRenderer::render_parts`] で作成します。ページの枠組みを自前で用意する場合に使います。
+#[derive(Debug, Clone, Default)]
+pub struct HtmlParts {
+    /// ヘッダー情報
+    pub header: HeaderInfo,
+    /// メタデータセクション（`<div class="metadata">`）
+    pub metadata: String,
+    /// 見出しの目次（`<div class="toc">`、見出しがなければ空）
+    pub toc: String,
+    /// 本文（`<div class="main_text">` の中身）
+    pub body: String,
+    /// 本文終わり後のテキスト（`<div class="after_text">`、なければ空）
+    pub after_text: String,
+    /// 底本情報（`<div class="bibliographical_information">`、なければ空）
+    pub bibliography: String,
+    /// 表記について（`<div class="notation_notes">`）
+    pub notes: String,
+    /// 未変換外字のリスト
+    pub unconverted_gaiji: Vec<UnconvertedGaiji>,
+}
+
 /// HTMLレンダラー
 #[derive(Debug, Clone)]
 pub struct HtmlRenderer {
     options: RenderOptions,
+    /// 直前の変換で出力の文字コードで表せなかった文字
+    unmappable_chars: Vec<UnmappableChar>,
 }
 
 impl HtmlRenderer {
     /// 新しいレンダラーを作成
     pub fn new(options: RenderOptions) -> Self {
-        Self { options }
+        Self {
+            options,
+            unmappable_chars: Vec::new(),
+        }
+    }
+
+    /// 直前の変換で出力の文字コードで表せなかった文字
+    ///
+    /// 本文の文字のうち、外字画像または数値文字参照にしたものです。
+    pub fn unmappable_chars(&self) -> &[UnmappableChar] {
+        &self.unmappable_chars
     }
 
     /// テキスト全体をHTMLに変換
     pub fn render(&mut self, input: &str) -> String {
+        let document = AozoraDocument::parse(input);
+        self.render_document(&document)
+    }
+
+    /// 解析済みの文書をHTMLに変換
+    pub fn render_document(&mut self, document: &AozoraDocument) -> String {
+        if let Some(template) = self.options.template.clone() {
+            let parts = self.render_document_parts(document);
+            return template.render(&parts, &self.options);
+        }
+
+        let outline = if self.options.toc {
+            document.outline()
+        } else {
+            Vec::new()
+        };
         let mut output = String::new();
-        let lines: Vec<&str> = input.lines().collect();
+        let mut writer = DocumentWriter::new(self);
 
-        // ヘッダー情報を抽出
-        let header_info = extract_header_info(&lines);
+        writer.start(&mut output, &document.header, &outline);
+        for line in &document.body {
+            writer.body_line(&mut output, line);
+        }
+        for line in &document.after_text {
+            writer.after_text_line(&mut output, line);
+        }
+        for line in &document.bibliography {
+            writer.bibliography_line(&mut output, line);
+        }
+        self.unmappable_chars = writer.finish(&mut output);
 
-        // サブレンダラーを作成
-        let doc_renderer = DocumentRenderer::new(&self.options);
-        let mut node_renderer = NodeRenderer::new(&self.options);
-        let mut block_manager = BlockManager::new();
+        output
+    }
 
-        // HTMLヘッダーとメタデータセクションを出力
-        doc_renderer.render_html_head(&mut output, &header_info);
-        doc_renderer.render_metadata_section(&mut output, &header_info);
+    /// テキスト全体を部分ごとにHTMLに変換
+    ///
+    /// [`RenderOptions::fragment`] の設定にかかわらず、すべての部分を変換します。
+    pub fn render_parts(&mut self, input: &str) -> HtmlParts {
+        let document = AozoraDocument::parse(input);
+        self.render_document_parts(&document)
+    }
 
-        // main_text開始
-        doc_renderer.render_main_text_start(&mut output);
-
-        // 本文のみ抽出してレンダリング
-        let body_lines = extract_body_lines(&lines);
-        for line in &body_lines {
-            let line_html =
-                self.render_line_with_context(line, &mut node_renderer, &mut block_manager);
-
-            // ぶら下げブロック内かどうかをチェック
-            let burasage_ctx = block_manager.find_burasage_context();
-            let line_type = classify_line(&line_html);
-
-            if let Some((wrap_width, text_indent)) = burasage_ctx {
-                // ぶら下げブロック内: インライン行を個別のdivでラップ
-                if line_type == LineType::Inline {
-                    output.push_str(&format!(
-                        "<div class=\"burasage\" style=\"margin-left: {wrap_width}em; text-indent: {text_indent}em;\">{line_html}</div>"
-                    ));
-                    output.push_str("\r\n");
-                    continue;
-                }
+    /// 解析済みの文書を部分ごとにHTMLに変換
+    pub fn render_document_parts(&mut self, document: &AozoraDocument) -> HtmlParts {
+        let mut parts = HtmlParts {
+            header: document.header.clone(),
+            ..HtmlParts::default()
+        };
+        let mut writer = DocumentWriter::new(self);
+
+        writer
+            .doc_renderer
+            .render_metadata_section(&mut parts.metadata, &document.header);
+        writer
+            .doc_renderer
+            .render_toc(&mut parts.toc, &document.outline());
+
+        for line in &document.body {
+            writer.body_line(&mut parts.body, line);
+        }
+        writer.close_blocks(&mut parts.body);
+
+        if !document.after_text.is_empty() {
+            writer
+                .doc_renderer
+                .render_after_text_header(&mut parts.after_text);
+            for line in &document.after_text {
+                writer.appendix_line(&mut parts.after_text, line);
             }
-
-            // line_htmlが空でかつ元の行も空じゃない場合（コマンドのみの行）は何も出力しない
-            if line_html.is_empty() && !line.is_empty() {
-                continue;
+            writer
+                .doc_renderer
+                .render_after_text_footer(&mut parts.after_text);
+        }
+        if !document.bibliography.is_empty() {
+            writer
+                .doc_renderer
+                .render_bibliographical_header(&mut parts.bibliography);
+            for line in &document.bibliography {
+                writer.appendix_line(&mut parts.bibliography, line);
             }
+            writer
+                .doc_renderer
+                .render_bibliographical_footer(&mut parts.bibliography);
+        }
 
-            output.push_str(&line_html);
+        writer.notation_notes(&mut parts.notes);
+        parts.unconverted_gaiji = writer.node_renderer.unconverted_gaiji.clone();
+        self.unmappable_chars = writer.node_renderer.unmappable_chars;
 
-            // インラインブロック（is_block = false）は行末で閉じる
-            let closed_blocks = block_manager.close_inline_blocks();
-            for (block_type, params) in closed_blocks {
-                output.push_str(&block_manager.render_block_end_tag(&block_type, &params));
-            }
+        parts
+    }
 
-            // ブロック開始/終了だけの行（div終わる）には<br />を追加しない
-            let ends_with_div = output.ends_with("</div>");
-
-            let needs_br = if line_html.is_empty() {
-                // line_htmlが空の場合：元の行が空白行なら<br />を追加
-                true
-            } else if ends_with_div {
-                // 現在の出力がdiv終了タグで終わる場合は<br />不要
-                false
-            } else {
-                !is_block_only_line(&line_html)
-            };
-            if needs_br {
-                output.push_str("<br />");
+    /// 入力を読みながらHTMLを書き出す
+    ///
+    /// [`render`](Self::render) と同じ変換を1行ずつ行い、変換した部分から順に出力します。
+    /// 保持するのはヘッダー行とブロックのスタックのみで、入力全体は読み込みません。
+    /// エンコーディングの判定は [`decode_lines`] を参照してください。
+    ///
+    /// [`RenderOptions::strict`] が有効な場合、出力の文字コードで表せない文字があると
+    /// [`io::ErrorKind::InvalidData`] のエラーを返します。途中までの出力を残さないよう、
+    /// 変換を終えてからまとめて書き出します。
+    ///
+    /// [`RenderOptions::template`] または [`RenderOptions::toc`] を指定した場合は、
+    /// 入力全体を読み込んでから変換します。
+    pub fn render_stream<R: BufRead, W: Write>(
+        &mut self,
+        reader: R,
+        mut writer: W,
+    ) -> io::Result<()> {
+        if self.options.template.is_some() || self.options.toc {
+            let lines = decode_lines(reader).collect::<io::Result<Vec<_>>>()?;
+            let html = self.render(&lines.join("\n"));
+            if self.options.strict {
+                if let Some(unmappable) = self.unmappable_chars.first() {
+                    return Err(unmappable_error(unmappable, &self.options));
+                }
             }
-            output.push_str("\r\n");
+            writer.write_all(html.as_bytes())?;
+            return writer.flush();
         }
 
-        // 閉じられていないブロックを閉じる
-        while let Some(ctx) = block_manager.pop() {
-            output.push_str(&block_manager.render_block_end_tag(&ctx.block_type, &ctx.params));
-        }
-
-        // main_text終了
-        doc_renderer.render_main_text_end(&mut output);
-
-        // 本文終わり後のテキスト（after_text）セクション
-        let after_text_lines = extract_after_text_lines(&lines);
-        if !after_text_lines.is_empty() {
-            doc_renderer.render_after_text_header(&mut output);
-            for line in &after_text_lines {
-                let line_html =
-                    self.render_line_with_context(line, &mut node_renderer, &mut block_manager);
-                // 自動リンク化を適用
-                let line_html = auto_link(&line_html);
-                output.push_str(&line_html);
-                output.push_str("<br />\r\n");
+        let mut output = String::new();
+        let mut document_writer = DocumentWriter::new(self);
+        let mut scanner = SectionScanner::new();
+        // ヘッダー情報の抽出が終わるまでヘッダー行を保持
+        let mut header_lines = Some(Vec::new());
+
+        for (i, line) in decode_lines(reader).enumerate() {
+            let line = line?;
+            let section = scanner.next_line(&line);
+            if section == Section::Header {
+                if let Some(header_lines) = &mut header_lines {
+                    header_lines.push(line);
+                }
+                continue;
             }
-            doc_renderer.render_after_text_footer(&mut output);
-        }
-
-        // 底本情報（bibliographical_information）セクション
-        let biblio_lines = extract_bibliographical_lines(&lines);
-        if !biblio_lines.is_empty() {
-            doc_renderer.render_bibliographical_header(&mut output);
-            for line in &biblio_lines {
-                let line_html =
-                    self.render_line_with_context(line, &mut node_renderer, &mut block_manager);
-                // 自動リンク化を適用
-                let line_html = auto_link(&line_html);
-                output.push_str(&line_html);
-                output.push_str("<br />\r\n");
+            if let Some(lines) = header_lines.take() {
+                document_writer.start(&mut output, &header_info(&lines), &[]);
             }
-            doc_renderer.render_bibliographical_footer(&mut output);
-        }
 
-        // 表記について（notation_notes）セクション
-        doc_renderer.render_notation_notes(
-            &mut output,
-            node_renderer.has_notes,
-            node_renderer.has_jisx0213,
-            node_renderer.has_accent,
-            &node_renderer.unconverted_gaiji,
-        );
+            let render_line = match section {
+                Section::Body => DocumentWriter::body_line,
+                Section::AfterText => DocumentWriter::after_text_line,
+                Section::Bibliography => DocumentWriter::bibliography_line,
+                Section::Header | Section::Separator | Section::Legend => continue,
+            };
+            // 行の位置情報はHTML変換では使わないため、行頭のバイト位置は0とする
+            render_line(
+                &mut document_writer,
+                &mut output,
+                &DocumentLine::parse(i, 0, &line),
+            );
+            if self.options.strict {
+                if let Some(unmappable) = document_writer.node_renderer.unmappable_chars.first() {
+                    return Err(unmappable_error(unmappable, &self.options));
+                }
+            }
 
-        // 図書カードセクション
-        doc_renderer.render_card_section(&mut output);
+            if !self.options.strict {
+                writer.write_all(output.as_bytes())?;
+                output.clear();
+            }
+        }
 
-        doc_renderer.render_html_foot(&mut output);
+        if let Some(lines) = header_lines.take() {
+            document_writer.start(&mut output, &header_info(&lines), &[]);
+        }
+        self.unmappable_chars = document_writer.finish(&mut output);
+        writer.write_all(output.as_bytes())?;
+        writer.flush()
+    }
 
-        output
+    /// 解析済みの1行をHTMLに変換（コンテキスト付き）
+    fn render_document_line(
+        &self,
+        line: &DocumentLine,
+        node_renderer: &mut NodeRenderer,
+        block_manager: &mut BlockManager,
+    ) -> String {
+        self.render_line_with_context(&line.text, &line.to_nodes(), node_renderer, block_manager)
     }
 
     /// 1行をHTMLに変換（コンテキスト付き）
     fn render_line_with_context(
         &self,
         line: &str,
+        nodes: &[Node],
         node_renderer: &mut NodeRenderer,
         block_manager: &mut BlockManager,
     ) -> String {
-        let tokens = tokenize(line);
-        let mut nodes = parse(&tokens);
-
-        // 行内ルビを解決
-        resolve_inline_ruby(&mut nodes);
-
         // 行の開始時点でのブロックスタックの長さを記録
         let stack_len_before = block_manager.stack_len();
 
-        let mut output = node_renderer.render_nodes(&nodes, block_manager);
+        let mut output = node_renderer.render_nodes(nodes, block_manager);
 
         // 行単位字下げ: 行の終わりで、その行で開いたブロックを閉じる
         let is_line_scope_block = line.starts_with("［＃")
@@ -193,7 +280,8 @@ impl HtmlRenderer {
     pub fn render_line(&mut self, line: &str) -> String {
         let mut node_renderer = NodeRenderer::new(&self.options);
         let mut block_manager = BlockManager::new();
-        self.render_line_with_context(line, &mut node_renderer, &mut block_manager)
+        let document_line = DocumentLine::parse(0, 0, line);
+        self.render_document_line(&document_line, &mut node_renderer, &mut block_manager)
     }
 
     /// ノード列をHTMLに変換
@@ -204,9 +292,234 @@ impl HtmlRenderer {
     }
 }
 
+/// 出力の文字コードで表せない文字のエラー
+fn unmappable_error(unmappable: &UnmappableChar, options: &RenderOptions) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "line {}: character {} (U+{:04X}) cannot be encoded in {}",
+            unmappable.line + 1,
+            unmappable.ch,
+            u32::from(unmappable.ch),
+            options.charset.name()
+        ),
+    )
+}
+
+/// ヘッダー行からヘッダー情報を抽出
+fn header_info(lines: &[String]) -> HeaderInfo {
+    let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+    extract_header_info(&lines)
+}
+
+/// 出力中のセクション
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputSection {
+    /// 本文（main_text）
+    Body,
+    /// 本文終わり後のテキスト（after_text）
+    AfterText,
+    /// 底本情報（bibliographical_information）
+    Bibliography,
+}
+
+/// 文書を先頭から1行ずつHTMLに変換する状態
+struct DocumentWriter<'a> {
+    renderer: &'a HtmlRenderer,
+    doc_renderer: DocumentRenderer<'a>,
+    node_renderer: NodeRenderer<'a>,
+    block_manager: BlockManager,
+    section: OutputSection,
+}
+
+impl<'a> DocumentWriter<'a> {
+    fn new(renderer: &'a HtmlRenderer) -> Self {
+        Self {
+            renderer,
+            doc_renderer: DocumentRenderer::new(&renderer.options),
+            node_renderer: NodeRenderer::new(&renderer.options),
+            block_manager: BlockManager::new(),
+            section: OutputSection::Body,
+        }
+    }
+
+    /// 断片のみ（本文の中身のみ）を出力するか
+    fn fragment(&self) -> bool {
+        self.renderer.options.fragment
+    }
+
+    /// HTMLヘッダー、メタデータセクションと目次を出力し、本文を開始
+    fn start(&self, output: &mut String, header_info: &HeaderInfo, outline: &[OutlineEntry]) {
+        if self.fragment() {
+            return;
+        }
+        self.doc_renderer.render_html_head(output, header_info);
+        self.doc_renderer
+            .render_metadata_section(output, header_info);
+        self.doc_renderer.render_toc(output, outline);
+
+        // main_text開始
+        self.doc_renderer.render_main_text_start(output);
+    }
+
+    /// 本文の1行を出力
+    fn body_line(&mut self, output: &mut String, line: &DocumentLine) {
+        let line_html = self.render_line(line);
+        let block_manager = &mut self.block_manager;
+
+        // ぶら下げブロック内かどうかをチェック
+        let burasage_ctx = block_manager.find_burasage_context();
+        let line_type = classify_line(&line_html);
+
+        if let Some((wrap_width, text_indent)) = burasage_ctx {
+            // ぶら下げブロック内: インライン行を個別のdivでラップ
+            if line_type == LineType::Inline {
+                output.push_str(&format!(
+                    "<div class=\"burasage\" style=\"margin-left: {wrap_width}em; text-indent: {text_indent}em;\">{line_html}</div>"
+                ));
+                output.push_str("\r\n");
+                return;
+            }
+        }
+
+        // line_htmlが空でかつ元の行も空じゃない場合（コマンドのみの行）は何も出力しない
+        if line_html.is_empty() && !line.text.is_empty() {
+            return;
+        }
+
+        output.push_str(&line_html);
+
+        // インラインブロック（is_block = false）は行末で閉じる
+        let closed_blocks = block_manager.close_inline_blocks();
+        for (block_type, params) in closed_blocks {
+            output.push_str(&block_manager.render_block_end_tag(&block_type, &params));
+        }
+
+        // ブロック開始/終了だけの行（div終わる）には<br />を追加しない
+        let ends_with_div = output.ends_with("</div>");
+
+        let needs_br = if line_html.is_empty() {
+            // line_htmlが空の場合：元の行が空白行なら<br />を追加
+            true
+        } else if ends_with_div {
+            // 現在の出力がdiv終了タグで終わる場合は<br />不要
+            false
+        } else {
+            !is_block_only_line(&line_html)
+        };
+        if needs_br {
+            output.push_str("<br />");
+        }
+        output.push_str("\r\n");
+    }
+
+    /// 本文終わり後のテキストの1行を出力
+    fn after_text_line(&mut self, output: &mut String, line: &DocumentLine) {
+        if self.fragment() {
+            return;
+        }
+        self.enter(output, OutputSection::AfterText);
+        self.appendix_line(output, line);
+    }
+
+    /// 底本情報の1行を出力
+    fn bibliography_line(&mut self, output: &mut String, line: &DocumentLine) {
+        if self.fragment() {
+            return;
+        }
+        self.enter(output, OutputSection::Bibliography);
+        self.appendix_line(output, line);
+    }
+
+    /// 本文後のセクションの1行を出力
+    fn appendix_line(&mut self, output: &mut String, line: &DocumentLine) {
+        // 自動リンク化を適用
+        let line_html = auto_link(&self.render_line(line));
+        output.push_str(&line_html);
+        output.push_str("<br />\r\n");
+    }
+
+    /// 残りのセクションと文書の末尾を出力し、出力の文字コードで表せなかった文字を返す
+    fn finish(mut self, output: &mut String) -> Vec<UnmappableChar> {
+        if self.fragment() {
+            self.close_blocks(output);
+            return self.node_renderer.unmappable_chars;
+        }
+        self.leave(output);
+
+        // 表記について（notation_notes）セクション
+        self.notation_notes(output);
+
+        // 図書カードセクション
+        self.doc_renderer.render_card_section(output);
+
+        self.doc_renderer.render_html_foot(output);
+
+        self.node_renderer.unmappable_chars
+    }
+
+    /// 表記について（notation_notes）セクションを出力
+    fn notation_notes(&self, output: &mut String) {
+        self.doc_renderer.render_notation_notes(
+            output,
+            self.node_renderer.has_notes,
+            self.node_renderer.has_jisx0213,
+            self.node_renderer.has_accent,
+            &self.node_renderer.unconverted_gaiji,
+        );
+    }
+
+    /// 閉じられていないブロックを閉じる
+    fn close_blocks(&mut self, output: &mut String) {
+        while let Some(ctx) = self.block_manager.pop() {
+            output.push_str(
+                &self
+                    .block_manager
+                    .render_block_end_tag(&ctx.block_type, &ctx.params),
+            );
+        }
+    }
+
+    /// 出力中のセクションを閉じて次のセクションを開く
+    fn enter(&mut self, output: &mut String, section: OutputSection) {
+        if self.section == section {
+            return;
+        }
+        self.leave(output);
+        match section {
+            OutputSection::Body => {}
+            OutputSection::AfterText => self.doc_renderer.render_after_text_header(output),
+            OutputSection::Bibliography => self.doc_renderer.render_bibliographical_header(output),
+        }
+        self.section = section;
+    }
+
+    /// 出力中のセクションを閉じる
+    fn leave(&mut self, output: &mut String) {
+        match self.section {
+            OutputSection::Body => {
+                self.close_blocks(output);
+
+                // main_text終了
+                self.doc_renderer.render_main_text_end(output);
+            }
+            OutputSection::AfterText => self.doc_renderer.render_after_text_footer(output),
+            OutputSection::Bibliography => self.doc_renderer.render_bibliographical_footer(output),
+        }
+    }
+
+    /// 1行をHTMLに変換
+    fn render_line(&mut self, line: &DocumentLine) -> String {
+        self.node_renderer.line = line.line;
+        self.renderer
+            .render_document_line(line, &mut self.node_renderer, &mut self.block_manager)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::html::{Charset, CorrectionDisplay};
 
     #[test]
     fn test_render_text() {
@@ -223,4 +536,66 @@ mod tests {
         assert!(html.contains("<rb>漢字</rb>"));
         assert!(html.contains("<rt>かんじ</rt>"));
     }
+
+    #[test]
+    fn test_render_correction() {
+        let line = "灯［＃「灯」は底本では「燈」］と云ふ［＃「云ふ」はママ］";
+        let render = |display| {
+            let options = RenderOptions::new().with_correction_display(display);
+            HtmlRenderer::new(options).render_line(line)
+        };
+
+        assert_eq!(
+            render(CorrectionDisplay::Note),
+            "灯<span class=\"notes\">［＃「灯」は底本では「燈」］</span>\
+             と云ふ<span class=\"notes\">［＃「云ふ」はママ］</span>"
+        );
+        assert_eq!(render(CorrectionDisplay::Corrected), "灯と云ふ");
+        assert_eq!(render(CorrectionDisplay::Original), "燈と云ふ");
+        assert_eq!(
+            render(CorrectionDisplay::Both),
+            "<del class=\"teihon\">燈</del><ins class=\"correction\">灯</ins>\
+             と<span class=\"mama\" title=\"ママ\">云ふ</span>"
+        );
+    }
+
+    #[test]
+    fn test_render_unmappable_chars() {
+        // 「ㇰ」はJIS X 0213（1-06-78）にあるがShift_JISにはない
+        let input = "題名\n\nトㇰと😀\n";
+        let mut renderer = HtmlRenderer::new(RenderOptions::default());
+        let html = renderer.render(input);
+        assert!(html.contains("ト<img src=\"../../../gaiji/1-06/1-06-78.png\" alt=\"※(U+31F0)\" class=\"gaiji\" />と&#128512;"));
+        let unmappable: Vec<_> = renderer
+            .unmappable_chars()
+            .iter()
+            .map(|u| (u.ch, u.line, u.jis_code.as_deref()))
+            .collect();
+        assert_eq!(
+            unmappable,
+            vec![('ㇰ', 2, Some("1-06-78")), ('😀', 2, None)]
+        );
+
+        // UTF-8ではそのまま出力
+        let options = RenderOptions::new().with_charset(Charset::Utf8);
+        let mut renderer = HtmlRenderer::new(options);
+        assert!(renderer.render(input).contains("トㇰと😀"));
+        assert!(renderer.unmappable_chars().is_empty());
+    }
+
+    #[test]
+    fn test_render_stream_strict() {
+        let input = "題名\n\n本文\n😀\n";
+        let options = RenderOptions::new().with_strict(true);
+        let mut output = Vec::new();
+        let err = HtmlRenderer::new(options)
+            .render_stream(input.as_bytes(), &mut output)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "line 4: character 😀 (U+1F600) cannot be encoded in Shift_JIS"
+        );
+        assert!(output.is_empty());
+    }
 }
@@ -2,10 +2,11 @@
 //!
 //! ブロック要素のHTMLタグを生成する純粋関数を提供します。
 
-use aozora_core::node::{BlockParams, BlockType, MidashiLevel, MidashiStyle};
+use aozora_core::node::{BlockParams, BlockType, BreakKind, MidashiLevel, MidashiStyle};
 
 use super::presentation::{
-    midashi_combined_css_class, midashi_html_tag, style_css_class, style_html_tag,
+    break_before_value, break_css_class, midashi_combined_css_class, midashi_html_tag,
+    style_css_class, style_html_tag,
 };
 
 /// ブロック開始タグを生成
@@ -59,6 +60,15 @@ pub fn generate_block_end_tag(block_type: &BlockType, params: &BlockParams) -> S
     }
 }
 
+/// 改ページなどの区切りのタグを生成
+pub fn generate_break_tag(kind: BreakKind) -> String {
+    format!(
+        "<div class=\"{}\" style=\"break-before: {}\"></div>",
+        break_css_class(kind),
+        break_before_value(kind)
+    )
+}
+
 // 個別タグ生成関数
 
 fn generate_jisage_start(params: &BlockParams) -> String {
@@ -242,6 +252,18 @@ mod tests {
         assert_eq!(tag, "<span class=\"caption\">");
     }
 
+    #[test]
+    fn test_generate_break_tag() {
+        assert_eq!(
+            generate_break_tag(BreakKind::Page),
+            "<div class=\"kaipage\" style=\"break-before: page\"></div>"
+        );
+        assert_eq!(
+            generate_break_tag(BreakKind::Column),
+            "<div class=\"kaidan\" style=\"break-before: column\"></div>"
+        );
+    }
+
     #[test]
     fn test_generate_block_end() {
         let params = BlockParams::default();
@@ -0,0 +1,399 @@
+//! ページテンプレート
+//!
+//! 変換結果をユーザー指定のテンプレートに埋め込みます。
+//! 構文はMustacheの一部で、`{{名前}}` を値に置き換え、
+//! `{{#名前}}…{{/名前}}` は値が空でなければ（一覧なら要素ごとに）、
+//! `{{^名前}}…{{/名前}}` は値が空なら中身を出力します。
+//!
+//! ```text
+//! <!DOCTYPE html>
+//! <html lang="ja">
+//! <head>
+//! <meta charset="{{charset}}">
+//! <title>{{title}}</title>
+//! {{#css_files}}<link rel="stylesheet" href="{{href}}">{{/css_files}}
+//! </head>
+//! <body>
+//! <h1>{{work_title}}</h1>{{#author}}<p>{{author}}</p>{{/author}}
+//! <main>{{body}}</main>
+//! {{bibliography}}
+//! </body>
+//! </html>
+//! ```
+//!
+//! # 使用できる名前
+//!
+//! - `title` - 文書のタイトル（`<title>` と同じ）
+//! - `work_title`、`original_title`、`subtitle`、`original_subtitle`、
+//!   `author`、`translator`、`editor`、`henyaku` - ヘッダー情報
+//! - `charset` - 出力の文字コード名
+//! - `contributors` - 著者・翻訳者などの一覧（要素は `name`、`role`）。
+//!   `role` は `author`、`translator`、`editor`、`henyaku`、`revisor`、`supervisor`、`illustrator` のいずれか
+//! - `css_files` - CSSファイルの一覧（要素は `href`）
+//! - `metadata`、`toc`、`body`、`after_text`、`bibliography`、`notes` - 変換したHTML（[`HtmlParts`]）
+//! - `unconverted_gaiji` - 未変換外字の一覧（要素は `name`、`page_line`）
+//!
+//! HTML以外の値はエスケープ済みです。
+
+use std::io;
+
+use super::options::RenderOptions;
+use super::presentation::html_escape;
+use super::renderer::HtmlParts;
+
+/// 文書全体で使える名前と、その値が一覧の場合の要素の名前
+const NAMES: &[(&str, &[&str])] = &[
+    ("title", &[]),
+    ("work_title", &[]),
+    ("original_title", &[]),
+    ("subtitle", &[]),
+    ("original_subtitle", &[]),
+    ("author", &[]),
+    ("translator", &[]),
+    ("editor", &[]),
+    ("henyaku", &[]),
+    ("contributors", &["name", "role"]),
+    ("charset", &[]),
+    ("css_files", &["href"]),
+    ("metadata", &[]),
+    ("toc", &[]),
+    ("body", &[]),
+    ("after_text", &[]),
+    ("bibliography", &[]),
+    ("notes", &[]),
+    ("unconverted_gaiji", &["name", "page_line"]),
+];
+
+/// ページテンプレート
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+/// テンプレートの構成要素
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    /// そのまま出力する文字列
+    Text(String),
+    /// `{{名前}}`
+    Value(String),
+    /// `{{#名前}}…{{/名前}}`（`inverted` なら `{{^名前}}`）
+    Section {
+        name: String,
+        inverted: bool,
+        children: Vec<Part>,
+    },
+}
+
+/// テンプレートに渡す値
+enum Value {
+    /// エスケープ済みの文字列
+    Text(String),
+    /// 一覧（要素ごとの名前と値）
+    List(Vec<Vec<(&'static str, String)>>),
+}
+
+impl Template {
+    /// テンプレートを解析
+    ///
+    /// 閉じられていないセクションや未知の名前は [`io::ErrorKind::InvalidData`] のエラーになります。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use aozora2::html::{convert_parts, RenderOptions, Template};
+    ///
+    /// let template = Template::new("<h1>{{work_title}}</h1>{{#author}}<p>{{author}}</p>{{/author}}").unwrap();
+    /// let options = RenderOptions::default();
+    /// let parts = convert_parts("吾輩は猫である\n夏目漱石\n\n本文", &options);
+    /// assert_eq!(template.render(&parts, &options), "<h1>吾輩は猫である</h1><p>夏目漱石</p>");
+    /// assert!(Template::new("{{#author}}").is_err());
+    /// ```
+    pub fn new(source: &str) -> io::Result<Self> {
+        let mut rest = source;
+        let parts = parse(&mut rest, None)?;
+        Ok(Self { parts })
+    }
+
+    /// 変換結果をテンプレートに埋め込む
+    pub fn render(&self, parts: &HtmlParts, options: &RenderOptions) -> String {
+        let header = &parts.header;
+        let text =
+            |value: &Option<String>| Value::Text(html_escape(value.as_deref().unwrap_or("")));
+        let title = match &options.title {
+            Some(title) => html_escape(title),
+            None => html_escape(&header.html_title()),
+        };
+
+        let values = [
+            ("title", Value::Text(title)),
+            ("work_title", text(&header.title)),
+            ("original_title", text(&header.original_title)),
+            ("subtitle", text(&header.subtitle)),
+            ("original_subtitle", text(&header.original_subtitle)),
+            ("author", text(&header.author)),
+            ("translator", text(&header.translator)),
+            ("editor", text(&header.editor)),
+            ("henyaku", text(&header.henyaku)),
+            (
+                "contributors",
+                Value::List(
+                    header
+                        .contributors
+                        .iter()
+                        .map(|contributor| {
+                            vec![
+                                ("name", html_escape(&contributor.name)),
+                                ("role", contributor.role.name().to_string()),
+                            ]
+                        })
+                        .collect(),
+                ),
+            ),
+            ("charset", Value::Text(options.charset.name().to_string())),
+            (
+                "css_files",
+                Value::List(
+                    options
+                        .css_files
+                        .iter()
+                        .map(|css| vec![("href", html_escape(css))])
+                        .collect(),
+                ),
+            ),
+            ("metadata", Value::Text(parts.metadata.clone())),
+            ("toc", Value::Text(parts.toc.clone())),
+            ("body", Value::Text(parts.body.clone())),
+            ("after_text", Value::Text(parts.after_text.clone())),
+            ("bibliography", Value::Text(parts.bibliography.clone())),
+            ("notes", Value::Text(parts.notes.clone())),
+            (
+                "unconverted_gaiji",
+                Value::List(
+                    parts
+                        .unconverted_gaiji
+                        .iter()
+                        .map(|gaiji| {
+                            vec![
+                                ("name", html_escape(&gaiji.gaiji_name)),
+                                ("page_line", html_escape(&gaiji.page_line)),
+                            ]
+                        })
+                        .collect(),
+                ),
+            ),
+        ];
+
+        let mut output = String::new();
+        render_parts(&self.parts, &values, &[], &mut output);
+        output
+    }
+}
+
+/// `{{/名前}}` まで（`section` が `None` なら末尾まで）を解析
+fn parse(rest: &mut &str, section: Option<&str>) -> io::Result<Vec<Part>> {
+    let mut parts = Vec::new();
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            parts.push(Part::Text(rest[..start].to_string()));
+        }
+        let tag_len = rest[start..]
+            .find("}}")
+            .ok_or_else(|| invalid_template(format!("unclosed placeholder: {}", &rest[start..])))?;
+        let tag = rest[start + 2..start + tag_len].trim();
+        *rest = &rest[start + tag_len + 2..];
+
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = name.trim();
+            return if section == Some(name) {
+                Ok(parts)
+            } else {
+                Err(invalid_template(format!(
+                    "unexpected section end: {{{{/{name}}}}}"
+                )))
+            };
+        }
+
+        let (name, inverted) = match tag.strip_prefix('#') {
+            Some(name) => (name.trim(), Some(false)),
+            None => match tag.strip_prefix('^') {
+                Some(name) => (name.trim(), Some(true)),
+                None => (tag, None),
+            },
+        };
+        if !is_known_name(name, section) {
+            return Err(invalid_template(format!(
+                "unknown placeholder: {{{{{name}}}}}"
+            )));
+        }
+
+        match inverted {
+            Some(inverted) => {
+                // 一覧のセクションの中では要素の名前を使える
+                let children = parse(rest, Some(name))?;
+                parts.push(Part::Section {
+                    name: name.to_string(),
+                    inverted,
+                    children,
+                });
+            }
+            None => parts.push(Part::Value(name.to_string())),
+        }
+    }
+
+    if let Some(name) = section {
+        return Err(invalid_template(format!(
+            "unclosed section: {{{{#{name}}}}}"
+        )));
+    }
+    if !rest.is_empty() {
+        parts.push(Part::Text(rest.to_string()));
+        *rest = "";
+    }
+    Ok(parts)
+}
+
+/// 名前が使えるかどうか（`section` は囲んでいるセクション）
+fn is_known_name(name: &str, section: Option<&str>) -> bool {
+    NAMES
+        .iter()
+        .any(|(known, items)| *known == name || (section == Some(*known) && items.contains(&name)))
+}
+
+/// 構成要素を出力（`item` は一覧のセクションで処理中の要素）
+fn render_parts(
+    parts: &[Part],
+    values: &[(&str, Value)],
+    item: &[(&str, String)],
+    output: &mut String,
+) {
+    for part in parts {
+        match part {
+            Part::Text(text) => output.push_str(text),
+            Part::Value(name) => {
+                if let Some((_, value)) = item.iter().find(|(n, _)| n == name) {
+                    output.push_str(value);
+                } else if let Some(Value::Text(value)) = lookup(values, name) {
+                    output.push_str(value);
+                }
+            }
+            Part::Section {
+                name,
+                inverted,
+                children,
+            } => match lookup(values, name) {
+                Some(Value::List(list)) if !inverted => {
+                    for item in list {
+                        render_parts(children, values, item, output);
+                    }
+                }
+                Some(Value::List(list)) if list.is_empty() => {
+                    render_parts(children, values, item, output);
+                }
+                Some(Value::Text(text)) if text.is_empty() == *inverted => {
+                    render_parts(children, values, item, output);
+                }
+                _ => {}
+            },
+        }
+    }
+}
+
+/// 名前の値を探す
+fn lookup<'a>(values: &'a [(&str, Value)], name: &str) -> Option<&'a Value> {
+    values.iter().find(|(n, _)| *n == name).map(|(_, v)| v)
+}
+
+/// テンプレートの誤りのエラー
+fn invalid_template(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("template: {message}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::html::{convert_parts, UnconvertedGaiji};
+
+    #[test]
+    fn test_render_values_and_sections() {
+        let template = Template::new(
+            "<title>{{title}}</title>{{#subtitle}}<h2>{{subtitle}}</h2>{{/subtitle}}\
+             {{^translator}}[no translator]{{/translator}}\
+             {{#css_files}}<link href=\"{{href}}\">{{/css_files}}|{{body}}",
+        )
+        .unwrap();
+        let options = RenderOptions::new().with_css_files(vec!["a.css".into(), "b&c.css".into()]);
+        let parts = convert_parts("題名\n著者\n\n本文", &options);
+        assert_eq!(
+            template.render(&parts, &options),
+            "<title>著者 題名</title>[no translator]\
+             <link href=\"a.css\"><link href=\"b&amp;c.css\">|本文<br />\r\n"
+        );
+    }
+
+    #[test]
+    fn test_render_escapes_header_title() {
+        let template = Template::new("<title>{{title}}</title>").unwrap();
+        let options = RenderOptions::default();
+        let parts = convert_parts("A&B <1>\n著者\n\n本文", &options);
+        assert_eq!(
+            template.render(&parts, &options),
+            "<title>著者 A&amp;B &lt;1&gt;</title>"
+        );
+    }
+
+    #[test]
+    fn test_render_contributors() {
+        let template =
+            Template::new("{{#contributors}}<li class=\"{{role}}\">{{name}}</li>{{/contributors}}")
+                .unwrap();
+        let options = RenderOptions::default();
+        let parts = convert_parts("題名\n甲野一郎、乙野花子\n丙野次郎訳\n\n本文", &options);
+        assert_eq!(
+            template.render(&parts, &options),
+            "<li class=\"author\">甲野一郎</li><li class=\"author\">乙野花子</li>\
+             <li class=\"translator\">丙野次郎</li>"
+        );
+    }
+
+    #[test]
+    fn test_render_unconverted_gaiji() {
+        let template = Template::new(
+            "{{#unconverted_gaiji}}<li>{{name}} ({{page_line}})</li>{{/unconverted_gaiji}}",
+        )
+        .unwrap();
+        let parts = HtmlParts {
+            unconverted_gaiji: vec![UnconvertedGaiji {
+                gaiji_name: "「口＋世」".to_string(),
+                page_line: "152-12".to_string(),
+            }],
+            ..HtmlParts::default()
+        };
+        assert_eq!(
+            template.render(&parts, &RenderOptions::default()),
+            "<li>「口＋世」 (152-12)</li>"
+        );
+    }
+
+    #[test]
+    fn test_invalid_template() {
+        let message = |source| Template::new(source).unwrap_err().to_string();
+        assert_eq!(
+            message("{{#body}}"),
+            "template: unclosed section: {{#body}}"
+        );
+        assert_eq!(
+            message("{{/body}}"),
+            "template: unexpected section end: {{/body}}"
+        );
+        assert_eq!(
+            message("{{tilte}}"),
+            "template: unknown placeholder: {{tilte}}"
+        );
+        assert_eq!(
+            message("{{name}}"),
+            "template: unknown placeholder: {{name}}"
+        );
+        assert_eq!(message("{{body"), "template: unclosed placeholder: {{body");
+    }
+}
@@ -6,6 +6,8 @@
 //!
 //! - `strip` - プレーンテキストへの変換（注記・ルビを除去）
 //! - `html` - HTMLへの変換
+//! - `format` - 表記の整形（正規形へのそろえ）
+//! - `cli` - CLIの共通部品（入出力、引数）
 //!
 //! # 使用例
 //!
@@ -17,6 +19,8 @@
 //! assert_eq!(plain, "吾輩は猫である");
 //! ```
 
+pub mod cli;
+pub mod format;
 pub mod html;
 pub mod strip;
 
@@ -4,6 +4,7 @@
 
 use clap::{Parser, Subcommand};
 use std::io;
+use std::process::ExitCode;
 
 mod commands;
 
@@ -22,12 +23,30 @@ enum Commands {
     Strip(commands::strip::Args),
     /// HTMLに変換
     Html(commands::html::Args),
+    /// 注記を検査（エラーがあれば終了コード1）
+    Check(commands::check::Args),
+    /// 解析結果をJSONで出力
+    Ast(commands::ast::Args),
+    /// 表記を正規形に整形（--check で整形が必要なら終了コード1）
+    Fmt(commands::fmt::Args),
 }
 
-fn main() -> io::Result<()> {
+fn main() -> io::Result<ExitCode> {
     let cli = Cli::parse();
     match cli.command {
-        Commands::Strip(args) => commands::strip::run(args),
-        Commands::Html(args) => commands::html::run(args),
+        Commands::Strip(args) => commands::strip::run(args)?,
+        Commands::Html(args) => commands::html::run(args)?,
+        Commands::Ast(args) => commands::ast::run(args)?,
+        Commands::Check(args) => {
+            if !commands::check::run(args)? {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Commands::Fmt(args) => {
+            if !commands::fmt::run(args)? {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
     }
+    Ok(ExitCode::SUCCESS)
 }
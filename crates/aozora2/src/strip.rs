@@ -2,12 +2,158 @@
 //!
 //! 青空文庫形式のテキストからルビ・注記を除去してプレーンテキストに変換します。
 
+use std::io::{self, BufRead, Write};
+
 use aozora_core::accent::convert_accent;
-use aozora_core::document;
-use aozora_core::encoding;
+use aozora_core::document::{self, AozoraDocument, Section, SectionScanner};
+use aozora_core::encoding::{self, TextEncoding};
 use aozora_core::gaiji::convert_gaiji;
-use aozora_core::token::Token;
-use aozora_core::tokenizer::Tokenizer;
+use aozora_core::node::BreakKind;
+use aozora_core::outline::OutlineEntry;
+use aozora_core::token::TokenRef;
+use aozora_core::tokenizer::tokenize_ref;
+use clap::ValueEnum;
+use encoding_rs::SHIFT_JIS;
+
+/// UTF-8 BOM
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+
+/// Shift_JISで表せない文字の代わりに出力するゲタ記号
+const GETA: &str = "〓";
+
+/// 出力エンコーディング
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputEncoding {
+    /// UTF-8（既定）
+    #[default]
+    #[value(name = "utf-8", alias = "utf8")]
+    Utf8,
+    /// BOM付きUTF-8
+    #[value(name = "utf-8-bom", alias = "utf8-bom")]
+    Utf8Bom,
+    /// Shift_JIS（CP932の拡張文字を含む）
+    #[value(name = "shift_jis", alias = "sjis", alias = "cp932")]
+    ShiftJis,
+}
+
+impl OutputEncoding {
+    /// 名前から変換（`utf-8`、`utf-8-bom`、`shift_jis` または `cp932`）
+    pub fn from_name(name: &str) -> Option<Self> {
+        ValueEnum::from_str(name, true).ok()
+    }
+}
+
+/// 改行コード
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Newline {
+    /// LF（既定）
+    #[default]
+    Lf,
+    /// CRLF
+    #[value(name = "crlf")]
+    CrLf,
+}
+
+impl Newline {
+    /// 名前から変換（`lf` または `crlf`）
+    pub fn from_name(name: &str) -> Option<Self> {
+        ValueEnum::from_str(name, true).ok()
+    }
+
+    /// 改行文字列
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Newline::Lf => "\n",
+            Newline::CrLf => "\r\n",
+        }
+    }
+}
+
+/// Shift_JISで出力するときに表せない文字の扱い
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Unmappable {
+    /// エラーにする
+    Error,
+    /// ゲタ記号 `〓` にする（既定）
+    #[default]
+    Geta,
+    /// 外字注記 `※［＃…］` にする
+    ///
+    /// 外字注記から変換した文字は元の注記に、アクセント分解から変換した文字は
+    /// 元の `〔…〕` に戻します。それ以外の文字は `※［＃U+XXXX］` にします。
+    Notation,
+}
+
+impl Unmappable {
+    /// 名前から変換（`error`、`geta` または `notation`）
+    pub fn from_name(name: &str) -> Option<Self> {
+        ValueEnum::from_str(name, true).ok()
+    }
+}
+
+/// プレーンテキスト変換オプション
+#[derive(Debug, Clone, Default)]
+pub struct StripOptions {
+    /// 改ページ・改丁・改見開き・改段の注記の代わりに出力する文字列
+    ///
+    /// `None` の場合は他の注記と同じく除去します。
+    pub break_separator: Option<String>,
+    /// 出力エンコーディング（[`convert_to_bytes`] と [`convert_stream`] で使用）
+    pub encoding: OutputEncoding,
+    /// 改行コード
+    pub newline: Newline,
+    /// Shift_JISで表せない文字の扱い
+    pub unmappable: Unmappable,
+    /// 本文の前に見出しの目次を出力
+    ///
+    /// 目次は下位の見出しほど全角空白で字下げし、空行をはさんで本文を続けます。
+    /// [`convert_stream`] では入力全体を読み込んでから変換します。
+    pub toc: bool,
+}
+
+impl StripOptions {
+    /// 新しいオプションを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 改ページなどの区切りの代わりに出力する文字列を設定
+    pub fn with_break_separator(mut self, separator: impl Into<String>) -> Self {
+        self.break_separator = Some(separator.into());
+        self
+    }
+
+    /// 出力エンコーディングを設定
+    pub fn with_encoding(mut self, encoding: OutputEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// 改行コードを設定
+    pub fn with_newline(mut self, newline: Newline) -> Self {
+        self.newline = newline;
+        self
+    }
+
+    /// Shift_JISで表せない文字の扱いを設定
+    pub fn with_unmappable(mut self, unmappable: Unmappable) -> Self {
+        self.unmappable = unmappable;
+        self
+    }
+
+    /// 見出しの目次を出力するかを設定
+    pub fn with_toc(mut self, toc: bool) -> Self {
+        self.toc = toc;
+        self
+    }
+
+    /// 変換した文字の代わりに元の注記を残すかどうか
+    fn keeps_notation(&self, converted: &str) -> bool {
+        self.encoding == OutputEncoding::ShiftJis
+            && self.unmappable == Unmappable::Notation
+            && SHIFT_JIS.encode(converted).2
+    }
+}
 
 /// 青空文庫形式のバイト列をプレーンテキストに変換
 ///
@@ -22,11 +168,50 @@ use aozora_core::tokenizer::Tokenizer;
 /// assert_eq!(plain, "本文です\n");
 /// ```
 pub fn convert(input: &[u8]) -> String {
+    convert_with_options(input, &StripOptions::default())
+}
+
+/// オプションを指定してプレーンテキストに変換
+///
+/// 改行コードはオプションに従います。出力エンコーディングを適用するには
+/// [`convert_to_bytes`] を使ってください。
+///
+/// # Examples
+///
+/// ```
+/// use aozora2::strip::{convert_with_options, StripOptions};
+///
+/// let input = "タイトル\n\n一章\n［＃改ページ］\n二章\n";
+/// let options = StripOptions::new().with_break_separator("----");
+/// assert_eq!(convert_with_options(input.as_bytes(), &options), "一章\n----\n二章\n");
+///
+/// let input = "タイトル\n\n上［＃「上」は大見出し］\n一［＃「一」は中見出し］\n本文\n";
+/// let options = StripOptions::new().with_toc(true);
+/// assert_eq!(
+///     convert_with_options(input.as_bytes(), &options),
+///     "上\n　一\n\n上\n一\n本文\n"
+/// );
+/// ```
+pub fn convert_with_options(input: &[u8], options: &StripOptions) -> String {
     let text = encoding::decode_to_utf8(input);
     let lines: Vec<&str> = text.lines().collect();
     let body_lines = document::extract_body_lines(&lines);
 
-    let converted: Vec<String> = body_lines.iter().map(|line| convert_line(line)).collect();
+    let mut converted: Vec<String> = body_lines
+        .iter()
+        .map(|line| convert_line_with_options(line, options))
+        .collect();
+
+    if options.toc {
+        let mut toc = Vec::new();
+        push_toc_lines(&AozoraDocument::parse(&text).outline(), 0, &mut toc);
+        if !toc.is_empty() {
+            // 目次と本文の間に空行を入れる（本文冒頭の空行は下で削除）
+            let start = converted.iter().position(|s| !s.is_empty()).unwrap_or(0);
+            toc.push(String::new());
+            converted.splice(..start, toc);
+        }
+    }
 
     // 冒頭と末尾の空行を削除
     let start = converted.iter().position(|s| !s.is_empty()).unwrap_or(0);
@@ -39,10 +224,135 @@ pub fn convert(input: &[u8]) -> String {
     if start >= end {
         String::new()
     } else {
-        converted[start..end].join("\n") + "\n"
+        let newline = options.newline.as_str();
+        converted[start..end].join(newline) + newline
+    }
+}
+
+/// オプションを指定してプレーンテキストに変換し、出力エンコーディングのバイト列にする
+///
+/// Shift_JISで表せない文字を [`Unmappable::Error`] で変換した場合は
+/// [`io::ErrorKind::InvalidData`] のエラーを返します。
+///
+/// # Examples
+///
+/// ```
+/// use aozora2::strip::{convert_to_bytes, Newline, OutputEncoding, StripOptions};
+///
+/// let input = "タイトル\n\n本文です\n";
+/// let options = StripOptions::new()
+///     .with_encoding(OutputEncoding::ShiftJis)
+///     .with_newline(Newline::CrLf);
+/// let bytes = convert_to_bytes(input.as_bytes(), &options).unwrap();
+/// assert_eq!(bytes, encoding_rs::SHIFT_JIS.encode("本文です\r\n").0.as_ref());
+/// ```
+pub fn convert_to_bytes(input: &[u8], options: &StripOptions) -> io::Result<Vec<u8>> {
+    let text = convert_with_options(input, options);
+    let mut output = Vec::with_capacity(text.len() + UTF8_BOM.len());
+    if options.encoding == OutputEncoding::Utf8Bom {
+        output.extend_from_slice(UTF8_BOM);
+    }
+    encode_into(&text, options, &mut output)?;
+    Ok(output)
+}
+
+/// 目次の行を追加（下位の見出しほど全角空白で字下げ）
+fn push_toc_lines(entries: &[OutlineEntry], depth: usize, lines: &mut Vec<String>) {
+    for entry in entries {
+        lines.push(format!("{}{}", "　".repeat(depth), entry.text));
+        push_toc_lines(&entry.children, depth + 1, lines);
     }
 }
 
+/// 文字列を出力エンコーディングで追記（BOMは含まない）
+fn encode_into(text: &str, options: &StripOptions, output: &mut Vec<u8>) -> io::Result<()> {
+    let encoding = match options.encoding {
+        OutputEncoding::Utf8 | OutputEncoding::Utf8Bom => TextEncoding::Utf8,
+        OutputEncoding::ShiftJis => TextEncoding::ShiftJis,
+    };
+    encoding::encode(text, encoding, output, |c| match options.unmappable {
+        Unmappable::Error => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "character {c} (U+{:04X}) cannot be encoded in Shift_JIS",
+                u32::from(c)
+            ),
+        )),
+        Unmappable::Geta => Ok(GETA.to_string()),
+        Unmappable::Notation => Ok(format!("※［＃U+{:04X}］", u32::from(c))),
+    })
+}
+
+/// 青空文庫形式の入力を読みながらプレーンテキストを書き出す
+///
+/// [`convert_to_bytes`] と同じ変換を1行ずつ行い、入力全体を読み込まずに出力します。
+/// ただし [`StripOptions::toc`] を指定した場合は入力全体を読み込みます。
+/// 入力のエンコーディングの判定は [`encoding::decode_lines`] を参照してください。
+///
+/// # Examples
+///
+/// ```
+/// use aozora2::strip::{convert_stream, StripOptions};
+///
+/// let input = "タイトル\n著者\n\n\n本文です\n\n底本：青空文庫";
+/// let mut output = Vec::new();
+/// convert_stream(input.as_bytes(), &mut output, &StripOptions::default()).unwrap();
+/// assert_eq!(output, "本文です\n".as_bytes());
+/// ```
+pub fn convert_stream<R: BufRead, W: Write>(
+    mut reader: R,
+    mut writer: W,
+    options: &StripOptions,
+) -> io::Result<()> {
+    if options.toc {
+        let mut input = Vec::new();
+        reader.read_to_end(&mut input)?;
+        writer.write_all(&convert_to_bytes(&input, options)?)?;
+        return writer.flush();
+    }
+
+    let mut scanner = SectionScanner::new();
+    let mut started = false;
+    // 出力を保留している空行の数（末尾の空行は出力しない）
+    let mut pending_blank_lines = 0;
+    let newline = options.newline.as_str().as_bytes();
+    let mut encoded = Vec::new();
+
+    if options.encoding == OutputEncoding::Utf8Bom {
+        writer.write_all(UTF8_BOM)?;
+    }
+
+    for line in encoding::decode_lines(reader) {
+        let line = line?;
+        match scanner.next_line(&line) {
+            Section::Body => {}
+            // 本文より後は読まない
+            Section::AfterText | Section::Bibliography => break,
+            _ => continue,
+        }
+
+        let converted = convert_line_with_options(&line, options);
+        if converted.is_empty() {
+            // 冒頭の空行は出力しない
+            if started {
+                pending_blank_lines += 1;
+            }
+            continue;
+        }
+
+        for _ in 0..std::mem::take(&mut pending_blank_lines) {
+            writer.write_all(newline)?;
+        }
+        encoded.clear();
+        encode_into(&converted, options, &mut encoded)?;
+        writer.write_all(&encoded)?;
+        writer.write_all(newline)?;
+        started = true;
+    }
+
+    writer.flush()
+}
+
 /// 青空文庫形式の文字列をプレーンテキストに変換（本文抽出なし）
 ///
 /// 前付け・後付けの除去を行わず、入力全体を変換する。
@@ -55,36 +365,70 @@ pub fn convert(input: &[u8]) -> String {
 /// assert_eq!(plain, "吾輩は猫である");
 /// ```
 pub fn convert_line(input: &str) -> String {
-    let mut tokenizer = Tokenizer::new(input);
-    let tokens = tokenizer.tokenize();
-    extract(&tokens)
+    convert_line_with_options(input, &StripOptions::default())
 }
 
-/// トークン列をプレーンテキストに変換
-fn extract(tokens: &[Token]) -> String {
-    tokens.iter().map(extract_token).collect()
+/// オプションを指定して1行をプレーンテキストに変換（本文抽出なし）
+pub fn convert_line_with_options(input: &str, options: &StripOptions) -> String {
+    let mut output = String::with_capacity(input.len());
+    extract(&tokenize_ref(input), &mut output, options);
+    output
 }
 
-/// 単一トークンからテキストを抽出
-fn extract_token(token: &Token) -> String {
+/// トークン列をプレーンテキストに変換して追記
+fn extract(tokens: &[TokenRef<'_>], output: &mut String, options: &StripOptions) {
+    for token in tokens {
+        extract_token(token, output, options);
+    }
+}
+
+/// 単一トークンからテキストを抽出して追記
+fn extract_token(token: &TokenRef<'_>, output: &mut String, options: &StripOptions) {
     match token {
         // テキスト: そのまま出力
-        Token::Text(s) => s.clone(),
+        TokenRef::Text(s) => output.push_str(s),
 
         // 暗黙ルビ: 削除（親文字は直前のTextに含まれる）
-        Token::Ruby { .. } => String::new(),
+        TokenRef::Ruby { .. } => {}
 
         // 明示ルビ: 親文字部分のみ抽出
-        Token::PrefixedRuby { base_children, .. } => extract(base_children),
+        TokenRef::PrefixedRuby { base_children, .. } => extract(base_children, output, options),
+
+        // 改ページなど: 区切りが指定されていれば出力
+        TokenRef::Command { content } if BreakKind::from_command(content.trim()).is_some() => {
+            if let Some(separator) = &options.break_separator {
+                output.push_str(separator);
+            }
+        }
 
         // コマンド: 削除
-        Token::Command { .. } => String::new(),
+        TokenRef::Command { .. } => {}
 
         // 外字: Unicode文字列に変換
-        Token::Gaiji { description } => convert_gaiji(description),
+        TokenRef::Gaiji { description } => {
+            let converted = convert_gaiji(description);
+            if options.keeps_notation(&converted) {
+                output.push_str("※［＃");
+                output.push_str(description);
+                output.push('］');
+            } else {
+                output.push_str(&converted);
+            }
+        }
 
         // アクセント: 内容を抽出してアクセント変換
-        Token::Accent { children } => convert_accent(&extract(children)),
+        TokenRef::Accent { children } => {
+            let mut content = String::new();
+            extract(children, &mut content, options);
+            let converted = convert_accent(&content);
+            if options.keeps_notation(&converted) {
+                output.push('〔');
+                output.push_str(&content);
+                output.push('〕');
+            } else {
+                output.push_str(&converted);
+            }
+        }
     }
 }
 
@@ -136,4 +480,115 @@ mod tests {
         let plain = convert(input.as_bytes());
         assert_eq!(plain, "本文です\n");
     }
+
+    #[test]
+    fn test_convert_stream_matches_convert() {
+        let input = "タイトル\r\n著者\r\n\r\n\
+                     -------\r\n《》：ルビ\r\n-------\r\n\
+                     \r\n吾輩《わがはい》は猫である\r\n\r\n\r\n\
+                     ｜名前《なまえ》はまだ無い\r\n\r\n\
+                     ［＃本文終わり］\r\n後書き\r\n底本：青空文庫\r\n";
+        let mut output = Vec::new();
+        convert_stream(input.as_bytes(), &mut output, &StripOptions::default()).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            convert(input.as_bytes())
+        );
+    }
+
+    #[test]
+    fn test_break_separator() {
+        assert_eq!(convert_line("［＃改ページ］"), "");
+        let options = StripOptions::new().with_break_separator("＊");
+        assert_eq!(convert_line_with_options("［＃改丁］", &options), "＊");
+        assert_eq!(
+            convert_line_with_options("上［＃改段］下", &options),
+            "上＊下"
+        );
+        assert_eq!(
+            convert_line_with_options("［＃「改ページ」に傍点］", &options),
+            ""
+        );
+
+        let input = "題名\n\n一\n［＃改見開き］\n二\n";
+        let mut output = Vec::new();
+        convert_stream(input.as_bytes(), &mut output, &options).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "一\n＊\n二\n");
+    }
+
+    #[test]
+    fn test_output_newline_and_bom() {
+        let input = "題名\n\n一行目\n\n二行目\n";
+        let options = StripOptions::new()
+            .with_encoding(OutputEncoding::Utf8Bom)
+            .with_newline(Newline::CrLf);
+        let bytes = convert_to_bytes(input.as_bytes(), &options).unwrap();
+        assert_eq!(bytes, "\u{FEFF}一行目\r\n\r\n二行目\r\n".as_bytes());
+
+        let mut output = Vec::new();
+        convert_stream(input.as_bytes(), &mut output, &options).unwrap();
+        assert_eq!(output, bytes);
+    }
+
+    #[test]
+    fn test_output_shift_jis_unmappable() {
+        let input = "題名\n\n※［＃「木＋吉」、U+6AB5］と〔cafe'〕と😀\n";
+        let shift_jis = StripOptions::new().with_encoding(OutputEncoding::ShiftJis);
+        let decode = |bytes: Vec<u8>| SHIFT_JIS.decode(&bytes).0.into_owned();
+
+        let geta = convert_to_bytes(input.as_bytes(), &shift_jis).unwrap();
+        assert_eq!(decode(geta), "〓とcaf〓と〓\n");
+
+        let options = shift_jis.clone().with_unmappable(Unmappable::Notation);
+        let notation = convert_to_bytes(input.as_bytes(), &options).unwrap();
+        assert_eq!(
+            decode(notation),
+            "※［＃「木＋吉」、U+6AB5］と〔cafe'〕と※［＃U+1F600］\n"
+        );
+
+        let options = shift_jis.with_unmappable(Unmappable::Error);
+        let err = convert_to_bytes(input.as_bytes(), &options).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let mut output = Vec::new();
+        assert!(convert_stream(input.as_bytes(), &mut output, &options).is_err());
+    }
+
+    #[test]
+    fn test_output_options_from_name() {
+        assert_eq!(
+            OutputEncoding::from_name("CP932"),
+            Some(OutputEncoding::ShiftJis)
+        );
+        assert_eq!(Newline::from_name("crlf"), Some(Newline::CrLf));
+        assert_eq!(
+            Unmappable::from_name("notation"),
+            Some(Unmappable::Notation)
+        );
+        assert_eq!(Unmappable::from_name("drop"), None);
+    }
+
+    #[test]
+    fn test_toc() {
+        let input = "タイトル\n著者\n\n\n\
+                     ［＃大見出し］第一章［＃大見出し終わり］\n\
+                     ［＃３字下げ］一［＃「一」は中見出し］\n本文\n\
+                     ［＃３字下げ］二［＃「二」は中見出し］\n\
+                     １［＃「１」は小見出し］\n底本：青空文庫\n";
+        let options = StripOptions::new()
+            .with_toc(true)
+            .with_newline(Newline::CrLf);
+        let expected = "第一章\r\n　一\r\n　二\r\n　　１\r\n\r\n\
+                        第一章\r\n一\r\n本文\r\n二\r\n１\r\n";
+        assert_eq!(convert_with_options(input.as_bytes(), &options), expected);
+
+        let mut output = Vec::new();
+        convert_stream(input.as_bytes(), &mut output, &options).unwrap();
+        assert_eq!(output, expected.as_bytes());
+
+        // 見出しがなければ目次は出力しない
+        assert_eq!(
+            convert_with_options("題名\n\n本文\n".as_bytes(), &options),
+            "本文\r\n"
+        );
+    }
 }
@@ -4,10 +4,10 @@
 //! 新規ユーザーは `aozora2` コマンドの使用を推奨します。
 
 use std::fs;
-use std::io::{self, Read, Write};
+use std::io::{self, Write};
 use std::path::PathBuf;
 
-use aozora2::aozora_core::zip::{is_zip_file, read_first_txt_from_zip};
+use aozora2::cli::{read_input, StripOutputArgs};
 use aozora2::strip;
 use clap::Parser;
 
@@ -26,47 +26,24 @@ struct Args {
     /// 入力をZIPファイルとして扱う
     #[arg(short, long)]
     zip: bool,
+
+    #[command(flatten)]
+    output_format: StripOutputArgs,
 }
 
 fn main() -> io::Result<()> {
     let args = Args::parse();
 
-    // 入力読み込み
-    let bytes = if args.zip {
-        let path = args.input.as_ref().ok_or_else(|| {
-            io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "ZIP mode requires an input file",
-            )
-        })?;
-        read_first_txt_from_zip(path)?
-    } else {
-        match &args.input {
-            Some(path) => {
-                let bytes = fs::read(path)?;
-                if is_zip_file(&bytes) {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidInput,
-                        "input appears to be a ZIP file; use --zip option",
-                    ));
-                }
-                bytes
-            }
-            None => {
-                let mut buf = Vec::new();
-                io::stdin().read_to_end(&mut buf)?;
-                buf
-            }
-        }
-    };
+    let options = args.output_format.options();
+    let bytes = read_input(args.input.as_deref(), args.zip)?;
 
     // 変換
-    let output = strip::convert(&bytes);
+    let output = strip::convert_to_bytes(&bytes, &options)?;
 
     // 出力
     match &args.output {
         Some(path) => fs::write(path, &output)?,
-        None => io::stdout().write_all(output.as_bytes())?,
+        None => io::stdout().write_all(&output)?,
     }
 
     Ok(())